@@ -19,6 +19,14 @@
 //!
 //! Run this example with: `cargo run --example basic_usage`
 
+#![allow(
+    clippy::uninlined_format_args,
+    clippy::branches_sharing_code,
+    clippy::if_same_then_else,
+    clippy::cast_lossless,
+    clippy::cast_precision_loss
+)]
+
 use rollpoly::roll;
 
 fn main() {
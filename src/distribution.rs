@@ -0,0 +1,1204 @@
+// Copyright 2025 Ray Krueger
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exact probability distributions for dice expressions
+//!
+//! This module computes the probability of every possible total of a parsed
+//! expression without rolling any dice. Sums are built by convolution, keep and
+//! drop modifiers use order statistics, and success counting sums each die's
+//! net successes.
+
+#![allow(clippy::cast_precision_loss)] // Dice counts and sides are far below 2^52
+#![allow(clippy::redundant_pub_crate)] // Marks the helpers that are not re-exported
+
+use std::collections::BTreeMap;
+
+use crate::evaluator::{binary_total, resolve_dynamic_dice, rounded_quotient};
+use crate::limits::{DiceBudget, RollLimits};
+use crate::parser::{
+    BinaryOp, Condition, DiceExpression, DieType, DynamicDie, Function, Modifier, RerollType,
+};
+
+/// Largest number of outcome pairs combined in a single step before giving up
+///
+/// Keeps pathological expressions such as `25d1000 * 25d1000` from running for
+/// minutes; callers fall back to sampling instead.
+const MAX_COMBINATIONS: usize = 1 << 20;
+
+/// Largest number of dice terms a term with rolled parts may resolve to
+///
+/// Each one needs its own distribution, so `(1d100)d(1d100)` falls back to sampling.
+const MAX_DYNAMIC_TERMS: usize = 1 << 10;
+
+/// Exact probability distribution of the total of a dice expression
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{distribution, parse};
+///
+/// let expression = parse("2d6").unwrap();
+/// let distribution = distribution(&expression).unwrap();
+///
+/// assert_eq!(distribution.min(), 2);
+/// assert_eq!(distribution.max(), 12);
+/// assert!((distribution.probability(7) - 6.0 / 36.0).abs() < 1e-12);
+/// assert!((distribution.mean() - 7.0).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    probabilities: BTreeMap<i32, f64>,
+}
+
+impl Distribution {
+    /// A distribution that always produces `value`
+    fn constant(value: i32) -> Self {
+        Self {
+            probabilities: BTreeMap::from([(value, 1.0)]),
+        }
+    }
+
+    /// Builds a distribution from (total, probability) pairs, merging duplicates
+    fn from_pairs(pairs: impl IntoIterator<Item = (i32, f64)>) -> Self {
+        let mut probabilities = BTreeMap::new();
+        for (total, probability) in pairs {
+            if probability > 0.0 {
+                *probabilities.entry(total).or_insert(0.0) += probability;
+            }
+        }
+        Self { probabilities }
+    }
+
+    /// Probability that the expression totals exactly `total`
+    #[must_use]
+    pub fn probability(&self, total: i32) -> f64 {
+        self.probabilities.get(&total).copied().unwrap_or(0.0)
+    }
+
+    /// Probability that the expression totals at least `total`
+    #[must_use]
+    pub fn probability_at_least(&self, total: i32) -> f64 {
+        self.probabilities.range(total..).map(|(_, p)| p).sum()
+    }
+
+    /// Every possible total with its probability, in ascending order of total
+    pub fn iter(&self) -> impl Iterator<Item = (i32, f64)> + '_ {
+        self.probabilities.iter().map(|(&total, &p)| (total, p))
+    }
+
+    /// Number of distinct possible totals
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    /// Whether the distribution has no possible totals
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.probabilities.is_empty()
+    }
+
+    /// Smallest possible total
+    #[must_use]
+    pub fn min(&self) -> i32 {
+        self.probabilities
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Largest possible total
+    #[must_use]
+    pub fn max(&self) -> i32 {
+        self.probabilities
+            .keys()
+            .next_back()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Expected value of the total
+    #[must_use]
+    pub fn mean(&self) -> f64 {
+        self.iter().map(|(total, p)| f64::from(total) * p).sum()
+    }
+
+    /// Smallest total whose cumulative probability reaches one half
+    #[must_use]
+    pub fn median(&self) -> i32 {
+        let mut cumulative = 0.0;
+        for (total, p) in self.iter() {
+            cumulative += p;
+            // Tolerate rounding error in the accumulated probabilities
+            if cumulative >= 0.5 - 1e-12 {
+                return total;
+            }
+        }
+        self.max()
+    }
+
+    /// Standard deviation of the total
+    #[must_use]
+    pub fn standard_deviation(&self) -> f64 {
+        let mean = self.mean();
+        self.iter()
+            .map(|(total, p)| (f64::from(total) - mean).powi(2) * p)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Distribution of `op(a, b)` for independent `a` from `self` and `b` from `other`
+    ///
+    /// Returns `None` if the operation fails for any pair of totals (overflow or
+    /// division by zero) or if there are too many pairs to combine.
+    fn combine(&self, other: &Self, op: impl Fn(i32, i32) -> Option<i32>) -> Option<Self> {
+        if self.len().saturating_mul(other.len()) > MAX_COMBINATIONS {
+            return None;
+        }
+
+        let mut probabilities = BTreeMap::new();
+        for (a, pa) in self.iter() {
+            for (b, pb) in other.iter() {
+                *probabilities.entry(op(a, b)?).or_insert(0.0) += pa * pb;
+            }
+        }
+        Some(Self { probabilities })
+    }
+
+    /// Distribution of the sum of `times` independent copies of `self`
+    fn sum_of(&self, times: usize) -> Option<Self> {
+        let mut total = Self::constant(0);
+        for _ in 0..times {
+            total = total.combine(self, i32::checked_add)?;
+        }
+        Some(total)
+    }
+
+    /// Distribution that follows `self` with probability `p` and `other` otherwise
+    fn mix(&self, other: &Self, p: f64) -> Self {
+        Self::from_pairs(
+            self.iter()
+                .map(|(total, q)| (total, q * p))
+                .chain(other.iter().map(|(total, q)| (total, q * (1.0 - p)))),
+        )
+    }
+
+    /// Distribution of `f(total)` for every total
+    fn map(&self, f: impl Fn(i32) -> i32) -> Self {
+        Self::from_pairs(self.iter().map(|(total, p)| (f(total), p)))
+    }
+}
+
+/// Computes the exact probability distribution of an expression's total
+///
+/// Returns `None` when the expression has no exact closed form (such as
+/// exploding dice), when it may divide by zero, or when the computation would
+/// be too large to finish quickly. Callers can fall back to sampling in that case.
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{distribution, parse};
+///
+/// let stats = distribution(&parse("4d6K3").unwrap()).unwrap();
+/// assert_eq!(stats.min(), 3);
+/// assert_eq!(stats.max(), 18);
+/// assert!((stats.mean() - 12.2446).abs() < 1e-4);
+///
+/// // Exploding dice have no exact distribution
+/// assert!(distribution(&parse("3d6!").unwrap()).is_none());
+/// ```
+#[must_use]
+pub fn distribution(expr: &DiceExpression) -> Option<Distribution> {
+    distribution_with_limits(expr, &RollLimits::new())
+}
+
+/// Computes the exact distribution of an expression rolled under `limits`
+///
+/// Continuous rerolls stop at the reroll limit, as they do when rolled.
+pub(crate) fn distribution_with_limits(
+    expr: &DiceExpression,
+    limits: &RollLimits,
+) -> Option<Distribution> {
+    match expr {
+        DiceExpression::Dice {
+            count,
+            die,
+            modifiers,
+        } => dice_distribution(*count, die, modifiers, limits),
+
+        DiceExpression::DynamicDice {
+            count,
+            die,
+            modifiers,
+        } => dynamic_distribution(count, die, modifiers, limits),
+
+        DiceExpression::Group {
+            expressions,
+            modifiers,
+        } => {
+            let members = expressions
+                .iter()
+                .map(|expr| distribution_with_limits(expr, limits))
+                .collect::<Option<Vec<_>>>()?;
+            group_distribution(&members, modifiers)
+        }
+
+        DiceExpression::RepeatList {
+            expression,
+            times,
+            modifiers,
+        } => repeat_list_distribution(
+            &distribution_with_limits(expression, limits)?,
+            *times,
+            modifiers,
+        ),
+
+        DiceExpression::Repeat { expression, times } => {
+            distribution_with_limits(expression, limits)?.sum_of(*times)
+        }
+
+        DiceExpression::Binary { left, op, right } => {
+            let left = distribution_with_limits(left, limits)?;
+            let right = distribution_with_limits(right, limits)?;
+            left.combine(&right, |a, b| binary_total(a, op, b).ok())
+        }
+
+        DiceExpression::Constant(value) => Some(Distribution::constant(*value)),
+
+        DiceExpression::Negate(operand) => Distribution::constant(0).combine(
+            &distribution_with_limits(operand, limits)?,
+            i32::checked_sub,
+        ),
+
+        DiceExpression::Compare {
+            left,
+            comparison,
+            right,
+        } => distribution_with_limits(left, limits)?
+            .combine(&distribution_with_limits(right, limits)?, |a, b| {
+                Some(i32::from(comparison.compare(a, b)))
+            }),
+
+        DiceExpression::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => {
+            let condition = distribution_with_limits(condition, limits)?;
+            let p = 1.0 - condition.probability(0);
+            Some(
+                distribution_with_limits(then, limits)?
+                    .mix(&distribution_with_limits(otherwise, limits)?, p),
+            )
+        }
+
+        DiceExpression::Function { function, args } => {
+            function_distribution(*function, args, limits)
+        }
+
+        DiceExpression::Symbolic { .. } => None,
+    }
+}
+
+/// Distribution of a dice term with rolled parts
+///
+/// Mixes the distribution of every term the rolled parts can resolve to,
+/// weighted by the chance of rolling those parts. Returns `None` if any of
+/// them would be rejected when rolled with `limits`.
+fn dynamic_distribution(
+    count: &DiceExpression,
+    die: &DynamicDie,
+    modifiers: &[(Modifier, Option<DiceExpression>)],
+    limits: &RollLimits,
+) -> Option<Distribution> {
+    let parts = DiceExpression::dynamic_parts(count, die, modifiers)
+        .map(|part| {
+            Some(
+                distribution_with_limits(part, limits)?
+                    .iter()
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let terms = parts.iter().try_fold(1_usize, |terms, part| {
+        terms
+            .checked_mul(part.len())
+            .filter(|&terms| terms <= MAX_DYNAMIC_TERMS)
+    })?;
+
+    let mut pairs = Vec::new();
+    for term in 0..terms {
+        // Pick one outcome of each part, counting through them like digits
+        let mut rest = term;
+        let mut weight = 1.0;
+        let mut values = Vec::with_capacity(parts.len());
+        for part in &parts {
+            let (value, p) = part[rest % part.len()];
+            rest /= part.len();
+            weight *= p;
+            values.push(value);
+        }
+
+        let (count, die, modifiers) =
+            resolve_dynamic_dice(die, modifiers, &values, &mut DiceBudget::new(limits)).ok()?;
+        let resolved = dice_distribution(count, &die, &modifiers, limits)?;
+        pairs.extend(resolved.iter().map(|(total, p)| (total, p * weight)));
+    }
+    Some(Distribution::from_pairs(pairs))
+}
+
+/// Distribution of a group's total, given the distribution of each member
+///
+/// Tracks the chance of every sorted set of member totals, then sums the
+/// totals the group's selections keep, or counts their net successes.
+/// Returns `None` if there are too many sets to track.
+fn group_distribution(members: &[Distribution], modifiers: &[Modifier]) -> Option<Distribution> {
+    let mut outcomes = BTreeMap::from([(Vec::new(), 1.0)]);
+    for member in members {
+        if outcomes
+            .len()
+            .saturating_mul(member.len())
+            .saturating_mul(members.len())
+            > MAX_COMBINATIONS
+        {
+            return None;
+        }
+
+        let mut next = BTreeMap::new();
+        for (totals, p) in &outcomes {
+            for (total, q) in member.iter() {
+                let mut totals: Vec<i32> = totals.clone();
+                let rank = totals.partition_point(|&other| other > total);
+                totals.insert(rank, total);
+                *next.entry(totals).or_insert(0.0) += p * q;
+            }
+        }
+        outcomes = next;
+    }
+
+    let selections: Vec<&Modifier> = modifiers
+        .iter()
+        .filter(|modifier| !matches!(modifier, Modifier::CountSuccesses { .. }))
+        .collect();
+    let (skip, keep) = selected_ranks(members.len(), &selections)?;
+    let successes = modifiers.iter().find_map(|modifier| match modifier {
+        Modifier::CountSuccesses { success, failure } => Some((success, failure.as_ref())),
+        _ => None,
+    });
+
+    let mut pairs = Vec::with_capacity(outcomes.len());
+    for (totals, p) in outcomes {
+        let mut kept = totals.iter().skip(skip).take(keep);
+        let total = match successes {
+            Some((success, failure)) => kept
+                .map(|&total| net_success(success, failure, total))
+                .sum(),
+            None => kept.try_fold(0_i32, |sum, &total| sum.checked_add(total))?,
+        };
+        pairs.push((total, p));
+    }
+    Some(Distribution::from_pairs(pairs))
+}
+
+/// Distribution of a repeat list's total, given the distribution of one roll
+///
+/// The rolls are independent and identical, so selections and success
+/// counting alone are worked out like dice. Both together need the general
+/// group computation.
+fn repeat_list_distribution(
+    roll: &Distribution,
+    times: usize,
+    modifiers: &[Modifier],
+) -> Option<Distribution> {
+    let (successes, selections): (Vec<&Modifier>, Vec<&Modifier>) = modifiers
+        .iter()
+        .partition(|modifier| matches!(modifier, Modifier::CountSuccesses { .. }));
+    match (selections.is_empty(), successes.first()) {
+        (false, Some(_)) => group_distribution(&vec![roll.clone(); times], modifiers),
+        (_, successes) => pool_distribution(times, roll, &selections, successes.copied()),
+    }
+}
+
+/// Distribution of a function call's total
+fn function_distribution(
+    function: Function,
+    args: &[DiceExpression],
+    limits: &RollLimits,
+) -> Option<Distribution> {
+    if let [DiceExpression::Binary {
+        left,
+        op: BinaryOp::Divide | BinaryOp::FloorDivide,
+        right,
+    }] = args
+    {
+        if matches!(function, Function::Floor | Function::Ceil | Function::Round) {
+            return distribution_with_limits(left, limits)?
+                .combine(&distribution_with_limits(right, limits)?, |a, b| {
+                    rounded_quotient(function, a, b)
+                });
+        }
+    }
+
+    let (min, max) = function.arity();
+    if args.len() < min || args.len() > max {
+        return None;
+    }
+    // `max` and `min` pick among the items of a list, not its total
+    if matches!(function, Function::Max | Function::Min) && !args.iter().all(is_single_item) {
+        return None;
+    }
+    let args = args
+        .iter()
+        .map(|expr| distribution_with_limits(expr, limits))
+        .collect::<Option<Vec<Distribution>>>()?;
+
+    match function {
+        Function::Max => fold(&args, |a, b| Some(a.max(b))),
+        Function::Min => fold(&args, |a, b| Some(a.min(b))),
+        Function::Sum => fold(&args, i32::checked_add),
+        Function::Abs => args[0].combine(&Distribution::constant(0), |a, _| a.checked_abs()),
+        Function::Clamp => args[0]
+            .combine(&args[2], |value, high| Some(value.min(high)))?
+            .combine(&args[1], |value, low| Some(value.max(low))),
+        _ => args.into_iter().next(),
+    }
+}
+
+/// Whether an expression's value is a single item, whose only item is its total
+fn is_single_item(expr: &DiceExpression) -> bool {
+    match expr {
+        DiceExpression::Dice {
+            count, modifiers, ..
+        } => *count == 1 && modifiers.is_empty(),
+        DiceExpression::Constant(_)
+        | DiceExpression::Negate(_)
+        | DiceExpression::Binary { .. }
+        | DiceExpression::Compare { .. } => true,
+        DiceExpression::Conditional {
+            then, otherwise, ..
+        } => is_single_item(then) && is_single_item(otherwise),
+        DiceExpression::Function { function, .. } => *function != Function::Sort,
+        _ => false,
+    }
+}
+
+/// Combines every distribution in `args`, left to right, with `op`
+fn fold(args: &[Distribution], op: impl Fn(i32, i32) -> Option<i32>) -> Option<Distribution> {
+    let (first, rest) = args.split_first()?;
+    rest.iter()
+        .try_fold(first.clone(), |total, arg| total.combine(arg, &op))
+}
+
+/// Distribution of a dice term's total, following its modifier pipeline
+///
+/// Covers rerolls followed by either keep/drop selections or a success
+/// count. Explosions chain without bound, so they have no compact exact form.
+fn dice_distribution(
+    count: usize,
+    die_type: &DieType,
+    modifiers: &[Modifier],
+    limits: &RollLimits,
+) -> Option<Distribution> {
+    limits.check_die(die_type).ok()?;
+    let mut die = single_die(die_type);
+    let mut selections = Vec::new();
+    let mut successes = None;
+    let mut rerolls = Vec::new();
+
+    for modifier in modifiers {
+        match modifier {
+            Modifier::Reroll {
+                condition,
+                reroll_type,
+            } => rerolls.push((condition, reroll_type)),
+            Modifier::Explode { .. } => return None,
+            Modifier::CountSuccesses { .. } => successes = Some(modifier),
+            // Critical marking leaves the total unchanged
+            Modifier::CriticalSuccess(_) | Modifier::CriticalFailure(_) => {}
+            _ => selections.push(modifier),
+        }
+    }
+
+    if !rerolls.is_empty() {
+        let max_rerolls = if rerolls.iter().all(|(_, t)| **t == RerollType::Once) {
+            1
+        } else if rerolls.iter().all(|(_, t)| **t == RerollType::Continuous) {
+            i32::try_from(limits.max_rerolls).unwrap_or(i32::MAX)
+        } else {
+            return None;
+        };
+        let should_reroll = |face| rerolls.iter().any(|(condition, _)| condition.matches(face));
+        die = rerolled_die(&die, should_reroll, max_rerolls);
+    }
+
+    pool_distribution(count, &die, &selections, successes)
+}
+
+/// Distribution of `count` independent dice after keep/drop `selections`,
+/// or of their net successes
+///
+/// Returns `None` when given both, which has no closed form here.
+fn pool_distribution(
+    count: usize,
+    die: &Distribution,
+    selections: &[&Modifier],
+    successes: Option<&Modifier>,
+) -> Option<Distribution> {
+    match (selections, successes) {
+        ([], None) => die.sum_of(count),
+        (selections, None) => {
+            let (skip, keep) = selected_ranks(count, selections)?;
+            // Assigning from the nearer end keeps the number of states small
+            let below = count - skip - keep;
+            if below < skip {
+                keep_from_lowest(count, die, below, keep)
+            } else {
+                keep_from_highest(count, die, skip, keep)
+            }
+        }
+        ([], Some(Modifier::CountSuccesses { success, failure })) => die
+            .map(|face| net_success(success, failure.as_ref(), face))
+            .sum_of(count),
+        _ => None,
+    }
+}
+
+/// One for a value meeting the success condition, minus one for meeting the failure condition
+fn net_success(success: &Condition, failure: Option<&Condition>, value: i32) -> i32 {
+    i32::from(success.matches(value))
+        - i32::from(failure.is_some_and(|failure| failure.matches(value)))
+}
+
+/// Distribution of a single fair die, every side equally likely
+fn single_die(die: &DieType) -> Distribution {
+    let faces = die.faces();
+    let p = 1.0 / faces.len() as f64;
+    Distribution::from_pairs(faces.into_iter().map(|face| (face, p)))
+}
+
+/// Distribution of a single die rerolled up to `max_rerolls` times
+///
+/// A face that does not trigger a reroll is kept the first time it shows up;
+/// a face that does trigger one is only kept when the rerolls run out.
+fn rerolled_die(
+    die: &Distribution,
+    should_reroll: impl Fn(i32) -> bool,
+    max_rerolls: i32,
+) -> Distribution {
+    let q: f64 = die
+        .iter()
+        .filter(|&(face, _)| should_reroll(face))
+        .map(|(_, p)| p)
+        .sum();
+    // Probability of reaching each attempt: 1 + q + q^2 + ... + q^max_rerolls,
+    // summed in closed form so a large reroll limit stays cheap
+    let attempts = if q < 1.0 {
+        (1.0 - q.powi(max_rerolls.saturating_add(1))) / (1.0 - q)
+    } else {
+        f64::from(max_rerolls) + 1.0
+    };
+
+    Distribution::from_pairs(die.iter().map(|(face, p)| {
+        if should_reroll(face) {
+            (face, p * q.powi(max_rerolls))
+        } else {
+            (face, p * attempts)
+        }
+    }))
+}
+
+/// Probability of each number of successes in `trials` trials with success chance `p`
+fn binomial_pmf(trials: usize, p: f64) -> Vec<f64> {
+    let mut pmf = vec![0.0; trials + 1];
+    if p >= 1.0 {
+        pmf[trials] = 1.0;
+        return pmf;
+    }
+
+    let odds = p / (1.0 - p);
+    pmf[0] = (1.0 - p).powi(i32::try_from(trials).unwrap_or(i32::MAX));
+    for j in 0..trials {
+        pmf[j + 1] = pmf[j] * (trials - j) as f64 / (j + 1) as f64 * odds;
+    }
+    pmf
+}
+
+/// Ranks kept by a chain of keep/drop selections, as the number of highest
+/// dice skipped and the number of dice kept after them
+///
+/// Every selection keeps a run of neighbouring ranks among the dice kept so
+/// far, so a whole chain does too.
+fn selected_ranks(count: usize, selections: &[&Modifier]) -> Option<(usize, usize)> {
+    selections
+        .iter()
+        .try_fold((0, count), |(skip, available), selection| {
+            let (top, keep) = match selection {
+                Modifier::KeepHighest(keep) => (0, *keep),
+                Modifier::KeepLowest(keep) => (available.saturating_sub(*keep), *keep),
+                Modifier::DropHighest(drop) => (*drop, available.saturating_sub(*drop)),
+                Modifier::DropLowest(drop) => (0, available.saturating_sub(*drop)),
+                Modifier::KeepMiddle(keep) => ((available.saturating_sub(*keep) + 1) / 2, *keep),
+                _ => return None,
+            };
+            let top = top.min(available);
+            Some((skip + top, keep.min(available - top)))
+        })
+}
+
+/// Distribution of the sum of `keep` of `count` independent dice, after
+/// skipping the `skip` highest
+///
+/// Faces are assigned from highest to lowest. At face `v` the dice not yet
+/// assigned are known to show `v` or less, so the number showing exactly `v`
+/// is binomial with chance `P(v) / P(face <= v)`. Only the dice assigned after
+/// the first `skip` are summed, and a branch is finished as soon as
+/// `skip + keep` dice have been assigned.
+fn keep_from_highest(
+    count: usize,
+    die: &Distribution,
+    skip: usize,
+    keep: usize,
+) -> Option<Distribution> {
+    if keep == 0 {
+        return Some(Distribution::constant(0));
+    }
+    let end = skip + keep;
+
+    // Faces x assigned counts x dice showing a face x running sums
+    let faces = die.len();
+    let work = faces
+        .saturating_mul(count)
+        .saturating_mul(end)
+        .saturating_mul(keep.saturating_mul(faces));
+    if work > MAX_COMBINATIONS.saturating_mul(64) {
+        return None;
+    }
+
+    // states[m] maps the running kept sum to its probability, with m dice assigned
+    let mut states = vec![BTreeMap::<i32, f64>::new(); end];
+    states[0].insert(0, 1.0);
+    let mut finished = Vec::new();
+    // Probability that a die shows the current face or less
+    let mut at_most = 1.0;
+
+    for (&face, &p) in die.probabilities.iter().rev() {
+        let mut next = vec![BTreeMap::<i32, f64>::new(); end];
+
+        for (assigned, sums) in states.iter().enumerate() {
+            let remaining = count - assigned;
+            let pmf = binomial_pmf(remaining, p / at_most);
+
+            for (&sum, &probability) in sums {
+                for (showing, &chance) in pmf.iter().enumerate() {
+                    if chance == 0.0 {
+                        continue;
+                    }
+                    let new_assigned = assigned + showing;
+                    let kept = new_assigned.min(end).saturating_sub(assigned.max(skip));
+                    let new_sum = i32::try_from(kept)
+                        .ok()?
+                        .checked_mul(face)?
+                        .checked_add(sum)?;
+
+                    if new_assigned >= end {
+                        finished.push((new_sum, probability * chance));
+                    } else {
+                        *next[new_assigned].entry(new_sum).or_insert(0.0) += probability * chance;
+                    }
+                }
+            }
+        }
+
+        states = next;
+        at_most -= p;
+    }
+
+    Some(Distribution::from_pairs(finished))
+}
+
+/// Distribution of the sum of `keep` of `count` independent dice, after
+/// skipping the `skip` lowest
+///
+/// Negates every face, which turns the lowest dice into the highest.
+fn keep_from_lowest(
+    count: usize,
+    die: &Distribution,
+    skip: usize,
+    keep: usize,
+) -> Option<Distribution> {
+    let negated = die.map(|face| -face);
+    Some(keep_from_highest(count, &negated, skip, keep)?.map(|total| -total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::DiceParser;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn exact(notation: &str) -> Option<Distribution> {
+        let mut parser = DiceParser::new(notation);
+        distribution(&parser.parse().unwrap())
+    }
+
+    /// Enumerates every outcome of `count` dice and applies `total` to each
+    fn brute_force(count: u32, sides: i32, total: impl Fn(&mut Vec<i32>) -> i32) -> Distribution {
+        let outcomes = sides.pow(count);
+        let p = 1.0 / f64::from(outcomes);
+        Distribution::from_pairs((0..outcomes).map(|mut index| {
+            let mut faces = Vec::new();
+            for _ in 0..count {
+                faces.push(index % sides + 1);
+                index /= sides;
+            }
+            (total(&mut faces), p)
+        }))
+    }
+
+    fn assert_same(actual: &Distribution, expected: &Distribution) {
+        assert_eq!(actual.len(), expected.len());
+        for (total, p) in expected.iter() {
+            assert!(
+                (actual.probability(total) - p).abs() < EPSILON,
+                "P({total}) was {}, expected {p}",
+                actual.probability(total)
+            );
+        }
+    }
+
+    #[test]
+    fn test_distribution_simple_dice() {
+        let dist = exact("2d6").unwrap();
+        assert_eq!(dist.min(), 2);
+        assert_eq!(dist.max(), 12);
+        assert!((dist.probability(7) - 6.0 / 36.0).abs() < EPSILON);
+        assert!((dist.probability(2) - 1.0 / 36.0).abs() < EPSILON);
+        assert_eq!(dist.median(), 7);
+    }
+
+    #[test]
+    fn test_distribution_probabilities_sum_to_one() {
+        for notation in [
+            "3d6",
+            "4d6K3",
+            "5d8k2",
+            "6d4X2",
+            "10d10>6f<3",
+            "4d6R<3",
+            "3d6x2 * 2",
+        ] {
+            let total: f64 = exact(notation).unwrap().iter().map(|(_, p)| p).sum();
+            assert!(
+                (total - 1.0).abs() < EPSILON,
+                "{notation} summed to {total}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_distribution_keep_highest_matches_enumeration() {
+        let expected = brute_force(4, 6, |faces| {
+            faces.sort_unstable_by(|a, b| b.cmp(a));
+            faces[..3].iter().sum()
+        });
+        assert_same(&exact("4d6K3").unwrap(), &expected);
+        assert_same(&exact("4d6dl1").unwrap(), &expected);
+    }
+
+    #[test]
+    fn test_distribution_keep_lowest_and_drop_highest_match_enumeration() {
+        let lowest_two = brute_force(4, 5, |faces| {
+            faces.sort_unstable();
+            faces[..2].iter().sum()
+        });
+        assert_same(&exact("4d5k2").unwrap(), &lowest_two);
+
+        let drop_one = brute_force(3, 8, |faces| {
+            faces.sort_unstable();
+            faces[..2].iter().sum()
+        });
+        assert_same(&exact("3d8X").unwrap(), &drop_one);
+    }
+
+    #[test]
+    fn test_distribution_keep_middle_and_chained_selections_match_enumeration() {
+        let trimmed = brute_force(5, 6, |faces| {
+            faces.sort_unstable();
+            faces[1..4].iter().sum()
+        });
+        assert_same(&exact("5d6km3").unwrap(), &trimmed);
+        assert_same(&exact("5d6dh1dl1").unwrap(), &trimmed);
+
+        // An odd die out is dropped from the top
+        let middle_two = brute_force(5, 6, |faces| {
+            faces.sort_unstable();
+            faces[1..3].iter().sum()
+        });
+        assert_same(&exact("5d6km2").unwrap(), &middle_two);
+
+        // Each selection narrows the window left by the one before it
+        let drop_then_keep = brute_force(6, 6, |faces| {
+            faces.sort_unstable();
+            faces[2..5].iter().sum()
+        });
+        assert_same(&exact("6d6dl1kh4kl3").unwrap(), &drop_then_keep);
+        assert_same(
+            &exact("5d6K4dl1").unwrap(),
+            &brute_force(5, 6, |faces| {
+                faces.sort_unstable();
+                faces[2..5].iter().sum()
+            }),
+        );
+    }
+
+    #[test]
+    fn test_distribution_success_counting_is_binomial() {
+        let dist = exact("5d10>6").unwrap();
+        // Each die succeeds on 7-10, a 40% chance
+        assert!((dist.probability(0) - 0.6_f64.powi(5)).abs() < EPSILON);
+        assert!((dist.probability(5) - 0.4_f64.powi(5)).abs() < EPSILON);
+        assert!((dist.mean() - 2.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_distribution_success_failure_matches_enumeration() {
+        let expected = brute_force(3, 10, |faces| {
+            faces
+                .iter()
+                .map(|&face| i32::from(face > 6) - i32::from(face < 3))
+                .sum()
+        });
+        assert_same(&exact("3d10>6f<3").unwrap(), &expected);
+    }
+
+    #[test]
+    fn test_distribution_full_conditions_match_enumeration() {
+        assert_same(&exact("5d10>=8").unwrap(), &exact("5d10>7").unwrap());
+        assert_same(
+            &exact("4d6{5,6}f=1").unwrap(),
+            &brute_force(4, 6, |faces| {
+                faces
+                    .iter()
+                    .map(|&face| i32::from(face >= 5) - i32::from(face == 1))
+                    .sum()
+            }),
+        );
+        assert_same(&exact("2d6r{1,2}").unwrap(), &exact("2d6r<=2").unwrap());
+        assert_same(&exact("2d20K1cs>18cf").unwrap(), &exact("2d20K1").unwrap());
+        let dist = exact("1d6r!=6").unwrap();
+        assert!((dist.probability(6) - 11.0 / 36.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_distribution_reroll_once() {
+        let dist = exact("1d6r1").unwrap();
+        assert!((dist.probability(1) - 1.0 / 36.0).abs() < EPSILON);
+        assert!((dist.probability(6) - 7.0 / 36.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_distribution_chained_reroll_and_selection_match_enumeration() {
+        // The last three faces are the rerolls, used when the first roll shows a 1
+        let rerolled = |faces: &mut Vec<i32>| -> Vec<i32> {
+            (0..3)
+                .map(|i| {
+                    if faces[i] == 1 {
+                        faces[i + 3]
+                    } else {
+                        faces[i]
+                    }
+                })
+                .collect()
+        };
+
+        let highest_two = brute_force(6, 4, |faces| {
+            let mut dice = rerolled(faces);
+            dice.sort_unstable_by(|a, b| b.cmp(a));
+            dice[..2].iter().sum()
+        });
+        assert_same(&exact("3d4r1K2").unwrap(), &highest_two);
+
+        let lowest_two = brute_force(6, 4, |faces| {
+            let mut dice = rerolled(faces);
+            dice.sort_unstable();
+            dice[..2].iter().sum()
+        });
+        assert_same(&exact("3d4r1X1").unwrap(), &lowest_two);
+
+        let successes = brute_force(6, 4, |faces| {
+            rerolled(faces)
+                .iter()
+                .map(|&face| i32::from(face > 2))
+                .sum()
+        });
+        assert_same(&exact("3d4r1>2").unwrap(), &successes);
+    }
+
+    #[test]
+    fn test_distribution_combines_reroll_conditions() {
+        let dist = exact("1d6r1r2").unwrap();
+        assert!((dist.probability(1) - 2.0 / 36.0).abs() < EPSILON);
+        assert!((dist.probability(6) - 8.0 / 36.0).abs() < EPSILON);
+        assert_same(&dist, &exact("1d6r<3").unwrap());
+    }
+
+    #[test]
+    fn test_distribution_fate_and_percentile_dice() {
+        let fate = exact("4dF").unwrap();
+        assert_eq!(fate.min(), -4);
+        assert_eq!(fate.max(), 4);
+        assert!((fate.probability(0) - 19.0 / 81.0).abs() < EPSILON);
+        assert!(fate.mean().abs() < EPSILON);
+
+        let single = exact("dF.1").unwrap();
+        assert!((single.probability(1) - 1.0 / 6.0).abs() < EPSILON);
+        assert!((single.probability(0) - 4.0 / 6.0).abs() < EPSILON);
+
+        // Keep highest works on any faces, including negative ones
+        let best = exact("2dFK1").unwrap();
+        assert!((best.probability(-1) - 1.0 / 9.0).abs() < EPSILON);
+        assert!((best.probability(1) - 5.0 / 9.0).abs() < EPSILON);
+
+        assert_same(&exact("1d%").unwrap(), &exact("1d100").unwrap());
+    }
+
+    #[test]
+    fn test_distribution_custom_faces() {
+        let loaded = exact("d{1,1,2}").unwrap();
+        assert!((loaded.probability(1) - 2.0 / 3.0).abs() < EPSILON);
+        assert!((loaded.probability(2) - 1.0 / 3.0).abs() < EPSILON);
+
+        let averaging = exact("d{2,3,3,4,4,5}").unwrap();
+        assert!((averaging.mean() - 3.5).abs() < EPSILON);
+        assert!((exact("d{2..7}").unwrap().mean() - 4.5).abs() < EPSILON);
+
+        // Matches enumerating the six sides of the die directly
+        let faces = [0, 0, 1, 1, 2, 5];
+        let expected = brute_force(3, 6, |sides| {
+            let mut dice: Vec<i32> = sides
+                .iter()
+                .map(|&side| faces[usize::try_from(side - 1).unwrap()])
+                .collect();
+            dice.sort_unstable_by(|a, b| b.cmp(a));
+            dice[..2].iter().sum()
+        });
+        assert_same(&exact("3d{0,0,1,1,2,5}K2").unwrap(), &expected);
+    }
+
+    #[test]
+    fn test_distribution_repeat_and_arithmetic() {
+        assert_same(&exact("3d6x2").unwrap(), &exact("6d6").unwrap());
+
+        let dist = exact("1d4 * 2 - 1").unwrap();
+        assert_eq!(
+            dist.iter().map(|(total, _)| total).collect::<Vec<_>>(),
+            vec![1, 3, 5, 7]
+        );
+
+        let ones = exact("1d100 % 10").unwrap();
+        assert_eq!((ones.min(), ones.max(), ones.len()), (0, 9, 10));
+        assert!((ones.probability(0) - 0.1).abs() < EPSILON);
+        assert_same(
+            &exact("1d6 ^ 2").unwrap(),
+            &brute_force(1, 6, |faces| faces[0] * faces[0]),
+        );
+
+        let penalty = exact("-1d6 + 10").unwrap();
+        assert_eq!((penalty.min(), penalty.max()), (4, 9));
+        assert!((penalty.mean() - 6.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_distribution_functions_match_enumeration() {
+        assert_same(
+            &exact("max(1d20, 1d20)").unwrap(),
+            &exact("2d20K1").unwrap(),
+        );
+        assert_same(
+            &exact("min(1d6, 1d6, 1d6)").unwrap(),
+            &exact("3d6k1").unwrap(),
+        );
+        assert_same(&exact("sum(2d6, 1d6)").unwrap(), &exact("3d6").unwrap());
+        // max() and min() pick among the dice of a pool, which has no exact form here
+        assert!(exact("max(4d6)").is_none());
+        assert!(exact("min(3d6x3, 1d6)").is_none());
+        assert_same(
+            &exact("abs(1d6 - 1d6)").unwrap(),
+            &brute_force(2, 6, |faces| (faces[0] - faces[1]).abs()),
+        );
+        assert_same(
+            &exact("clamp(1d6 + 1d6, 1d6 + 2, 9)").unwrap(),
+            &brute_force(3, 6, |faces| (faces[0] + faces[1]).min(9).max(faces[2] + 2)),
+        );
+
+        let halves = |notation| {
+            exact(notation)
+                .unwrap()
+                .iter()
+                .map(|(total, _)| total)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(halves("floor(1d6 / 2)"), vec![0, 1, 2, 3]);
+        assert_eq!(halves("ceil(1d6 / 2)"), vec![1, 2, 3]);
+        assert_eq!(halves("round(1d5 / 2)"), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_distribution_comparisons_and_conditionals() {
+        let hit = exact("1d20 + 5 >= 15").unwrap();
+        assert_eq!((hit.min(), hit.max()), (0, 1));
+        assert!((hit.probability(1) - 0.55).abs() < EPSILON);
+        assert!((exact("1d6 != 6").unwrap().mean() - 5.0 / 6.0).abs() < EPSILON);
+
+        let damage = exact("1d20 >= 11 ? 2d6 : 1d4").unwrap();
+        let expected = exact("2d6").unwrap().mix(&exact("1d4").unwrap(), 0.5);
+        assert_same(&damage, &expected);
+        assert!((damage.mean() - 4.75).abs() < EPSILON);
+
+        assert_same(
+            &exact("1d6 > 3 ? 1d6 : 0").unwrap(),
+            &brute_force(2, 6, |faces| if faces[0] > 3 { faces[1] } else { 0 }),
+        );
+    }
+
+    #[test]
+    fn test_distribution_unavailable() {
+        assert!(exact("3d6!").is_none());
+        assert!(exact("4d6r1!K3").is_none());
+        assert!(exact("2d6 / (1d2 - 1)").is_none());
+        assert!(exact("2d6 % (1d2 - 1)").is_none());
+        assert!(exact("1d2000 * 1d2000").is_none());
+    }
+
+    #[test]
+    fn test_distribution_groups_match_enumeration() {
+        assert_same(&exact("{1d20, 1d20}K1").unwrap(), &exact("2d20K1").unwrap());
+        assert_same(
+            &exact("{1d6, 1d6, 1d6}dl1>4").unwrap(),
+            &brute_force(3, 6, |faces| {
+                faces.sort_unstable();
+                faces[1..].iter().map(|&face| i32::from(face > 4)).sum()
+            }),
+        );
+        assert_same(
+            &exact("{1d6, 1d6}kl1x2").unwrap(),
+            &exact("2d6k1x2").unwrap(),
+        );
+
+        // Every outcome of 2d6, 1d8 and 1d10, keeping the highest total
+        let mut pairs = Vec::new();
+        for a in 1..=6 {
+            for b in 1..=6 {
+                for c in 1..=8 {
+                    for d in 1..=10 {
+                        pairs.push(((a + b).max(c).max(d), 1.0 / 2880.0));
+                    }
+                }
+            }
+        }
+        assert_same(
+            &exact("{2d6, 1d8, 1d10}K1").unwrap(),
+            &Distribution::from_pairs(pairs),
+        );
+
+        let advantage = exact("{1d20 + 5, 1d20 + 3}kh1").unwrap();
+        assert_eq!(advantage.min(), 6);
+        assert_eq!(advantage.max(), 25);
+
+        // Members without an exact form leave the group without one
+        assert!(exact("{1d6!, 1d6}K1").is_none());
+    }
+
+    #[test]
+    fn test_distribution_repeat_lists_match_enumeration() {
+        assert_same(&exact("(1d6x3)K2").unwrap(), &exact("3d6K2").unwrap());
+        assert_same(&exact("(1d10x4)>=8").unwrap(), &exact("4d10>=8").unwrap());
+        assert_same(&exact("sort(1d6x3)").unwrap(), &exact("3d6").unwrap());
+        assert_same(
+            &exact("(1d6x4)dl1>=4").unwrap(),
+            &brute_force(4, 6, |faces| {
+                faces.sort_unstable();
+                faces[1..].iter().map(|&face| i32::from(face >= 4)).sum()
+            }),
+        );
+        assert_same(
+            &exact("(2d6x3)kl1").unwrap(),
+            &exact("{2d6, 2d6, 2d6}kl1").unwrap(),
+        );
+
+        let stats = exact("(4d6K3x7)K6").unwrap();
+        assert_eq!(stats.min(), 18);
+        assert_eq!(stats.max(), 108);
+        assert!(stats.mean() > 6.0 * 12.2446);
+    }
+
+    #[test]
+    fn test_distribution_rejects_hand_built_dice_without_sides() {
+        for die in [
+            DieType::Numbered(0),
+            DieType::Numbered(-5),
+            DieType::Custom(Vec::new()),
+        ] {
+            let dice = DiceExpression::Dice {
+                count: 2,
+                die: die.clone(),
+                modifiers: Vec::new(),
+            };
+            let dynamic = DiceExpression::DynamicDice {
+                count: Box::new(DiceExpression::Constant(2)),
+                die: DynamicDie::Fixed(die),
+                modifiers: Vec::new(),
+            };
+            assert!(distribution(&dice).is_none());
+            assert!(distribution(&dynamic).is_none());
+        }
+    }
+
+    #[test]
+    fn test_distribution_dynamic_dice_mix_every_resolved_term() {
+        let mix = |terms: &[&str]| {
+            let p = 1.0 / terms.len() as f64;
+            Distribution::from_pairs(terms.iter().flat_map(|notation| {
+                exact(notation)
+                    .unwrap()
+                    .iter()
+                    .map(|(total, q)| (total, q * p))
+                    .collect::<Vec<_>>()
+            }))
+        };
+
+        assert_same(
+            &exact("(1d4)d6").unwrap(),
+            &mix(&["1d6", "2d6", "3d6", "4d6"]),
+        );
+        assert_same(&exact("2d(1d3*4)").unwrap(), &mix(&["2d4", "2d8", "2d12"]));
+        assert_same(
+            &exact("4d6K(1d3)").unwrap(),
+            &mix(&["4d6K1", "4d6K2", "4d6K3"]),
+        );
+        assert!((exact("(1d4)d6").unwrap().mean() - 8.75).abs() < EPSILON);
+
+        // No dice at all a quarter of the time
+        let escalating = exact("(1d4 - 1)d6").unwrap();
+        assert!((escalating.probability(0) - 0.25).abs() < EPSILON);
+
+        // Counts rejected when rolled, and too many resolved terms, have no exact form
+        assert!(exact("(1d4 - 2)d6").is_none());
+        assert!(exact("(1d30)d6").is_none());
+        assert!(exact("(1d100)d(1d100)").is_none());
+    }
+}
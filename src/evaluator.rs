@@ -16,277 +16,989 @@
 //!
 //! This module takes the parsed AST and evaluates it to produce actual dice roll results.
 
+#![allow(clippy::redundant_pub_crate)] // Marks the helpers that are not re-exported
+
+use crate::limits::{DiceBudget, RollLimits};
 use crate::parser::{
-    BinaryOp, Comparison, DiceExpression, ExplodeCondition, RerollCondition, RerollType,
+    BinaryOp, Condition, DiceExpression, DieType, DynamicDie, ExplodeCondition, ExplodeType,
+    Function, Modifier, RerollType,
 };
-use crate::DiceError;
+use crate::result::{Die, GroupMember, RollResult, Total};
+use crate::value::Value;
+use crate::{DiceError, Roller};
 use rand::Rng;
+use std::cmp::Reverse;
 
-/// Evaluates a dice expression and returns the results
+/// Evaluates a parsed dice expression and returns the results
+///
+/// The results have the same shape as those returned by [`roll`](crate::roll).
+/// A single [`DiceExpression`] can be evaluated any number of times.
+///
+/// # Errors
+///
+/// Returns a limit error if the expression exceeds a default [`RollLimits`]
+/// limit, or an error if the expression divides by zero or a total overflows.
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{evaluate, parse};
+///
+/// let expression = parse("2d6 + 3").unwrap();
+/// let results = evaluate(&expression).unwrap();
+/// assert_eq!(results.len(), 3);
+/// ```
 pub fn evaluate(expr: &DiceExpression) -> Result<Vec<i32>, DiceError> {
     let mut rng = rand::rng();
     evaluate_with_rng(expr, &mut rng)
 }
 
 /// Evaluates a dice expression with a provided RNG
-#[allow(clippy::too_many_lines)] // Complex but well-structured function
-fn evaluate_with_rng<R: Rng>(expr: &DiceExpression, rng: &mut R) -> Result<Vec<i32>, DiceError> {
+///
+/// Every die in the expression, including those in repeated rolls, is drawn
+/// from `rng`. Evaluating the same expression with identically seeded RNGs
+/// produces identical results.
+///
+/// # Errors
+///
+/// Returns a limit error if the expression exceeds a default [`RollLimits`]
+/// limit, or an error if the expression divides by zero or a total overflows.
+///
+/// # Examples
+///
+/// ```
+/// use rand::SeedableRng;
+/// use rand_chacha::ChaCha8Rng;
+/// use rollpoly::{evaluate_with_rng, parse};
+///
+/// let expression = parse("3d6x4").unwrap();
+/// let first = evaluate_with_rng(&expression, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+/// let second = evaluate_with_rng(&expression, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+/// assert_eq!(first, second);
+/// ```
+pub fn evaluate_with_rng<R: Rng + ?Sized>(
+    expr: &DiceExpression,
+    rng: &mut R,
+) -> Result<Vec<i32>, DiceError> {
+    evaluate_detailed_with_rng(expr, rng).map(|result| result.values())
+}
+
+/// Evaluates a parsed dice expression and returns the full result tree
+///
+/// # Errors
+///
+/// Returns a limit error if the expression exceeds a default [`RollLimits`]
+/// limit, or an error if the expression divides by zero or a total overflows.
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{evaluate_detailed, parse, RollResult};
+///
+/// let expression = parse("3d6").unwrap();
+/// let result = evaluate_detailed(&expression).unwrap();
+/// assert!(matches!(result, RollResult::Dice { .. }));
+/// assert_eq!(result.dice().len(), 3);
+/// ```
+pub fn evaluate_detailed(expr: &DiceExpression) -> Result<RollResult, DiceError> {
+    let mut rng = rand::rng();
+    evaluate_detailed_with_rng(expr, &mut rng)
+}
+
+/// Evaluates a dice expression with a provided RNG and returns the full result tree
+///
+/// The expression is held to the default [`RollLimits`], including expressions
+/// built by hand; use [`Roller`](crate::Roller) to evaluate with custom limits.
+///
+/// # Errors
+///
+/// Returns a limit error if the expression exceeds a default limit, or an error
+/// if the expression divides by zero or a total overflows.
+pub fn evaluate_detailed_with_rng<R: Rng + ?Sized>(
+    expr: &DiceExpression,
+    rng: &mut R,
+) -> Result<RollResult, DiceError> {
+    Roller::default().evaluate(expr, rng)
+}
+
+/// Evaluates a parsed dice expression to the typed value it produces
+///
+/// # Errors
+///
+/// Returns the same errors as [`evaluate`].
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{evaluate_value, parse, Value};
+///
+/// let expression = parse("(1d20) >= 1").unwrap();
+/// assert_eq!(evaluate_value(&expression).unwrap(), Value::Boolean(true));
+/// ```
+pub fn evaluate_value(expr: &DiceExpression) -> Result<Value, DiceError> {
+    let mut rng = rand::rng();
+    evaluate_value_with_rng(expr, &mut rng)
+}
+
+/// Evaluates a dice expression with a provided RNG to the typed value it produces
+///
+/// # Errors
+///
+/// Returns the same errors as [`evaluate`].
+pub fn evaluate_value_with_rng<R: Rng + ?Sized>(
+    expr: &DiceExpression,
+    rng: &mut R,
+) -> Result<Value, DiceError> {
+    Roller::default().evaluate_value(expr, rng)
+}
+
+/// Evaluates a dice expression, capping explosions and rerolls at the given limits
+///
+/// Totals are computed in `T` with checked arithmetic, so overflow is reported
+/// as [`DiceError::Overflow`] instead of panicking or wrapping.
+pub(crate) fn evaluate_with_limits<T: Total, R: Rng + ?Sized>(
+    expr: &DiceExpression,
+    limits: &RollLimits,
+    rng: &mut R,
+) -> Result<RollResult<T>, DiceError> {
+    evaluate_node(expr, &mut DiceBudget::new(limits), rng).map(|evaluated| evaluated.result)
+}
+
+/// Evaluates a dice expression to its typed value, capping explosions and rerolls at the given limits
+pub(crate) fn evaluate_value_with_limits<T: Total, R: Rng + ?Sized>(
+    expr: &DiceExpression,
+    limits: &RollLimits,
+    rng: &mut R,
+) -> Result<Value<T>, DiceError> {
+    evaluate_node(expr, &mut DiceBudget::new(limits), rng).map(|evaluated| evaluated.value)
+}
+
+/// A node's roll together with the value it produced
+///
+/// Each node computes its value from its children's values, coercing them
+/// where it needs a number, a condition or a list.
+struct Evaluated<T> {
+    result: RollResult<T>,
+    value: Value<T>,
+}
+
+impl<T: Total> Evaluated<T> {
+    /// A result whose value is its total
+    const fn scalar(result: RollResult<T>) -> Self {
+        Self {
+            value: Value::Scalar(result.total()),
+            result,
+        }
+    }
+}
+
+/// Evaluates one node, charging the dice it rolls against `budget`
+fn evaluate_node<T: Total, R: Rng + ?Sized>(
+    expr: &DiceExpression,
+    budget: &mut DiceBudget<'_>,
+    rng: &mut R,
+) -> Result<Evaluated<T>, DiceError> {
     match expr {
-        DiceExpression::Simple { count, sides } => {
-            let mut results = Vec::with_capacity(*count);
-            for _ in 0..*count {
-                results.push(rng.random_range(1..=*sides));
-            }
-            Ok(results)
+        DiceExpression::Dice {
+            count,
+            die,
+            modifiers,
+        } => {
+            budget.spend(*count)?;
+            roll_term(*count, die, modifiers, budget.limits(), rng)
         }
 
-        DiceExpression::KeepHighest { count, sides, keep } => {
-            let mut results = Vec::with_capacity(*count);
-            for _ in 0..*count {
-                results.push(rng.random_range(1..=*sides));
-            }
-            results.sort_unstable_by(|a, b| b.cmp(a)); // Sort descending (highest first)
-            results.truncate(*keep);
-            Ok(results)
+        DiceExpression::DynamicDice {
+            count,
+            die,
+            modifiers,
+        } => roll_dynamic_term(count, die, modifiers, budget, rng),
+
+        DiceExpression::Binary { left, op, right } => {
+            let left = evaluate_node::<T, R>(left, budget, rng)?;
+            let right = evaluate_node::<T, R>(right, budget, rng)?;
+            let total = binary_total(left.value.to_total()?, op, right.value.to_total()?)?;
+
+            Ok(Evaluated::scalar(RollResult::Binary {
+                left: Box::new(left.result),
+                op: op.clone(),
+                right: Box::new(right.result),
+                total,
+            }))
         }
 
-        DiceExpression::KeepLowest { count, sides, keep } => {
-            let mut results = Vec::with_capacity(*count);
-            for _ in 0..*count {
-                results.push(rng.random_range(1..=*sides));
-            }
-            results.sort_unstable(); // Sort ascending (lowest first)
-            results.truncate(*keep);
-            Ok(results)
+        DiceExpression::Group {
+            expressions,
+            modifiers,
+        } => roll_group(expressions, modifiers, budget, rng),
+
+        DiceExpression::RepeatList {
+            expression,
+            times,
+            modifiers,
+        } => roll_group(
+            std::iter::repeat(expression.as_ref()).take(*times),
+            modifiers,
+            budget,
+            rng,
+        ),
+
+        DiceExpression::Repeat { expression, times } => {
+            roll_repeat(expression, *times, budget, rng)
         }
 
-        DiceExpression::DropHighest { count, sides, drop } => {
-            let mut results = Vec::with_capacity(*count);
-            for _ in 0..*count {
-                results.push(rng.random_range(1..=*sides));
-            }
-            results.sort_unstable(); // Sort ascending (lowest first)
-            results.truncate(count - drop); // Keep all but the highest
-            Ok(results)
+        DiceExpression::Constant(value) => {
+            Ok(Evaluated::scalar(RollResult::Constant(T::from(*value))))
         }
 
-        DiceExpression::Exploding {
-            count,
-            sides,
-            condition,
+        DiceExpression::Negate(operand) => {
+            let operand = evaluate_node::<T, R>(operand, budget, rng)?;
+            let total =
+                T::default()
+                    .checked_sub(operand.value.to_total()?)
+                    .ok_or(DiceError::Overflow {
+                        operation: "negation",
+                    })?;
+            Ok(Evaluated::scalar(RollResult::Negate {
+                operand: Box::new(operand.result),
+                total,
+            }))
+        }
+
+        DiceExpression::Compare {
+            left,
+            comparison,
+            right,
         } => {
-            let mut all_results = Vec::new();
-
-            for _ in 0..*count {
-                const MAX_EXPLOSIONS: usize = 100;
-                let mut die_results = Vec::new();
-                let mut current_roll = rng.random_range(1..=*sides);
-                die_results.push(current_roll);
-
-                let mut explosion_count = 0;
-                loop {
-                    let should_explode = match condition {
-                        ExplodeCondition::Max => current_roll == *sides,
-                        ExplodeCondition::Value(target) => current_roll == *target,
-                        ExplodeCondition::Comparison(Comparison::GreaterThan, target) => {
-                            current_roll > *target
-                        }
-                        ExplodeCondition::Comparison(Comparison::LessThan, target) => {
-                            current_roll < *target
-                        }
-                    };
-
-                    if should_explode && explosion_count < MAX_EXPLOSIONS {
-                        current_roll = rng.random_range(1..=*sides);
-                        die_results.push(current_roll);
-                        explosion_count += 1;
-                    } else {
-                        break;
-                    }
-                }
+            let left = evaluate_node::<T, R>(left, budget, rng)?;
+            let right = evaluate_node::<T, R>(right, budget, rng)?;
+            let passed = comparison.compare(left.value.to_total()?, right.value.to_total()?);
+            Ok(Evaluated {
+                result: RollResult::Compare {
+                    left: Box::new(left.result),
+                    comparison: *comparison,
+                    right: Box::new(right.result),
+                    total: T::from(i32::from(passed)),
+                },
+                value: Value::Boolean(passed),
+            })
+        }
 
-                all_results.extend(die_results);
-            }
+        DiceExpression::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => roll_conditional(condition, then, otherwise, budget, rng),
 
-            Ok(all_results)
+        DiceExpression::Function { function, args } => {
+            evaluate_function(*function, args, budget, rng)
         }
 
-        DiceExpression::SuccessCounting {
-            count,
-            sides,
-            target,
-            comparison,
-        } => {
-            let mut success_count = 0;
+        DiceExpression::Symbolic { name, .. } => {
+            Err(DiceError::SymbolicDice { name: name.clone() })
+        }
+    }
+}
 
-            for _ in 0..*count {
-                let roll = rng.random_range(1..=*sides);
-                let is_success = match comparison {
-                    Comparison::GreaterThan => roll > *target,
-                    Comparison::LessThan => roll < *target,
-                };
+/// Rolls an expression `times` times, listing the value of each roll
+fn roll_repeat<T: Total, R: Rng + ?Sized>(
+    expression: &DiceExpression,
+    times: usize,
+    budget: &mut DiceBudget<'_>,
+    rng: &mut R,
+) -> Result<Evaluated<T>, DiceError> {
+    let mut rolls = Vec::with_capacity(times);
+    let mut values = Vec::with_capacity(times);
+    for _ in 0..times {
+        let roll = evaluate_node(expression, budget, rng)?;
+        rolls.push(roll.result);
+        values.push(roll.value);
+    }
+    let total = checked_sum(rolls.iter().map(RollResult::total), "repeat total")?;
+    Ok(Evaluated {
+        result: RollResult::Repeat { rolls, total },
+        value: Value::List(values),
+    })
+}
 
-                if is_success {
-                    success_count += 1;
-                }
+/// Rolls a condition, then only the branch it chooses
+fn roll_conditional<T: Total, R: Rng + ?Sized>(
+    condition: &DiceExpression,
+    then: &DiceExpression,
+    otherwise: &DiceExpression,
+    budget: &mut DiceBudget<'_>,
+    rng: &mut R,
+) -> Result<Evaluated<T>, DiceError> {
+    let condition = evaluate_node::<T, R>(condition, budget, rng)?;
+    let branch = if condition.value.to_bool()? {
+        then
+    } else {
+        otherwise
+    };
+    let branch = evaluate_node::<T, R>(branch, budget, rng)?;
+    Ok(Evaluated {
+        result: RollResult::Conditional {
+            total: branch.result.total(),
+            condition: Box::new(condition.result),
+            branch: Box::new(branch.result),
+        },
+        value: branch.value,
+    })
+}
+
+/// Resolves a dynamic dice term into a plain one, given the value of each rolled part
+///
+/// `values` follow the order of [`DiceExpression::dynamic_parts`]. The count
+/// must not be negative, so a rolled count of zero rolls no dice. The sides
+/// must be positive, both must be within the budget's limits, and keep/drop
+/// counts must not be negative. The rolled dice are charged against `budget`.
+pub(crate) fn resolve_dynamic_dice(
+    die: &DynamicDie,
+    modifiers: &[(Modifier, Option<DiceExpression>)],
+    values: &[i32],
+    budget: &mut DiceBudget<'_>,
+) -> Result<(usize, DieType, Vec<Modifier>), DiceError> {
+    let limits = budget.limits();
+    let mut values = values.iter().copied();
+
+    let rolled = values.next().unwrap_or_default();
+    let count = usize::try_from(rolled).map_err(|_| DiceError::InvalidDiceCount {
+        count: rolled.to_string(),
+    })?;
+    limits.check_dice_count(count)?;
+    budget.spend(count)?;
+
+    let die = match die {
+        DynamicDie::Fixed(die) => die.clone(),
+        DynamicDie::Rolled(_) => {
+            let sides = values.next().unwrap_or_default();
+            if sides <= 0 {
+                return Err(DiceError::InvalidDieSize {
+                    size: sides.to_string(),
+                });
+            }
+            limits.check_sides(sides)?;
+            DieType::Numbered(sides)
+        }
+    };
+
+    let modifiers = modifiers
+        .iter()
+        .map(|(modifier, rolled)| {
+            if rolled.is_none() {
+                return Ok(modifier.clone());
             }
+            let value = values.next().unwrap_or_default();
+            let count = usize::try_from(value).map_err(|_| DiceError::InvalidModifier {
+                modifier: format!("{}{value}", modifier.selection_prefix().unwrap_or_default()),
+            })?;
+            Ok(modifier.with_count(count))
+        })
+        .collect::<Result<_, DiceError>>()?;
+
+    Ok((count, die, modifiers))
+}
 
-            Ok(vec![success_count])
+/// Applies a binary operator to two totals with checked arithmetic
+///
+/// A negative exponent divides one by the power, truncating like `/`.
+pub(crate) fn binary_total<T: Total>(left: T, op: &BinaryOp, right: T) -> Result<T, DiceError> {
+    let zero = T::default();
+    let divides = matches!(
+        op,
+        BinaryOp::Divide | BinaryOp::FloorDivide | BinaryOp::Modulo
+    );
+    if right == zero && divides || left == zero && *op == BinaryOp::Power && right < zero {
+        return Err(DiceError::DivisionByZero);
+    }
+
+    let (total, operation) = match op {
+        BinaryOp::Add => (left.checked_add(right), "addition"),
+        BinaryOp::Subtract => (left.checked_sub(right), "subtraction"),
+        BinaryOp::Multiply => (left.checked_mul(right), "multiplication"),
+        BinaryOp::Divide => (left.checked_div(right), "division"),
+        BinaryOp::FloorDivide => (left.checked_div_euclid(right), "floor division"),
+        BinaryOp::Modulo => (left.checked_rem_euclid(right), "modulo"),
+        BinaryOp::Power => (power(left, right), "exponentiation"),
+    };
+    total.ok_or(DiceError::Overflow { operation })
+}
+
+/// Raises `base` to `exp`, `None` on overflow
+///
+/// A negative exponent gives `1 / base^-exp` truncated toward zero, which is
+/// zero unless `base` is 1 or -1. `base` must not be zero in that case.
+fn power<T: Total>(base: T, exp: T) -> Option<T> {
+    let zero = T::default();
+    let one = T::from(1);
+    if base == one {
+        Some(one)
+    } else if base == one.saturating_neg() {
+        let odd = exp.checked_rem(T::from(2))? != zero;
+        Some(if odd { base } else { one })
+    } else if exp < zero {
+        Some(zero)
+    } else {
+        base.checked_pow(exp)
+    }
+}
+
+/// Evaluates a function call
+///
+/// `floor`, `ceil` and `round` around a division evaluate the division
+/// themselves, so the quotient they record is already rounded their way.
+/// `max`, `min` and `sum` work on every item of their arguments' lists and
+/// `sort` orders its argument's items; the other functions work on totals.
+fn evaluate_function<T: Total, R: Rng + ?Sized>(
+    function: Function,
+    args: &[DiceExpression],
+    budget: &mut DiceBudget<'_>,
+    rng: &mut R,
+) -> Result<Evaluated<T>, DiceError> {
+    let (min, max) = function.arity();
+    if args.len() < min || args.len() > max {
+        return Err(DiceError::WrongArgumentCount {
+            function,
+            count: args.len(),
+        });
+    }
+
+    if let [DiceExpression::Binary {
+        left,
+        op: op @ (BinaryOp::Divide | BinaryOp::FloorDivide),
+        right,
+    }] = args
+    {
+        if matches!(function, Function::Floor | Function::Ceil | Function::Round) {
+            let left = evaluate_node::<T, R>(left, budget, rng)?;
+            let right = evaluate_node::<T, R>(right, budget, rng)?;
+            let divisor = right.value.to_total()?;
+            if divisor == T::default() {
+                return Err(DiceError::DivisionByZero);
+            }
+            let total = rounded_quotient(function, left.value.to_total()?, divisor).ok_or(
+                DiceError::Overflow {
+                    operation: "division",
+                },
+            )?;
+            let quotient = RollResult::Binary {
+                left: Box::new(left.result),
+                op: op.clone(),
+                right: Box::new(right.result),
+                total,
+            };
+            return Ok(Evaluated::scalar(RollResult::Function {
+                function,
+                args: vec![quotient],
+                total,
+            }));
         }
+    }
 
-        DiceExpression::SuccessFailure {
-            count,
-            sides,
-            success_target,
-            success_comparison,
-            failure_target,
-            failure_comparison,
-        } => {
-            let mut net_successes = 0;
+    let (args, mut values): (Vec<RollResult<T>>, Vec<Value<T>>) = args
+        .iter()
+        .map(|arg| evaluate_node(arg, budget, rng).map(|arg| (arg.result, arg.value)))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+    let totals = values
+        .iter()
+        .map(Value::to_total)
+        .collect::<Result<Vec<T>, _>>()?;
 
-            for _ in 0..*count {
-                let roll = rng.random_range(1..=*sides);
+    if function == Function::Sort {
+        let value = values.swap_remove(0).sorted();
+        return Ok(Evaluated {
+            result: RollResult::Function {
+                function,
+                args,
+                total: totals[0],
+            },
+            value,
+        });
+    }
 
-                let is_success = match success_comparison {
-                    Comparison::GreaterThan => roll > *success_target,
-                    Comparison::LessThan => roll < *success_target,
-                };
+    let total =
+        match function {
+            Function::Max => item_totals(values)?.into_iter().max().unwrap_or_default(),
+            Function::Min => item_totals(values)?.into_iter().min().unwrap_or_default(),
+            Function::Abs if totals[0] < T::default() => T::default()
+                .checked_sub(totals[0])
+                .ok_or(DiceError::Overflow {
+                    operation: "absolute value",
+                })?,
+            Function::Clamp => totals[0].min(totals[2]).max(totals[1]),
+            Function::Sum => checked_sum(item_totals(values)?, "sum")?,
+            _ => totals[0],
+        };
 
-                let is_failure = match failure_comparison {
-                    Comparison::GreaterThan => roll > *failure_target,
-                    Comparison::LessThan => roll < *failure_target,
-                };
+    Ok(Evaluated::scalar(RollResult::Function {
+        function,
+        args,
+        total,
+    }))
+}
 
-                if is_success {
-                    net_successes += 1;
-                }
-                if is_failure {
-                    net_successes -= 1;
+/// The total of every item of `values`, with lists flattened
+fn item_totals<T: Total>(values: Vec<Value<T>>) -> Result<Vec<T>, DiceError> {
+    values
+        .into_iter()
+        .flat_map(Value::into_list)
+        .map(|item| item.to_total())
+        .collect()
+}
+
+/// Divides `left` by a non-zero `right`, rounding the way `function` does
+///
+/// `round` rounds halves away from zero. Returns `None` on overflow.
+pub(crate) fn rounded_quotient<T: Total>(function: Function, left: T, right: T) -> Option<T> {
+    let quotient = left.checked_div(right)?;
+    let remainder = left.checked_rem(right)?;
+    if remainder == T::default() {
+        return Some(quotient);
+    }
+
+    // The remainder takes the sign of `left`, so the exact quotient is
+    // positive when it has the same sign as `right`
+    let zero = T::default();
+    let positive = (remainder > zero) == (right > zero);
+    let away_from_zero = match function {
+        Function::Floor => !positive,
+        Function::Ceil => positive,
+        _ => {
+            // Distance to the next multiple of `right` away from zero
+            let rest = if positive {
+                right.checked_sub(remainder)?
+            } else {
+                right.checked_add(remainder)?
+            };
+            let magnitude = |value: T| {
+                if value < zero {
+                    value.saturating_neg()
+                } else {
+                    value
                 }
-            }
+            };
+            magnitude(remainder) >= magnitude(rest)
+        }
+    };
+
+    if !away_from_zero {
+        Some(quotient)
+    } else if positive {
+        quotient.checked_add(T::from(1))
+    } else {
+        quotient.checked_sub(T::from(1))
+    }
+}
+
+/// Rolls a dice term and runs its modifier pipeline
+///
+/// Rerolls are applied first, then explosions, then keep/drop selections in
+/// the order they were written, and finally success counting and critical
+/// marking on the kept dice.
+fn roll_term<T: Total, R: Rng + ?Sized>(
+    count: usize,
+    die_type: &DieType,
+    modifiers: &[Modifier],
+    limits: &RollLimits,
+    rng: &mut R,
+) -> Result<Evaluated<T>, DiceError> {
+    let mut dice = roll_dice(count, die_type, rng);
 
-            Ok(vec![net_successes])
+    let rerolls: Vec<_> = modifiers
+        .iter()
+        .filter_map(|modifier| match modifier {
+            Modifier::Reroll {
+                condition,
+                reroll_type,
+            } => Some((condition, reroll_type)),
+            _ => None,
+        })
+        .collect();
+    if !rerolls.is_empty() {
+        for die in &mut dice {
+            reroll_die(die, die_type, &rerolls, limits, rng);
         }
+    }
 
-        DiceExpression::Rerolling {
-            count,
-            sides,
+    if let Some((condition, explode_type)) = modifiers.iter().find_map(|modifier| match modifier {
+        Modifier::Explode {
             condition,
-            reroll_type,
-        } => {
-            let mut results = Vec::with_capacity(*count);
-
-            for _ in 0..*count {
-                let mut current_roll = rng.random_range(1..=*sides);
-
-                if *reroll_type == RerollType::Once {
-                    let should_reroll = match condition {
-                        RerollCondition::Value(val) => current_roll == *val,
-                        RerollCondition::Comparison(Comparison::GreaterThan, val) => {
-                            current_roll > *val
-                        }
-                        RerollCondition::Comparison(Comparison::LessThan, val) => {
-                            current_roll < *val
-                        }
-                    };
-                    if should_reroll {
-                        current_roll = rng.random_range(1..=*sides);
-                    }
-                } else {
-                    // RerollType::Continuous
-                    const MAX_REROLLS: usize = 100;
-                    let mut reroll_count = 0;
-
-                    loop {
-                        let should_reroll = match condition {
-                            RerollCondition::Value(val) => current_roll == *val,
-                            RerollCondition::Comparison(Comparison::GreaterThan, val) => {
-                                current_roll > *val
-                            }
-                            RerollCondition::Comparison(Comparison::LessThan, val) => {
-                                current_roll < *val
-                            }
-                        };
-
-                        if should_reroll && reroll_count < MAX_REROLLS {
-                            current_roll = rng.random_range(1..=*sides);
-                            reroll_count += 1;
-                        } else {
-                            break;
-                        }
-                    }
-                }
+            explode_type,
+        } => Some((condition, *explode_type)),
+        _ => None,
+    }) {
+        dice = explode_dice(dice, die_type, condition, explode_type, limits, rng)?;
+    }
 
-                results.push(current_roll);
-            }
+    for modifier in modifiers {
+        select_dice(&mut dice, modifier);
+    }
+
+    mark_criticals(&mut dice, die_type, modifiers);
+
+    if let Some((success, failure)) = modifiers.iter().find_map(|modifier| match modifier {
+        Modifier::CountSuccesses { success, failure } => Some((success, failure.as_ref())),
+        _ => None,
+    }) {
+        return count_successes(dice, success, failure).map(Evaluated::scalar);
+    }
+
+    let value = Value::Pool(dice.clone());
+    let result = dice_result(dice)?;
+    Ok(Evaluated { result, value })
+}
+
+/// Rolls the parts of a dynamic dice term, then the term they resolve to
+fn roll_dynamic_term<T: Total, R: Rng + ?Sized>(
+    count: &DiceExpression,
+    die: &DynamicDie,
+    modifiers: &[(Modifier, Option<DiceExpression>)],
+    budget: &mut DiceBudget<'_>,
+    rng: &mut R,
+) -> Result<Evaluated<T>, DiceError> {
+    let (parts, values): (Vec<RollResult<T>>, Vec<i32>) =
+        DiceExpression::dynamic_parts(count, die, modifiers)
+            .map(|part| {
+                let part = evaluate_node::<T, R>(part, budget, rng)?;
+                let value = part.value.to_total()?.to_i32().ok_or(DiceError::Overflow {
+                    operation: "rolled dice term",
+                })?;
+                Ok((part.result, value))
+            })
+            .collect::<Result<Vec<_>, DiceError>>()?
+            .into_iter()
+            .unzip();
+
+    let (count, die_type, modifiers) = resolve_dynamic_dice(die, modifiers, &values, budget)?;
+    let term = roll_term(count, &die_type, &modifiers, budget.limits(), rng)?;
+    Ok(Evaluated {
+        result: RollResult::DynamicDice {
+            parts,
+            total: term.result.total(),
+            dice: Box::new(term.result),
+        },
+        value: term.value,
+    })
+}
+
+/// Rolls every expression of a group or repeat list, then applies the
+/// modifiers to their totals
+///
+/// Keep/drop selections narrow the kept members in the order they were
+/// written, leaving the members in the order they were rolled. The total is the net
+/// number of kept members meeting the success condition when one is given,
+/// and the sum of the kept members' totals otherwise. The value lists the
+/// kept members' values.
+fn roll_group<'e, T: Total, R: Rng + ?Sized>(
+    expressions: impl IntoIterator<Item = &'e DiceExpression>,
+    modifiers: &[Modifier],
+    budget: &mut DiceBudget<'_>,
+    rng: &mut R,
+) -> Result<Evaluated<T>, DiceError> {
+    let (mut members, values): (Vec<GroupMember<T>>, Vec<Value<T>>) = expressions
+        .into_iter()
+        .map(|expression| {
+            evaluate_node(expression, budget, rng)
+                .map(|member| (GroupMember::new(member.result), member.value))
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+
+    let mut kept: Vec<usize> = (0..members.len()).collect();
+    for modifier in modifiers {
+        let keep = order_selection(&mut kept, modifier, |&index| members[index].result.total());
+        kept.truncate(keep);
+    }
+    for (index, member) in members.iter_mut().enumerate() {
+        member.kept = kept.contains(&index);
+    }
+
+    if let Some((success, failure)) = modifiers.iter().find_map(|modifier| match modifier {
+        Modifier::CountSuccesses { success, failure } => Some((success, failure.as_ref())),
+        _ => None,
+    }) {
+        for member in members.iter_mut().filter(|member| member.kept) {
+            let total = member.result.total();
+            member.success = success.matches(total);
+            member.failure = failure.is_some_and(|failure| failure.matches(total));
+        }
+        let total = net_successes(
+            members
+                .iter()
+                .filter(|member| member.kept)
+                .map(|member| (member.success, member.failure)),
+        )?;
+        return Ok(Evaluated::scalar(RollResult::GroupSuccesses {
+            members,
+            total,
+        }));
+    }
+
+    let totals = members
+        .iter()
+        .filter(|member| member.kept)
+        .map(|member| member.result.total());
+    let total = checked_sum(totals, "group total")?;
+    let value = Value::List(
+        values
+            .into_iter()
+            .zip(&members)
+            .filter(|(_, member)| member.kept)
+            .map(|(value, _)| value)
+            .collect(),
+    );
+    Ok(Evaluated {
+        result: RollResult::Group { members, total },
+        value,
+    })
+}
 
-            Ok(results)
+/// Rerolls a die while it matches a reroll condition
+///
+/// Lowercase `r` conditions reroll a die at most once between them, while
+/// uppercase `R` conditions keep rerolling up to the reroll limit.
+fn reroll_die<R: Rng + ?Sized>(
+    die: &mut Die,
+    die_type: &DieType,
+    rerolls: &[(&Condition, &RerollType)],
+    limits: &RollLimits,
+    rng: &mut R,
+) {
+    let matching = |face: i32, reroll_type: RerollType| {
+        rerolls
+            .iter()
+            .any(|(condition, kind)| **kind == reroll_type && condition.matches(face))
+    };
+
+    let mut rerolled_once = false;
+    loop {
+        let continuous = matching(die.face, RerollType::Continuous)
+            && die.rerolled_from.len() < limits.max_rerolls;
+        let once = !rerolled_once && matching(die.face, RerollType::Once);
+        if !continuous && !once {
+            break;
+        }
+        if !continuous {
+            rerolled_once = true;
         }
+        die.rerolled_from.push(die.face);
+        die.face = die_type.roll(rng);
+    }
+}
 
-        DiceExpression::Binary { left, op, right } => {
-            let left_results = evaluate_with_rng(left, rng)?;
-            let right_results = evaluate_with_rng(right, rng)?;
-
-            match op {
-                BinaryOp::Add => {
-                    let mut results = left_results;
-                    results.extend(right_results);
-                    Ok(results)
-                }
-                BinaryOp::Subtract => {
-                    let mut results = left_results;
-                    // For subtraction, negate the right side values
-                    results.extend(right_results.iter().map(|&x| -x));
-                    Ok(results)
-                }
-                BinaryOp::Multiply => {
-                    let left_sum: i32 = left_results.iter().sum();
-                    let right_sum: i32 = right_results.iter().sum();
-                    Ok(vec![left_sum * right_sum])
-                }
-                BinaryOp::Divide => {
-                    let left_sum: i32 = left_results.iter().sum();
-                    let right_sum: i32 = right_results.iter().sum();
-                    if right_sum == 0 {
-                        return Err(DiceError::InvalidNotation {
-                            input: "division by zero".to_string(),
-                            reason: "Cannot divide by zero".to_string(),
-                        });
-                    }
-                    Ok(vec![left_sum / right_sum])
-                }
-                BinaryOp::FloorDivide => {
-                    let left_sum: i32 = left_results.iter().sum();
-                    let right_sum: i32 = right_results.iter().sum();
-                    if right_sum == 0 {
-                        return Err(DiceError::InvalidNotation {
-                            input: "division by zero".to_string(),
-                            reason: "Cannot divide by zero".to_string(),
-                        });
-                    }
-                    Ok(vec![left_sum.div_euclid(right_sum)])
-                }
-            }
+/// Rolls the explosions of each die
+///
+/// Standard and penetrating explosions add new dice directly after the die
+/// that exploded, with penetrating dice showing one less than rolled.
+/// Compounding explosions add their rolls into the die that exploded. The
+/// condition is always checked against the rolled value.
+fn explode_dice<R: Rng + ?Sized>(
+    dice: Vec<Die>,
+    die_type: &DieType,
+    condition: &ExplodeCondition,
+    explode_type: ExplodeType,
+    limits: &RollLimits,
+    rng: &mut R,
+) -> Result<Vec<Die>, DiceError> {
+    let mut all_dice = Vec::with_capacity(dice.len());
+
+    for mut die in dice {
+        let mut current_roll = die.face;
+        let mut explosions = Vec::new();
+
+        while condition.matches(current_roll, die_type.max_face())
+            && explosions.len() < limits.max_explosions
+        {
+            current_roll = die_type.roll(rng);
+            explosions.push(current_roll);
         }
 
-        DiceExpression::Repeat { expression, times } => {
-            let mut roll_results = Vec::new();
-            for _ in 0..*times {
-                let results = evaluate(expression)?;
-                let sum: i32 = results.iter().sum();
-                roll_results.push(sum);
+        if explode_type == ExplodeType::Compounding {
+            die.face = explosions
+                .iter()
+                .try_fold(die.face, |face, &roll| face.checked_add(roll))
+                .ok_or(DiceError::Overflow {
+                    operation: "compounding explosion",
+                })?;
+            die.compounded = explosions;
+            all_dice.push(die);
+            continue;
+        }
+
+        all_dice.push(die);
+        for roll in explosions {
+            let face = if explode_type == ExplodeType::Penetrating {
+                roll.checked_sub(1).ok_or(DiceError::Overflow {
+                    operation: "penetrating explosion",
+                })?
+            } else {
+                roll
+            };
+            let mut explosion = Die::new(face, die_type.sides());
+            explosion.exploded = true;
+            all_dice.push(explosion);
+        }
+    }
+
+    Ok(all_dice)
+}
+
+/// Applies a keep or drop modifier to the dice that are still kept
+///
+/// The kept dice are sorted so the selected ones come first, followed by the
+/// dice this modifier drops and then those dropped by earlier modifiers.
+fn select_dice(dice: &mut Vec<Die>, modifier: &Modifier) {
+    let (mut kept, mut dropped): (Vec<Die>, Vec<Die>) = dice.drain(..).partition(|die| die.kept);
+    let keep = order_selection(&mut kept, modifier, |die| die.face);
+    mark_dropped(&mut kept, keep);
+    kept.append(&mut dropped);
+    *dice = kept;
+}
+
+/// Orders `items` so a keep/drop `modifier` keeps the first ones, returning how many
+///
+/// Modifiers other than selections keep every item.
+fn order_selection<I, K: Ord>(
+    items: &mut [I],
+    modifier: &Modifier,
+    key: impl Fn(&I) -> K,
+) -> usize {
+    let available = items.len();
+    match modifier {
+        Modifier::KeepHighest(keep) => {
+            items.sort_by_key(|item| Reverse(key(item))); // Sort descending (highest first)
+            *keep
+        }
+        Modifier::KeepLowest(keep) => {
+            items.sort_by_key(|item| key(item)); // Sort ascending (lowest first)
+            *keep
+        }
+        Modifier::KeepMiddle(keep) => {
+            // Sort descending, then move the items above the middle to the
+            // end, taking an odd one out from the top
+            items.sort_by_key(|item| Reverse(key(item)));
+            items.rotate_left((available.saturating_sub(*keep) + 1) / 2);
+            *keep
+        }
+        Modifier::DropHighest(drop) => {
+            items.sort_by_key(|item| key(item)); // Keep all but the highest
+            available.saturating_sub(*drop)
+        }
+        Modifier::DropLowest(drop) => {
+            items.sort_by_key(|item| Reverse(key(item))); // Keep all but the lowest
+            available.saturating_sub(*drop)
+        }
+        _ => available,
+    }
+}
+
+/// Flags the kept dice meeting the term's critical success and failure conditions
+fn mark_criticals(dice: &mut [Die], die_type: &DieType, modifiers: &[Modifier]) {
+    for modifier in modifiers {
+        let (condition, natural) = match modifier {
+            Modifier::CriticalSuccess(condition) => (condition, die_type.max_face()),
+            Modifier::CriticalFailure(condition) => (condition, die_type.min_face()),
+            _ => continue,
+        };
+        let success = matches!(modifier, Modifier::CriticalSuccess(_));
+        for die in dice.iter_mut().filter(|die| die.kept) {
+            let critical = condition
+                .as_ref()
+                .map_or(die.face == natural, |condition| condition.matches(die.face));
+            if success {
+                die.critical_success = critical;
+            } else {
+                die.critical_failure = critical;
             }
-            Ok(roll_results)
         }
+    }
+}
+
+/// Counts the kept dice meeting the success condition, minus those meeting the failure condition
+fn count_successes<T: Total>(
+    mut dice: Vec<Die>,
+    success: &Condition,
+    failure: Option<&Condition>,
+) -> Result<RollResult<T>, DiceError> {
+    for die in dice.iter_mut().filter(|die| die.kept) {
+        die.success = success.matches(die.face);
+        die.failure = failure.is_some_and(|failure| failure.matches(die.face));
+    }
+
+    let total = net_successes(
+        dice.iter()
+            .filter(|die| die.kept)
+            .map(|die| (die.success, die.failure)),
+    )?;
+    Ok(RollResult::Successes { dice, total })
+}
+
+/// Counts one per success and minus one per failure in `flags`
+fn net_successes<T: Total>(flags: impl IntoIterator<Item = (bool, bool)>) -> Result<T, DiceError> {
+    flags
+        .into_iter()
+        .try_fold(T::default(), |net, (success, failure)| {
+            let net = if success {
+                net.checked_add(T::from(1))?
+            } else {
+                net
+            };
+            if failure {
+                net.checked_sub(T::from(1))
+            } else {
+                Some(net)
+            }
+        })
+        .ok_or(DiceError::Overflow {
+            operation: "success count",
+        })
+}
+
+/// Rolls `count` dice of the given type
+fn roll_dice<R: Rng + ?Sized>(count: usize, die_type: &DieType, rng: &mut R) -> Vec<Die> {
+    (0..count)
+        .map(|_| Die::new(die_type.roll(rng), die_type.sides()))
+        .collect()
+}
 
-        DiceExpression::Constant(value) => Ok(vec![*value]),
+/// Marks every die after the first `keep` dice as dropped
+fn mark_dropped(dice: &mut [Die], keep: usize) {
+    for die in dice.iter_mut().skip(keep) {
+        die.kept = false;
     }
 }
 
+/// Builds a dice result whose total is the sum of the kept dice
+fn dice_result<T: Total>(dice: Vec<Die>) -> Result<RollResult<T>, DiceError> {
+    let faces = dice
+        .iter()
+        .filter(|die| die.kept)
+        .map(|die| T::from(die.face));
+    let total = checked_sum(faces, "sum of dice")?;
+    Ok(RollResult::Dice { dice, total })
+}
+
+/// Sums `values`, reporting overflow as an error naming `operation`
+fn checked_sum<T: Total>(
+    values: impl IntoIterator<Item = T>,
+    operation: &'static str,
+) -> Result<T, DiceError> {
+    values
+        .into_iter()
+        .try_fold(T::default(), T::checked_add)
+        .ok_or(DiceError::Overflow { operation })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::parser::DiceParser;
+    use crate::value::Value;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
 
     #[test]
     fn test_evaluate_simple_dice() {
@@ -296,7 +1008,7 @@ mod tests {
 
         assert_eq!(results.len(), 2);
         for &result in &results {
-            assert!(result >= 1 && result <= 6);
+            assert!((1..=6).contains(&result));
         }
     }
 
@@ -312,7 +1024,7 @@ mod tests {
             assert!(results[i - 1] >= results[i]);
         }
         for &result in &results {
-            assert!(result >= 1 && result <= 6);
+            assert!((1..=6).contains(&result));
         }
     }
 
@@ -325,7 +1037,7 @@ mod tests {
         assert_eq!(results.len(), 3); // 2 dice + 1 constant
                                       // First two should be dice results
         for &result in &results[0..2] {
-            assert!(result >= 1 && result <= 6);
+            assert!((1..=6).contains(&result));
         }
         // Last should be the constant
         assert_eq!(results[2], 3);
@@ -363,7 +1075,7 @@ mod tests {
 
         assert_eq!(results.len(), 1);
         let success_count = results[0];
-        assert!(success_count >= 0 && success_count <= 5);
+        assert!((0..=5).contains(&success_count));
     }
 
     #[test]
@@ -375,7 +1087,7 @@ mod tests {
         // Should have at least 2 dice (the original rolls)
         assert!(results.len() >= 2);
         for &result in &results {
-            assert!(result >= 1 && result <= 6);
+            assert!((1..=6).contains(&result));
         }
     }
 
@@ -411,4 +1123,1133 @@ mod tests {
                                       // Result should be between 1*2=2 and 6*8=48
         assert!(results[0] >= 2 && results[0] <= 48);
     }
+
+    #[test]
+    fn test_evaluate_with_seeded_rng_is_reproducible() {
+        let mut parser = DiceParser::new("4d6K3 + 2d8! - 1d4r1");
+        let expr = parser.parse().unwrap();
+
+        let first = evaluate_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(42)).unwrap();
+        let second = evaluate_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(42)).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_evaluate_repeat_uses_provided_rng() {
+        let mut parser = DiceParser::new("10d20x10");
+        let expr = parser.parse().unwrap();
+
+        let first = evaluate_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+        let second = evaluate_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_evaluate_detailed_marks_dropped_dice() {
+        let mut parser = DiceParser::new("5d6X2");
+        let expr = parser.parse().unwrap();
+        let result = evaluate_detailed(&expr).unwrap();
+
+        let dice = result.dice();
+        assert_eq!(dice.len(), 5);
+        assert_eq!(dice.iter().filter(|die| die.is_dropped()).count(), 2);
+
+        // Dropped dice are never lower than any kept die
+        let highest_kept = dice.iter().filter(|die| die.kept).map(|die| die.face).max();
+        for die in dice.iter().filter(|die| die.is_dropped()) {
+            assert!(Some(die.face) >= highest_kept);
+        }
+        assert_eq!(result.total(), result.values().iter().sum::<i32>());
+    }
+
+    #[test]
+    fn test_evaluate_detailed_drops_lowest_dice() {
+        let mut parser = DiceParser::new("6d6dl2");
+        let expr = parser.parse().unwrap();
+        let result = evaluate_detailed(&expr).unwrap();
+
+        let dice = result.dice();
+        assert_eq!(dice.iter().filter(|die| die.is_dropped()).count(), 2);
+
+        // Dropped dice are never higher than any kept die
+        let lowest_kept = dice.iter().filter(|die| die.kept).map(|die| die.face).min();
+        for die in dice.iter().filter(|die| die.is_dropped()) {
+            assert!(Some(die.face) <= lowest_kept);
+        }
+        assert_eq!(result.values().len(), 4);
+    }
+
+    #[test]
+    fn test_evaluate_detailed_flags_exploded_dice() {
+        let mut parser = DiceParser::new("3d6!>3");
+        let expr = parser.parse().unwrap();
+
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let dice = result.dice();
+
+            // Every die rolled because of an explosion follows a die above 3
+            assert!(!dice[0].exploded);
+            for pair in dice.windows(2) {
+                assert_eq!(pair[1].exploded, pair[0].face > 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_compounds_explosions_into_one_die() {
+        let mut parser = DiceParser::new("4d6!!");
+        let expr = parser.parse().unwrap();
+
+        for seed in 0..50 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let dice = result.dice();
+            assert_eq!(dice.len(), 4);
+
+            // Every roll but the last was a 6, and they all add into the face
+            for die in &dice {
+                let first = die.face - die.compounded.iter().sum::<i32>();
+                let rolls: Vec<i32> = std::iter::once(first)
+                    .chain(die.compounded.iter().copied())
+                    .collect();
+                let (last, exploding) = rolls.split_last().unwrap();
+                assert!(exploding.iter().all(|&roll| roll == 6));
+                assert!((1..=5).contains(last));
+                assert!(!die.exploded);
+            }
+            assert_eq!(result.total(), dice.iter().map(|die| die.face).sum::<i32>());
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_penetrating_explosions_subtract_one() {
+        let mut parser = DiceParser::new("3d6!p");
+        let expr = parser.parse().unwrap();
+
+        for seed in 0..50 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let dice = result.dice();
+
+            // The condition checks the rolled value, before the penalty
+            let rolled = |die: &Die| die.face + i32::from(die.exploded);
+            assert!(!dice[0].exploded);
+            for pair in dice.windows(2) {
+                assert_eq!(pair[1].exploded, rolled(pair[0]) == 6);
+            }
+            for die in dice.iter().filter(|die| die.exploded) {
+                assert!((0..=5).contains(&die.face));
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_holds_hand_built_expressions_to_default_limits() {
+        let dice = |count, sides| DiceExpression::Dice {
+            count,
+            die: DieType::Numbered(sides),
+            modifiers: Vec::new(),
+        };
+        let deep = (0..200).fold(dice(1, 6), |expr, _| DiceExpression::Negate(Box::new(expr)));
+
+        assert_eq!(
+            evaluate(&dice(1_000_000, 6)),
+            Err(DiceError::TooManyDice {
+                count: 1_000_000,
+                max: 25
+            })
+        );
+        assert_eq!(
+            evaluate(&dice(1, i32::MAX)),
+            Err(DiceError::TooManySides {
+                sides: i32::MAX,
+                max: 1_000_000
+            })
+        );
+        assert_eq!(
+            evaluate_detailed(&deep),
+            Err(DiceError::ExpressionTooDeep { max: 100 })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_rejects_hand_built_dice_without_sides() {
+        let cases = [
+            (DieType::Numbered(0), "0"),
+            (DieType::Numbered(-5), "-5"),
+            (DieType::Custom(Vec::new()), "{}"),
+        ];
+        for (die, size) in cases {
+            let expected = Err(DiceError::InvalidDieSize {
+                size: size.to_string(),
+            });
+            let dice = DiceExpression::Dice {
+                count: 2,
+                die: die.clone(),
+                modifiers: Vec::new(),
+            };
+            let dynamic = DiceExpression::DynamicDice {
+                count: Box::new(DiceExpression::Constant(2)),
+                die: DynamicDie::Fixed(die),
+                modifiers: Vec::new(),
+            };
+            assert_eq!(evaluate(&dice), expected);
+            assert_eq!(evaluate(&dynamic), expected);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_reports_overflow_in_compounding_explosion() {
+        let expr = DiceParser::new("1d1000000!!>0").parse().unwrap();
+        let limits = RollLimits::default().with_max_explosions(5000);
+        let result: Result<RollResult, _> =
+            evaluate_with_limits(&expr, &limits, &mut ChaCha8Rng::seed_from_u64(2));
+
+        assert_eq!(
+            result,
+            Err(DiceError::Overflow {
+                operation: "compounding explosion"
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_reports_overflow_in_penetrating_explosion() {
+        let expr = DiceParser::new("1d{-2147483648}!p{-2147483648}")
+            .parse()
+            .unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(2));
+
+        assert_eq!(
+            result,
+            Err(DiceError::Overflow {
+                operation: "penetrating explosion"
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_detailed_rolls_fate_and_percentile_dice() {
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let cases = [
+            ("20dF", -1..=1, 6),
+            ("20dF.1", -1..=1, 6),
+            ("20d%", 1..=100, 100),
+        ];
+
+        for (notation, faces, sides) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            let result: RollResult =
+                evaluate_with_limits(&expr, &RollLimits::default(), &mut rng).unwrap();
+            for die in result.dice() {
+                assert!(faces.contains(&die.face), "{notation} rolled {}", die.face);
+                assert_eq!(die.sides, sides);
+            }
+            assert_eq!(result.total(), result.values().iter().sum::<i32>());
+        }
+    }
+
+    #[test]
+    fn test_evaluate_custom_faces_with_modifiers() {
+        let faces = [1, 1, 2, 3, 5, 8];
+        let mut rng = ChaCha8Rng::seed_from_u64(9);
+
+        let keep = DiceParser::new("4d{1,1,2,3,5,8}K2").parse().unwrap();
+        let exploding = DiceParser::new("4d{1,1,2,3,5,8}!").parse().unwrap();
+        let rerolling = DiceParser::new("4d{1,1,2,3,5,8}R1").parse().unwrap();
+        let successes = DiceParser::new("4d{1,1,2,3,5,8}>4").parse().unwrap();
+
+        for _ in 0..20 {
+            let result: RollResult =
+                evaluate_with_limits(&keep, &RollLimits::default(), &mut rng).unwrap();
+            assert!(result.dice().iter().all(|die| faces.contains(&die.face)));
+            assert!(result.dice().iter().all(|die| die.sides == 6));
+            assert_eq!(result.values().len(), 2);
+
+            let result: RollResult =
+                evaluate_with_limits(&exploding, &RollLimits::default(), &mut rng).unwrap();
+            for pair in result.dice().windows(2) {
+                assert_eq!(pair[1].exploded, pair[0].face == 8);
+            }
+
+            let result: RollResult =
+                evaluate_with_limits(&rerolling, &RollLimits::default(), &mut rng).unwrap();
+            assert!(result.dice().iter().all(|die| die.face != 1));
+
+            let result: RollResult =
+                evaluate_with_limits(&successes, &RollLimits::default(), &mut rng).unwrap();
+            for die in result.dice() {
+                assert_eq!(die.success, die.face > 4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_fate_dice_explode_on_plus() {
+        let mut parser = DiceParser::new("4dF!");
+        let expr = parser.parse().unwrap();
+
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            for pair in result.dice().windows(2) {
+                assert_eq!(pair[1].exploded, pair[0].face == 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_records_rerolled_faces() {
+        let mut parser = DiceParser::new("10d4R1");
+        let expr = parser.parse().unwrap();
+        let result = evaluate_detailed(&expr).unwrap();
+
+        for die in result.dice() {
+            assert_ne!(die.face, 1);
+            assert!(die.rerolled_from.iter().all(|&face| face == 1));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_flags_successes_and_failures() {
+        let mut parser = DiceParser::new("10d10>6f<3");
+        let expr = parser.parse().unwrap();
+        let result = evaluate_detailed(&expr).unwrap();
+
+        let mut net = 0;
+        for die in result.dice() {
+            assert_eq!(die.success, die.face > 6);
+            assert_eq!(die.failure, die.face < 3);
+            net += i32::from(die.success) - i32::from(die.failure);
+        }
+        assert!(matches!(result, RollResult::Successes { .. }));
+        assert_eq!(result.total(), net);
+    }
+
+    #[test]
+    fn test_evaluate_detailed_applies_full_conditions() {
+        let expr = DiceParser::new("10d10>=8f{1,2}").parse().unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(19)).unwrap();
+        for die in result.dice() {
+            assert_eq!(die.success, die.face >= 8);
+            assert_eq!(die.failure, die.face <= 2);
+        }
+
+        let expr = DiceParser::new("20d6R{1,2}").parse().unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(20)).unwrap();
+        assert!(result.dice().iter().all(|die| die.face >= 3));
+
+        // Every die other than a 6 explodes into one more die
+        let expr = DiceParser::new("5d6!!=6").parse().unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(21)).unwrap();
+        let dice = result.dice();
+        assert_eq!(
+            dice.iter().filter(|die| die.exploded).count(),
+            dice.iter().filter(|die| die.face != 6).count()
+        );
+        assert_eq!(
+            dice.len() - 5,
+            dice.iter().filter(|die| die.exploded).count()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_detailed_marks_criticals() {
+        let expr = DiceParser::new("20d20cs>18cf").parse().unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(22)).unwrap();
+        for die in result.dice() {
+            assert_eq!(die.critical_success, die.face > 18);
+            assert_eq!(die.critical_failure, die.face == 1);
+        }
+        assert_eq!(
+            result.has_critical_success(),
+            result.dice().iter().any(|die| die.face > 18)
+        );
+        assert_eq!(
+            result.has_critical_failure(),
+            result.dice().iter().any(|die| die.face == 1)
+        );
+
+        // Only kept dice are marked, and only on terms that ask for criticals
+        let expr = DiceParser::new("6d20K1cscf + 6d20").parse().unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(23)).unwrap();
+        let RollResult::Binary { left, right, .. } = &result else {
+            panic!("Expected a binary result, got {result:?}");
+        };
+        assert!(left
+            .dice()
+            .iter()
+            .all(|die| die.kept || !(die.critical_success || die.critical_failure)));
+        assert!(right
+            .dice()
+            .iter()
+            .all(|die| !(die.critical_success || die.critical_failure)));
+
+        let expr = DiceParser::new("4dFcscf").parse().unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(24)).unwrap();
+        for die in result.dice() {
+            assert_eq!(die.critical_success, die.face == 1);
+            assert_eq!(die.critical_failure, die.face == -1);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_chains_reroll_then_keep() {
+        let mut parser = DiceParser::new("4d6r1K3");
+        let expr = parser.parse().unwrap();
+
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let dice = result.dice();
+
+            // Only a first-roll 1 is rerolled, and selection sees the rerolled faces
+            for die in &dice {
+                assert!(die.rerolled_from.iter().all(|&face| face == 1));
+                assert!(die.rerolled_from.len() <= 1);
+            }
+            assert_eq!(dice.iter().filter(|die| die.is_dropped()).count(), 1);
+            let lowest_kept = dice.iter().filter(|die| die.kept).map(|die| die.face).min();
+            assert!(dice
+                .iter()
+                .filter(|die| die.is_dropped())
+                .all(|die| Some(die.face) <= lowest_kept));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_chains_explode_then_count_successes() {
+        let mut parser = DiceParser::new("5d10!>8>7");
+        let expr = parser.parse().unwrap();
+
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let dice = result.dice();
+
+            // Explosion dice are counted like any other die
+            assert!(dice.len() >= 5);
+            for die in &dice {
+                assert_eq!(die.success, die.face > 7);
+            }
+            let successes = dice.iter().filter(|die| die.success).count();
+            assert_eq!(result.total(), i32::try_from(successes).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_applies_selections_in_order() {
+        let mut parser = DiceParser::new("6d6R1X2dl1");
+        let expr = parser.parse().unwrap();
+
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let mut faces: Vec<i32> = result.dice().iter().map(|die| die.face).collect();
+            faces.sort_unstable();
+
+            // Drop the highest 2, then the lowest of the remaining 4
+            assert!(faces.iter().all(|&face| face != 1));
+            assert_eq!(result.total(), faces[1..4].iter().sum::<i32>());
+            assert_eq!(result.values().len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_keeps_middle_dice() {
+        // Sorted from lowest, the ranks each notation keeps
+        for (notation, ranks) in [("6d6km4", 1..5), ("6d6dh1dl1", 1..5), ("6d6km3", 1..4)] {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            for seed in 0..20 {
+                let result =
+                    evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed))
+                        .unwrap();
+                let dice = result.dice();
+                assert_eq!(dice.len(), 6, "Every die is reported for '{notation}'");
+
+                let mut faces: Vec<i32> = dice.iter().map(|die| die.face).collect();
+                faces.sort_unstable();
+                let mut kept: Vec<i32> = dice
+                    .iter()
+                    .filter(|die| die.kept)
+                    .map(|die| die.face)
+                    .collect();
+                kept.sort_unstable();
+                assert_eq!(kept, faces[ranks.clone()], "Kept dice for '{notation}'");
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_combines_reroll_once_conditions() {
+        let mut parser = DiceParser::new("20d20r1r2");
+        let expr = parser.parse().unwrap();
+        let result = evaluate_detailed(&expr).unwrap();
+
+        for die in result.dice() {
+            assert!(die.rerolled_from.len() <= 1);
+            assert!(die.rerolled_from.iter().all(|&face| face <= 2));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_detailed_keeps_subtracted_terms_positive() {
+        let mut parser = DiceParser::new("2d6 - 1d4");
+        let expr = parser.parse().unwrap();
+        let result = evaluate_detailed(&expr).unwrap();
+
+        match &result {
+            RollResult::Binary { left, right, .. } => {
+                assert!(right.total() > 0);
+                assert_eq!(result.total(), left.total() - right.total());
+            }
+            _ => panic!("Expected binary result"),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_with_limits_caps_explosions_and_rerolls() {
+        let limits = RollLimits::default()
+            .with_max_explosions(0)
+            .with_max_rerolls(2);
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+
+        let exploding = DiceParser::new("20d2!").parse().unwrap();
+        let result: RollResult = evaluate_with_limits(&exploding, &limits, &mut rng).unwrap();
+        assert_eq!(result.dice().len(), 20);
+
+        let rerolling = DiceParser::new("20d2R<3").parse().unwrap();
+        let result: RollResult = evaluate_with_limits(&rerolling, &limits, &mut rng).unwrap();
+        for die in result.dice() {
+            assert_eq!(die.rerolled_from.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_reports_overflow_with_operation() {
+        let cases = [
+            ("2147483647 + 1", "addition"),
+            ("-2147483648 - 1", "subtraction"),
+            ("65536 * 65536", "multiplication"),
+            ("-2147483648 / -1", "division"),
+            ("-2147483648 // -1", "floor division"),
+        ];
+
+        for (notation, operation) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(
+                evaluate(&expr),
+                Err(DiceError::Overflow { operation }),
+                "Expected overflow for '{notation}'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_reports_overflow_in_dice_sum() {
+        let expr = DiceExpression::Dice {
+            count: 5000,
+            die: DieType::Numbered(1_000_000),
+            modifiers: vec![],
+        };
+        let limits = RollLimits::default().with_max_total_dice(5000);
+        let mut rng = ChaCha8Rng::seed_from_u64(8);
+
+        let narrow = evaluate_with_limits::<i32, _>(&expr, &limits, &mut rng);
+        let wide = evaluate_with_limits::<i64, _>(&expr, &limits, &mut rng).unwrap();
+
+        assert_eq!(
+            narrow,
+            Err(DiceError::Overflow {
+                operation: "sum of dice"
+            })
+        );
+        assert!(wide.total() > i64::from(i32::MAX));
+        assert_eq!(wide.total(), wide.values().iter().sum::<i64>());
+    }
+
+    #[test]
+    fn test_evaluate_with_rng_matches_detailed_values() {
+        let mut parser = DiceParser::new("4d6K3 + 2d8! - 1d4r1 + 3d6x2 * 2");
+        let expr = parser.parse().unwrap();
+
+        let values = evaluate_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(3)).unwrap();
+        let detailed =
+            evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(3)).unwrap();
+
+        assert_eq!(values, detailed.values());
+    }
+
+    fn total(notation: &str) -> Result<i32, DiceError> {
+        let expr = DiceParser::new(notation).parse().unwrap();
+        evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(15)).map(|r| r.total())
+    }
+
+    #[test]
+    fn test_evaluate_functions_on_constants() {
+        let cases = [
+            ("max(3, 9, -2)", 9),
+            ("min(3, 9, -2)", -2),
+            ("abs(-7)", 7),
+            ("abs(4)", 4),
+            ("clamp(25, 1, 20)", 20),
+            ("clamp(-3, 1, 20)", 1),
+            ("clamp(7, 1, 20)", 7),
+            ("sum(1, 2, 3) * 2", 12),
+            ("sort(3)", 3),
+            ("floor(7)", 7),
+            ("max(1, 1d4 - 10)", 1),
+        ];
+
+        for (notation, expected) in cases {
+            assert_eq!(total(notation), Ok(expected), "Evaluating '{notation}'");
+        }
+    }
+
+    #[test]
+    fn test_evaluate_rounding_functions_round_division() {
+        let cases = [
+            ("7 / 2", 3, 4, 4),
+            ("-7 / 2", -4, -3, -4),
+            ("7 / -2", -4, -3, -4),
+            ("8 / 3", 2, 3, 3),
+            ("7 / 3", 2, 3, 2),
+            ("-8 / 3", -3, -2, -3),
+            ("6 / 3", 2, 2, 2),
+            ("7 // 2", 3, 4, 4),
+        ];
+
+        for (division, floor, ceil, round) in cases {
+            assert_eq!(
+                total(&format!("floor({division})")),
+                Ok(floor),
+                "floor({division})"
+            );
+            assert_eq!(
+                total(&format!("ceil({division})")),
+                Ok(ceil),
+                "ceil({division})"
+            );
+            assert_eq!(
+                total(&format!("round({division})")),
+                Ok(round),
+                "round({division})"
+            );
+        }
+
+        assert_eq!(total("ceil(1d6 / 0)"), Err(DiceError::DivisionByZero));
+        assert_eq!(
+            total("abs(-2147483647 - 1)"),
+            Err(DiceError::Overflow {
+                operation: "absolute value"
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_detailed_function_records_arguments() {
+        let expr = DiceParser::new("max(1d20, 1d20) + sum(2d6, 3)")
+            .parse()
+            .unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(4)).unwrap();
+
+        let RollResult::Binary { left, right, .. } = &result else {
+            panic!("Expected a binary result, got {result:?}");
+        };
+        let RollResult::Function { args, total, .. } = left.as_ref() else {
+            panic!("Expected a function result, got {left:?}");
+        };
+        assert_eq!(args.len(), 2);
+        assert_eq!(*total, args.iter().map(RollResult::total).max().unwrap());
+        assert_eq!(result.dice().len(), 4);
+
+        // max() contributes its total, sum() its arguments' values
+        let values = result.values();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[0], *total);
+        assert_eq!(values[3], 3);
+        assert_eq!(right.total(), values[1] + values[2] + 3);
+    }
+
+    #[test]
+    fn test_evaluate_rejects_wrong_argument_count() {
+        let expr = DiceExpression::Function {
+            function: Function::Clamp,
+            args: vec![DiceExpression::Constant(1)],
+        };
+
+        assert_eq!(
+            evaluate(&expr),
+            Err(DiceError::WrongArgumentCount {
+                function: Function::Clamp,
+                count: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_unary_minus() {
+        let cases = [
+            ("-3 + 10", 7),
+            ("- 3 + 10", 7),
+            ("2 - -3", 5),
+            ("2--3", 5),
+            ("-(2 * 3)", -6),
+            ("--4", 4),
+            ("-2 * -3", 6),
+        ];
+        for (notation, expected) in cases {
+            assert_eq!(total(notation), Ok(expected), "Evaluating '{notation}'");
+        }
+
+        for _ in 0..20 {
+            let penalty = total("-1d6 + 10").unwrap();
+            assert!((4..=9).contains(&penalty));
+        }
+
+        assert_eq!(
+            total("-(-2147483647 - 1)"),
+            Err(DiceError::Overflow {
+                operation: "negation"
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_detailed_negates_operand_values() {
+        let expr = DiceParser::new("-(2d4) + 1").parse().unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(16)).unwrap();
+
+        let values = result.values();
+        assert_eq!(values.len(), 3);
+        assert!(values[..2].iter().all(|value| (-4..=-1).contains(value)));
+        assert_eq!(result.total(), values.iter().sum::<i32>());
+        assert_eq!(result.dice().len(), 2);
+        assert!(result.dice().iter().all(|die| die.face > 0));
+    }
+
+    #[test]
+    fn test_evaluate_modulo_and_power() {
+        let cases = [
+            ("17 % 5", 2),
+            ("-7 % 3", 2),
+            ("7 % -3", 1),
+            ("2 ^ 10", 1024),
+            ("2 ** 3 ** 2", 512),
+            ("-3 ^ 2", -9),
+            ("(-3) ^ 2", 9),
+            ("(-2) ^ 3", -8),
+            ("5 ^ 0", 1),
+            ("2 ^ -1", 0),
+            ("1 ^ -5", 1),
+            ("(-1) ^ -3", -1),
+            ("(-1) ^ -4", 1),
+            ("1 + 2 * 3 ^ 2 % 5", 4),
+        ];
+        for (notation, expected) in cases {
+            assert_eq!(total(notation), Ok(expected), "Evaluating '{notation}'");
+        }
+
+        for _ in 0..20 {
+            let ones = total("1d100 % 10").unwrap();
+            assert!((0..=9).contains(&ones));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_modulo_and_power_errors() {
+        assert_eq!(total("1d6 % 0"), Err(DiceError::DivisionByZero));
+        assert_eq!(total("0 ^ -1"), Err(DiceError::DivisionByZero));
+        assert_eq!(
+            total("2 ^ 31"),
+            Err(DiceError::Overflow {
+                operation: "exponentiation"
+            })
+        );
+        assert_eq!(
+            total("(-2147483647 - 1) % -1"),
+            Err(DiceError::Overflow {
+                operation: "modulo"
+            })
+        );
+
+        let expr = DiceParser::new("2 ^ 31").parse().unwrap();
+        let wide = evaluate_with_limits::<i64, _>(
+            &expr,
+            &RollLimits::default(),
+            &mut ChaCha8Rng::seed_from_u64(17),
+        )
+        .unwrap();
+        assert_eq!(wide.total(), 1 << 31);
+    }
+
+    #[test]
+    fn test_evaluate_comparisons() {
+        let cases = [
+            ("3 > 2", 1),
+            ("2 > 2", 0),
+            ("2 >= 2", 1),
+            ("2 < 2", 0),
+            ("2 <= 2", 1),
+            ("2 = 2", 1),
+            ("2 == 3", 0),
+            ("2 != 3", 1),
+            ("(3 > 2) + (1 > 2) + (2 >= 2)", 2),
+            ("1 ? 5 : 7", 5),
+            ("0 ? 5 : 7", 7),
+            ("-2 ? 5 : 7", 5),
+            ("0 ? 1 : 0 ? 2 : 3", 3),
+            ("1d20 + 20 >= 21 ? 10 : 0", 10),
+        ];
+        for (notation, expected) in cases {
+            assert_eq!(total(notation), Ok(expected), "Evaluating '{notation}'");
+        }
+
+        let expr = DiceParser::new("1d20 + 5 >= 15").parse().unwrap();
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let attack = result.dice()[0].face + 5;
+            assert_eq!(result.passed(), Some(attack >= 15));
+            assert_eq!(result.total(), i32::from(attack >= 15));
+            assert_eq!(result.values(), vec![result.total()]);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_spaced_comparisons_compare_whole_totals() {
+        // A spaced comparison after arithmetic ending in a dice term compares
+        // the whole total, it does not count the last term's successes
+        for notation in ["5 + 1d20 >= 15", "1d20 + 1d4 >= 15", "2d20 >= 15 ? 1 : 0"] {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            let comparison = match &expr {
+                DiceExpression::Conditional { condition, .. } => condition.as_ref(),
+                expr => expr,
+            };
+            assert!(
+                matches!(comparison, DiceExpression::Compare { .. }),
+                "Unexpected parse for '{notation}': {expr:?}"
+            );
+
+            for seed in 0..20 {
+                let result =
+                    evaluate_detailed_with_rng(comparison, &mut ChaCha8Rng::seed_from_u64(seed))
+                        .unwrap();
+                let RollResult::Compare { left, .. } = &result else {
+                    panic!("Expected a comparison for '{notation}', got {result:?}");
+                };
+                let passed = left.total() >= 15;
+                assert_eq!(result.total(), i32::from(passed), "Total of '{notation}'");
+
+                let value =
+                    evaluate_value_with_rng(comparison, &mut ChaCha8Rng::seed_from_u64(seed))
+                        .unwrap();
+                assert_eq!(value, Value::Boolean(passed), "Value of '{notation}'");
+
+                let total = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed))
+                    .unwrap()
+                    .total();
+                assert!(total == 0 || total == 1, "Total of '{notation}'");
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_conditional_rolls_only_chosen_branch() {
+        let expr = DiceParser::new("1d20 >= 11 ? 2d6 : 1d4").parse().unwrap();
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let dice = result.dice();
+            let hit = dice[0].face >= 11;
+            assert_eq!(dice.len(), if hit { 3 } else { 2 });
+            assert_eq!(result.passed(), None);
+
+            let damage: i32 = dice[1..].iter().map(|die| die.face).sum();
+            assert_eq!(result.total(), damage);
+            assert_eq!(result.values().iter().sum::<i32>(), damage);
+        }
+
+        // The branch not taken is never evaluated
+        assert_eq!(total("1 ? 4 : 1 / 0"), Ok(4));
+        assert_eq!(total("0 ? 1 / 0 : 4"), Ok(4));
+    }
+
+    #[test]
+    fn test_evaluate_dynamic_dice_rolls_parts_first() {
+        let expr = DiceParser::new("(1d4)d6").parse().unwrap();
+        let keep = DiceParser::new("4d6K(1d3)").parse().unwrap();
+        for seed in 0..20 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let result = evaluate_detailed_with_rng(&expr, &mut rng).unwrap();
+            let RollResult::DynamicDice { parts, dice, total } = &result else {
+                panic!("Expected a dynamic dice result, got {result:?}");
+            };
+            assert_eq!(parts.len(), 1);
+            assert_eq!(
+                dice.dice().len(),
+                usize::try_from(parts[0].total()).unwrap()
+            );
+            assert_eq!(*total, dice.total());
+            assert_eq!(result.values(), dice.values());
+            // The count die is reported before the dice it rolled
+            assert_eq!(result.dice().len(), dice.dice().len() + 1);
+
+            let result = evaluate_detailed_with_rng(&keep, &mut rng).unwrap();
+            let RollResult::DynamicDice { parts, dice, .. } = &result else {
+                panic!("Expected a dynamic dice result, got {result:?}");
+            };
+            // The written-out count is a part too, rolled before the keep count
+            assert_eq!(parts[0], RollResult::Constant(4));
+            let kept = dice.dice().iter().filter(|die| die.kept).count();
+            assert_eq!(kept, usize::try_from(parts[1].total()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_evaluate_group_keeps_member_totals() {
+        let expr = DiceParser::new("{2d6, 1d8, 1d10}K1").parse().unwrap();
+        let drop = DiceParser::new("{1d20 + 5, 1d20 + 3, 1d4}dh1kl1")
+            .parse()
+            .unwrap();
+        for seed in 0..20 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let result = evaluate_detailed_with_rng(&expr, &mut rng).unwrap();
+            let RollResult::Group { members, total } = &result else {
+                panic!("Expected a group result, got {result:?}");
+            };
+            let totals: Vec<i32> = members.iter().map(|member| member.result.total()).collect();
+            assert_eq!(*total, *totals.iter().max().unwrap());
+            assert_eq!(members.iter().filter(|member| member.kept).count(), 1);
+            // Members stay in the order they were written
+            assert_eq!(members[0].result.dice().len(), 2);
+            assert_eq!(result.values(), vec![*total]);
+            assert_eq!(result.dice().len(), 4);
+
+            let result = evaluate_detailed_with_rng(&drop, &mut rng).unwrap();
+            let mut totals: Vec<i32> = match &result {
+                RollResult::Group { members, .. } => {
+                    members.iter().map(|member| member.result.total()).collect()
+                }
+                _ => panic!("Expected a group result, got {result:?}"),
+            };
+            totals.sort_unstable();
+            assert_eq!(result.total(), totals[0]);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_group_counts_successful_totals() {
+        let expr = DiceParser::new("{3d6, 3d6, 3d6}>10f<6").parse().unwrap();
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let RollResult::GroupSuccesses { members, total } = &result else {
+                panic!("Expected a success counting group, got {result:?}");
+            };
+            let mut expected = 0;
+            for member in members {
+                let value = member.result.total();
+                assert_eq!(member.success, value > 10);
+                assert_eq!(member.failure, value < 6);
+                expected += i32::from(value > 10) - i32::from(value < 6);
+            }
+            assert_eq!(*total, expected);
+            assert_eq!(result.values(), vec![expected]);
+        }
+
+        // Only kept members are counted
+        assert_eq!(total("{1d1 + 20, 1d1 + 30}K1>10"), Ok(1));
+        assert_eq!(total("{1d1 + 20, 1d1 + 30}K1x3"), Ok(93));
+    }
+
+    #[test]
+    fn test_evaluate_repeat_list_keeps_rolls() {
+        let expr = DiceParser::new("(4d6K3x7)K6").parse().unwrap();
+        let sorted = DiceParser::new("sort((4d6K3x7)K6)").parse().unwrap();
+        for seed in 0..20 {
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let RollResult::Group { members, total } = &result else {
+                panic!("Expected a group result, got {result:?}");
+            };
+            assert_eq!(members.len(), 7);
+            let lowest = members.iter().map(|member| member.result.total()).min();
+            let dropped: Vec<_> = members.iter().filter(|member| !member.kept).collect();
+            assert_eq!(dropped.len(), 1);
+            assert_eq!(Some(dropped[0].result.total()), lowest);
+            assert_eq!(result.values().len(), 6);
+            assert_eq!(result.values().iter().sum::<i32>(), *total);
+            assert_eq!(result.dice().len(), 28);
+
+            // Sorting lists the same values, highest first
+            let sorted =
+                evaluate_detailed_with_rng(&sorted, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+            let mut values = result.values();
+            values.sort_unstable_by(|a, b| b.cmp(a));
+            assert_eq!(sorted.values(), values);
+            assert_eq!(sorted.total(), *total);
+        }
+
+        assert_eq!(total("(1d1x6)>=1"), Ok(6));
+        assert_eq!(total("(1d1x6)dl2>=1x2"), Ok(8));
+    }
+
+    #[test]
+    fn test_evaluate_value_coerces_operands() {
+        let value = |notation: &str| {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            evaluate_value_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(6)).unwrap()
+        };
+
+        // Totals for arithmetic and comparisons, a condition for conditionals
+        // and the items of a list for sum
+        assert_eq!(value("1d1x3 * 2"), Value::Scalar(6));
+        assert_eq!(value("(1d1x3) ? 4 : 5"), Value::Scalar(4));
+        assert_eq!(value("(1d1 - 1) ? 4 : 5"), Value::Scalar(5));
+        assert_eq!(value("sum(1d1x3, 2)"), Value::Scalar(5));
+        assert_eq!(value("(4d1) = 4"), Value::Boolean(true));
+        assert!(matches!(value("(1d1) = 1 ? 2d6 : 0"), Value::Pool(dice) if dice.len() == 2));
+
+        // The value and the result tree come from the same evaluation
+        for notation in [
+            "4d6K3 + 2",
+            "3d6x4",
+            "{1d6, 2d6}K1",
+            "(1d4)d6",
+            "sort(1d20x3)",
+        ] {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            let value = evaluate_value_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+            let result =
+                evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+            assert_eq!(
+                value.to_total(),
+                Ok(result.total()),
+                "Total of '{notation}'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_max_and_min_pick_items() {
+        let value = |notation: &str, seed: u64| {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            evaluate_value_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(seed)).unwrap()
+        };
+
+        // The highest or lowest roll of a repeat or die of a pool, as rolled
+        // by the same seed without the function
+        for seed in 0..20 {
+            let totals = |notation| {
+                value(notation, seed)
+                    .into_list()
+                    .iter()
+                    .map(|item| item.to_total().unwrap())
+                    .collect::<Vec<i32>>()
+            };
+            let rolls = totals("3d6x3");
+            assert_eq!(rolls.len(), 3);
+            assert_eq!(
+                value("max(3d6x3)", seed),
+                Value::Scalar(*rolls.iter().max().unwrap())
+            );
+            let dice = totals("4d6");
+            assert_eq!(dice.len(), 4);
+            assert_eq!(
+                value("min(4d6)", seed),
+                Value::Scalar(*dice.iter().min().unwrap())
+            );
+        }
+
+        // Lists flatten alongside scalar arguments
+        assert_eq!(value("max(1d1x3, 2)", 0), Value::Scalar(2));
+        assert_eq!(value("min(4d1, 2)", 0), Value::Scalar(1));
+    }
+
+    #[test]
+    fn test_evaluate_value_gives_each_kind() {
+        let value = |notation: &str| {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            evaluate_value_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(5)).unwrap()
+        };
+
+        // Arithmetic is a scalar however it is grouped
+        for notation in ["2d6 * 2 + 3", "(2d6 + 3) * 2", "2d6 + 1d4", "-2d6", "7"] {
+            assert!(
+                matches!(value(notation), Value::Scalar(_)),
+                "Value of '{notation}'"
+            );
+        }
+        assert!(matches!(value("4d6K3"), Value::Pool(dice) if dice.len() == 4));
+        assert!(matches!(value("(1d1 + 1)d6"), Value::Pool(dice) if dice.len() == 2));
+        assert_eq!(value("10d10>=1"), Value::Scalar(10));
+        assert_eq!(value("1d6 >= 1"), Value::Boolean(true));
+        assert_eq!(value("1d6 > 6"), Value::Boolean(false));
+        assert!(matches!(value("1d6 > 6 ? 2d6 : 1d4"), Value::Pool(dice) if dice[0].sides == 4));
+
+        let stats = value("3d6x4").into_list();
+        assert_eq!(stats.len(), 4);
+        assert!(stats.iter().all(|stat| matches!(stat, Value::Pool(_))));
+        assert_eq!(value("{1d6, 2d6}K1").into_list().len(), 1);
+        assert_eq!(value("(1d6x5)dl2").into_list().len(), 3);
+        assert_eq!(value("(1d6x5)>=1"), Value::Scalar(5));
+
+        let sorted = value("sort(1d20x5)");
+        let totals: Vec<i32> = sorted
+            .into_list()
+            .iter()
+            .map(|item| item.to_total().unwrap())
+            .collect();
+        assert!(totals.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(matches!(value("max(1d6, 2)"), Value::Scalar(_)));
+    }
+
+    #[test]
+    fn test_evaluate_dynamic_dice_checks_rolled_values() {
+        assert_eq!(total("(1d1 - 1)d6"), Ok(0));
+        assert_eq!(total("3d6dl(1d1 + 5)"), Ok(0));
+        let expr = DiceParser::new("3d6K(1d1 + 5)").parse().unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut ChaCha8Rng::seed_from_u64(8)).unwrap();
+        assert!(result.dice().iter().all(|die| die.kept));
+        assert_eq!(
+            total("(1d1 * 30)d6"),
+            Err(DiceError::TooManyDice { count: 30, max: 25 })
+        );
+        assert_eq!(
+            total("2d(1d1 * 2000000)"),
+            Err(DiceError::TooManySides {
+                sides: 2_000_000,
+                max: 1_000_000
+            })
+        );
+        assert_eq!(
+            total("(1d1 - 2)d6"),
+            Err(DiceError::InvalidDiceCount {
+                count: "-1".to_string()
+            })
+        );
+        assert_eq!(
+            total("2d(1d1 - 1)"),
+            Err(DiceError::InvalidDieSize {
+                size: "0".to_string()
+            })
+        );
+        assert_eq!(
+            total("4d6K(1d1 - 2)"),
+            Err(DiceError::InvalidModifier {
+                modifier: "K-1".to_string()
+            })
+        );
+
+        let relaxed = RollLimits::default().with_max_dice_per_term(40);
+        let expr = DiceParser::new("(1d1 * 30)d6").parse().unwrap();
+        let result: RollResult =
+            evaluate_with_limits(&expr, &relaxed, &mut ChaCha8Rng::seed_from_u64(3)).unwrap();
+        assert_eq!(result.dice().len(), 31);
+    }
 }
@@ -23,10 +23,13 @@
 //!
 //! - **Basic dice rolling**: Roll any number of dice with any number of sides (e.g., `4d10`, `d20`)
 //! - **Arithmetic operations**: Add, subtract, multiply, and divide dice results (e.g., `3d6 + 5`)
-//! - **Advanced mechanics**: Keep highest/lowest, drop highest, exploding dice, rerolling, success counting
-//! - **Safety limits**: Maximum of 25 dice per roll to prevent excessive resource usage
+//! - **Advanced mechanics**: Keep highest/lowest/middle, drop highest/lowest, exploding dice, rerolling, success counting,
+//!   chained on a single term (e.g., `4d6r1K3`)
+//! - **Safety limits**: Configurable [`RollLimits`] to prevent excessive resource usage
 //! - **Error handling**: Comprehensive error reporting for invalid input
-//! - **Random number generation**: Uses cryptographically secure random number generation
+//! - **Overflow safety**: Checked arithmetic, with optional `i64` totals via [`Roller::roll_i64`]
+//! - **Random number generation**: Uses cryptographically secure random number generation,
+//!   or any caller-supplied RNG via [`roll_with_rng`] for reproducible rolls
 //!
 //! # Quick Start
 //!
@@ -55,35 +58,151 @@
 //! - `1d4 * 3`: Roll 1d4 and multiply by 3
 //! - `5d6 / 2`: Roll 5d6 and divide by 2
 //! - `4d8 // 3`: Roll 4d8 and floor divide by 3
+//! - `d100 % 10`: Roll d100 and take the remainder after dividing by 10
+//! - `2 ^ 1d4`, `2 ** 1d4`: Raise 2 to the power of 1d4; powers group to the right
+//! - `-1d6 + 10`, `-(2d4)`: Negate a term
+//! - `1d20 + 5 >= 15`: Compare two totals, 1 when the comparison holds and 0 otherwise
+//! - `1d20 >= 15 ? 2d6 : 0`: Roll `2d6` when the condition is non-zero and `0` otherwise
+//! - `max(1d20, 1d20)`, `max(1, 1d4 - 2)`: Call a function; `min`, `abs`, `clamp`, `sum`
+//!   and `sort` are also available, and `floor`, `ceil` and `round` choose how a division
+//!   inside them rounds
+//! - `4dF`: Roll four Fate/Fudge dice (faces -1, 0 and +1); `dF.1` has a single -1 and +1 face
+//! - `d%`: Roll a percentile die, the same as `d100`
+//! - `d{1,1,2,3,5,8}`, `d{2..7}`: Roll a die with custom faces, one side per listed face
+//! - `2dAbility - 1dDifficulty`: Roll symbolic dice registered with a [`SymbolRegistry`]
+//! - `(1d4)d6`, `2d(1d3*4)`, `4d6K(1d3)`: Roll a count, sides or keep/drop count first;
+//!   the rolled values are checked against the limits
+//! - `{2d6, 1d8, 1d10}K1`, `{3d6, 3d6}>10`: Keep, drop or count successes among the
+//!   totals of a group of expressions
+//! - `(4d6K3x7)K6`, `(3d6x6)>=15`: Keep, drop or count successes among the rolls of a repeat
+//! - `10d10>=8f=1`, `4d6r{1,2}`, `3d6!>=5`: Success, failure, reroll and explode
+//!   [`Condition`]s take `>`, `<`, `>=`, `<=`, `=`, `!=` or a value set
+//! - `1d20cs>19cf1 + 5`, `1d20cscf`: Mark critical successes and failures on the kept dice,
+//!   reported by [`RollResult::has_critical_success`] and [`RollResult::has_critical_failure`]
+//!
+//! # Parsing and Evaluating Separately
+//!
+//! [`roll`] parses and evaluates in one step. When the same notation is rolled
+//! many times, parse it once with [`parse`] and keep the resulting
+//! [`DiceExpression`], then roll it as often as needed with [`evaluate`]:
+//!
+//! ```
+//! use rollpoly::{evaluate, parse};
+//!
+//! let expression = parse("4d6K3").unwrap();
+//! for _ in 0..6 {
+//!     let results = evaluate(&expression).unwrap();
+//!     assert_eq!(results.len(), 3);
+//! }
+//! ```
+//!
+//! # Typed Values
+//!
+//! The numbers returned by [`roll`] depend on the operators used: `2d6 + 3`
+//! lists each die, while `(2d6 + 3) * 2` is a single total. To see what an
+//! expression produced, roll it with [`roll_value`] (or [`Roller::roll_value`]),
+//! which returns a [`Value`]: a scalar, a pool of dice, a list, a boolean or a
+//! symbol tally, with explicit coercions between them.
+//!
+//! ```
+//! use rollpoly::{roll_value, Value};
+//!
+//! let value = roll_value("4d6K3x6").unwrap();
+//! let stats = value.into_list();
+//! assert_eq!(stats.len(), 6);
+//! assert!(matches!(stats[0], Value::Pool(_)));
+//! ```
+//!
+//! # Probability Distributions
+//!
+//! [`distribution`] computes the exact probability of every possible total of a
+//! parsed expression without rolling any dice:
+//!
+//! ```
+//! use rollpoly::{distribution, parse};
+//!
+//! let stats = distribution(&parse("3d6").unwrap()).unwrap();
+//! assert!((stats.mean() - 10.5).abs() < 1e-9);
+//! ```
 //!
 //! # Safety Limits
 //!
 //! To prevent excessive resource usage and potential abuse, the library enforces
-//! a maximum limit of 25 dice per roll. Attempts to roll more than 25 dice will
-//! result in a [`DiceError::TooManyDice`] error.
+//! [`RollLimits`] on every roll. By default a single dice term may roll at most
+//! 25 dice; attempts to roll more will result in a [`DiceError::TooManyDice`]
+//! error. Other limits cap the total dice in an expression, die sides, repeat
+//! counts, nesting depth, and how often a die may explode or be rerolled.
+//!
+//! Use a [`Roller`] to apply stricter or more relaxed limits:
+//!
+//! ```
+//! use rand::SeedableRng;
+//! use rand_chacha::ChaCha8Rng;
+//! use rollpoly::{DiceError, RollLimits, Roller};
+//!
+//! let roller = Roller::new(RollLimits::default().with_max_dice_per_term(100));
+//! let result = roller.roll("100d6", &mut ChaCha8Rng::seed_from_u64(1)).unwrap();
+//! assert_eq!(result.dice().len(), 100);
+//!
+//! let strict = Roller::new(RollLimits::default().with_max_sides(100));
+//! assert_eq!(
+//!     strict.parse("1d1000"),
+//!     Err(DiceError::TooManySides { sides: 1000, max: 100 })
+//! );
+//! ```
 //!
 //! # Error Handling
 //!
 //! All functions return `Result` types with descriptive error messages.
 //! The [`DiceError`] type provides both the original input and a description
-//! of what went wrong.
+//! of what went wrong. Parse errors also carry the byte span of the mistake and
+//! a stable [`ParseErrorKind`], and [`DiceError::render`] shows the input with a
+//! caret under the offending text.
+
+use std::ops::Range;
 
+use rand::Rng;
 use thiserror::Error;
 
+mod distribution;
 mod evaluator;
+mod limits;
 mod parser;
+mod result;
+mod symbols;
+mod value;
+
+pub use distribution::{distribution, Distribution};
+pub use evaluator::{
+    evaluate, evaluate_detailed, evaluate_detailed_with_rng, evaluate_value,
+    evaluate_value_with_rng, evaluate_with_rng,
+};
+pub use limits::RollLimits;
+pub use parser::{
+    BinaryOp, Comparison, Condition, DiceExpression, DieType, DynamicDie, ExplodeCondition,
+    ExplodeType, Function, Modifier, RerollType,
+};
+pub use result::{Die, GroupMember, RollResult, Total};
+pub use symbols::{SymbolDie, SymbolRegistry, SymbolRoll, SymbolTally, SymbolicDie};
+pub use value::Value;
 
-use evaluator::evaluate;
 use parser::DiceParser;
 
 /// Error type for dice rolling operations
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum DiceError {
     #[error("Empty dice notation provided")]
     EmptyInput,
 
     #[error("Invalid dice notation '{input}': {reason}")]
-    InvalidNotation { input: String, reason: String },
+    InvalidNotation {
+        input: String,
+        reason: String,
+        /// Byte range of the offending text within `input`
+        span: Range<usize>,
+        kind: ParseErrorKind,
+    },
 
     #[error("Invalid die size '{size}': must be a positive integer")]
     InvalidDieSize { size: String },
@@ -99,6 +218,388 @@ pub enum DiceError {
 
     #[error("Too many dice '{count}': maximum allowed is {max}")]
     TooManyDice { count: usize, max: usize },
+
+    #[error("Too many dice in expression '{count}': maximum allowed is {max}")]
+    TooManyTotalDice { count: usize, max: usize },
+
+    #[error("Too many sides '{sides}': maximum allowed is {max}")]
+    TooManySides { sides: i32, max: i32 },
+
+    #[error("Too many repeats '{times}': maximum allowed is {max}")]
+    TooManyRepeats { times: usize, max: usize },
+
+    #[error("Expression nested too deeply: maximum depth is {max}")]
+    ExpressionTooDeep { max: usize },
+
+    #[error("Cannot divide by zero")]
+    DivisionByZero,
+
+    #[error("Arithmetic overflow in {operation}")]
+    Overflow {
+        /// The operation that overflowed (e.g., "multiplication")
+        operation: &'static str,
+    },
+
+    #[error("Function '{function}' cannot take {count} arguments")]
+    WrongArgumentCount { function: Function, count: usize },
+
+    #[error("Symbolic die name '{name}' cannot be written in dice notation")]
+    InvalidDieName { name: String },
+
+    #[error("Unknown symbolic die 'd{name}'")]
+    UnknownDie { name: String },
+
+    #[error("Die 'd{name}' rolls symbols, not numbers; roll it with a SymbolRegistry")]
+    SymbolicDice { name: String },
+
+    #[error("Symbolic dice can only be added and subtracted")]
+    InvalidSymbolicExpression,
+}
+
+/// Stable classification of [`DiceError::InvalidNotation`] errors
+///
+/// The reason text may be reworded between releases; match on the kind instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// Input continues after a complete expression (e.g., "4x6")
+    UnexpectedCharacters,
+    /// An opening parenthesis has no matching `)`
+    UnclosedParenthesis,
+    /// A dice count is not followed by `d`
+    ExpectedDie,
+    /// A number was required (e.g., the sides in "4d")
+    ExpectedNumber,
+    /// A number does not fit in an `i32`
+    InvalidNumber,
+    /// A repeat count of zero (e.g., "3d6x0")
+    InvalidRepeatCount,
+    /// Keeping more dice than were rolled (e.g., "2d6K3")
+    KeepTooMany,
+    /// Dropping every die (e.g., "2d6X2")
+    DropTooMany,
+    /// A failure condition without a comparison or value set (e.g., "5d10>6f3")
+    ExpectedComparison,
+    /// Success and failure conditions can both match the same face (e.g., "5d10>6f>3")
+    ConflictingConditions,
+    /// An exploding condition that cannot be parsed (e.g., "3d6!a")
+    InvalidExplodeCondition,
+    /// A reroll modifier without a condition (e.g., "4d6r")
+    ExpectedRerollCondition,
+    /// A Fate die variant other than `dF.1` or `dF.2` (e.g., "dF.3")
+    InvalidDieType,
+    /// A face list or value set is missing its closing `}` (e.g., "d{1,2", "r{1,2")
+    UnclosedBrace,
+    /// A range in a face list or value set whose end is below its start (e.g., "d{5..2}")
+    InvalidFaceRange,
+    /// A modifier that may only appear once per term is repeated (e.g., "3d6!>4!5")
+    DuplicateModifier,
+    /// A call to a function that does not exist (e.g., "avg(1d6, 1d8)")
+    UnknownFunction,
+    /// A function called with the wrong number of arguments (e.g., "abs(1, 2)")
+    WrongArgumentCount,
+    /// A conditional without its ':' branch (e.g., "1d20 >= 15 ? 2d6")
+    IncompleteConditional,
+    /// A group or repeat list given a modifier for single dice or a rolled count
+    /// (e.g., "{1d6, 1d8}!", "(3d6x6)r1")
+    InvalidGroupModifier,
+}
+
+impl DiceError {
+    /// Byte range of the offending text, for errors that point into the input
+    #[must_use]
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::InvalidNotation { span, .. } => Some(span.clone()),
+            _ => None,
+        }
+    }
+
+    /// Renders the error as a multi-line diagnostic
+    ///
+    /// Errors that point into the input show it with a caret under the
+    /// offending text; other errors render as a single line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rollpoly::parse;
+    ///
+    /// let error = parse("4d6 + abc").unwrap_err();
+    /// assert_eq!(
+    ///     error.render(),
+    ///     "error: Expected number\n  |\n  | 4d6 + abc\n  |       ^^^"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn render(&self) -> String {
+        match self {
+            Self::InvalidNotation {
+                input,
+                reason,
+                span,
+                ..
+            } => {
+                let start = input.get(..span.start).map_or(0, |s| s.chars().count());
+                let width = input.get(span.clone()).map_or(0, |s| s.chars().count());
+                format!(
+                    "error: {reason}\n  |\n  | {input}\n  | {}{}",
+                    " ".repeat(start),
+                    "^".repeat(width.max(1))
+                )
+            }
+            _ => format!("error: {self}"),
+        }
+    }
+}
+
+/// Parses and rolls dice notation under a set of [`RollLimits`]
+///
+/// The free functions such as [`roll`] and [`parse`] use a default `Roller`.
+///
+/// # Examples
+///
+/// ```
+/// use rand::SeedableRng;
+/// use rand_chacha::ChaCha8Rng;
+/// use rollpoly::{RollLimits, Roller};
+///
+/// let roller = Roller::new(RollLimits::default().with_max_explosions(3));
+/// let expression = roller.parse("2d6!").unwrap();
+/// let result = roller.evaluate(&expression, &mut ChaCha8Rng::seed_from_u64(5)).unwrap();
+/// assert!(result.dice().len() <= 8);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Roller {
+    limits: RollLimits,
+}
+
+impl Roller {
+    /// Creates a roller that enforces the given limits
+    #[must_use]
+    pub const fn new(limits: RollLimits) -> Self {
+        Self { limits }
+    }
+
+    /// The limits enforced by this roller
+    #[must_use]
+    pub const fn limits(&self) -> &RollLimits {
+        &self.limits
+    }
+
+    /// Parses a dice notation string, rejecting expressions that exceed the limits
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`parse`], or a limit error such as
+    /// [`DiceError::TooManyDice`] when the expression exceeds a limit.
+    pub fn parse(&self, dice_notation: &str) -> Result<DiceExpression, DiceError> {
+        // Check for empty input
+        if dice_notation.trim().is_empty() {
+            return Err(DiceError::EmptyInput);
+        }
+
+        // Parse the dice notation using the recursive descent parser; spans refer
+        // to the untrimmed input so callers can point into what the user typed
+        let mut parser = DiceParser::with_limits(dice_notation, self.limits);
+        let expression = parser.parse()?;
+        self.limits.check(&expression)?;
+        Ok(expression)
+    }
+
+    /// Evaluates a parsed expression, drawing every die from `rng`
+    ///
+    /// The expression is checked against the limits first, so expressions built
+    /// by hand are held to the same limits as parsed ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns a limit error if the expression exceeds a limit, or an error if
+    /// the expression divides by zero or a total overflows an `i32`.
+    pub fn evaluate<R: Rng + ?Sized>(
+        &self,
+        expression: &DiceExpression,
+        rng: &mut R,
+    ) -> Result<RollResult, DiceError> {
+        self.limits.check(expression)?;
+        evaluator::evaluate_with_limits(expression, &self.limits, rng)
+    }
+
+    /// Parses and rolls a dice notation string, drawing every die from `rng`
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Roller::parse`] and [`Roller::evaluate`].
+    pub fn roll<R: Rng + ?Sized>(
+        &self,
+        dice_notation: &str,
+        rng: &mut R,
+    ) -> Result<RollResult, DiceError> {
+        let expression = self.parse(dice_notation)?;
+
+        // Evaluate the parsed expression
+        evaluator::evaluate_with_limits(&expression, &self.limits, rng)
+    }
+
+    /// Evaluates a parsed expression to the typed [`Value`] it produces
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Roller::evaluate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::SeedableRng;
+    /// use rand_chacha::ChaCha8Rng;
+    /// use rollpoly::{Roller, Value};
+    ///
+    /// let roller = Roller::default();
+    /// let expression = roller.parse("(2d6 + 3) * 2").unwrap();
+    /// let value = roller.evaluate_value(&expression, &mut ChaCha8Rng::seed_from_u64(4)).unwrap();
+    /// assert!(matches!(value, Value::Scalar(_)));
+    /// ```
+    pub fn evaluate_value<R: Rng + ?Sized>(
+        &self,
+        expression: &DiceExpression,
+        rng: &mut R,
+    ) -> Result<Value, DiceError> {
+        self.limits.check(expression)?;
+        evaluator::evaluate_value_with_limits(expression, &self.limits, rng)
+    }
+
+    /// Parses and rolls a dice notation string to the typed [`Value`] it produces
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Roller::parse`] and [`Roller::evaluate`].
+    pub fn roll_value<R: Rng + ?Sized>(
+        &self,
+        dice_notation: &str,
+        rng: &mut R,
+    ) -> Result<Value, DiceError> {
+        let expression = self.parse(dice_notation)?;
+        evaluator::evaluate_value_with_limits(&expression, &self.limits, rng)
+    }
+
+    /// Evaluates a parsed expression with `i64` totals
+    ///
+    /// Use this for expressions whose totals may not fit in an `i32`, such as
+    /// products of large pools. Arithmetic is still checked, so an `i64`
+    /// overflow is reported as [`DiceError::Overflow`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Roller::evaluate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::SeedableRng;
+    /// use rand_chacha::ChaCha8Rng;
+    /// use rollpoly::{DiceError, Roller};
+    ///
+    /// let roller = Roller::default();
+    /// let expression = roller.parse("1d6 + 2147483647").unwrap();
+    /// let mut rng = ChaCha8Rng::seed_from_u64(1);
+    ///
+    /// assert_eq!(
+    ///     roller.evaluate(&expression, &mut rng),
+    ///     Err(DiceError::Overflow { operation: "addition" })
+    /// );
+    /// assert!(roller.evaluate_i64(&expression, &mut rng).unwrap().total() > 2_147_483_647);
+    /// ```
+    pub fn evaluate_i64<R: Rng + ?Sized>(
+        &self,
+        expression: &DiceExpression,
+        rng: &mut R,
+    ) -> Result<RollResult<i64>, DiceError> {
+        self.limits.check(expression)?;
+        evaluator::evaluate_with_limits(expression, &self.limits, rng)
+    }
+
+    /// Computes the exact distribution of an expression's total under these limits
+    ///
+    /// Continuous rerolls stop at [`RollLimits::max_rerolls`] and rolled dice
+    /// counts and sides are held to the limits, matching [`Roller::evaluate`].
+    /// Returns `None` in the same cases as [`distribution`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rollpoly::{distribution, RollLimits, Roller};
+    ///
+    /// let roller = Roller::new(RollLimits::default().with_max_rerolls(1));
+    /// let expression = roller.parse("1d6R1").unwrap();
+    ///
+    /// // A one shows up only when both the roll and its single reroll are ones
+    /// let capped = roller.distribution(&expression).unwrap();
+    /// assert!((capped.probability(1) - 1.0 / 36.0).abs() < 1e-12);
+    /// assert!(distribution(&expression).unwrap().probability(1) < 1e-12);
+    /// ```
+    #[must_use]
+    pub fn distribution(&self, expression: &DiceExpression) -> Option<Distribution> {
+        distribution::distribution_with_limits(expression, &self.limits)
+    }
+
+    /// Parses and rolls a dice notation string with `i64` totals
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Roller::parse`] and [`Roller::evaluate_i64`].
+    pub fn roll_i64<R: Rng + ?Sized>(
+        &self,
+        dice_notation: &str,
+        rng: &mut R,
+    ) -> Result<RollResult<i64>, DiceError> {
+        let expression = self.parse(dice_notation)?;
+        evaluator::evaluate_with_limits(&expression, &self.limits, rng)
+    }
+}
+
+/// Parses a dice notation string into a [`DiceExpression`] without rolling it.
+///
+/// The returned expression can be stored and rolled any number of times with
+/// [`evaluate`], so notation only needs to be validated once.
+///
+/// # Arguments
+///
+/// * `dice_notation` - A string slice containing the dice notation (e.g., "4d10 + 17")
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The dice notation is empty or contains only whitespace
+/// * The dice notation contains invalid characters or syntax
+/// * The dice notation is malformed (e.g., missing die size, invalid operators)
+/// * Numeric values in the notation cannot be parsed
+/// * The expression exceeds the default [`RollLimits`]
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{parse, Comparison, Condition, DiceExpression, DieType, Modifier, RerollType};
+///
+/// let expression = parse("4d6r1K3").unwrap();
+/// assert_eq!(
+///     expression,
+///     DiceExpression::Dice {
+///         count: 4,
+///         die: DieType::Numbered(6),
+///         modifiers: vec![
+///             Modifier::Reroll {
+///                 condition: Condition::Compare(Comparison::Equal, 1),
+///                 reroll_type: RerollType::Once,
+///             },
+///             Modifier::KeepHighest(3),
+///         ],
+///     }
+/// );
+///
+/// assert!(parse("invalid nonsense").is_err());
+/// ```
+pub fn parse(dice_notation: &str) -> Result<DiceExpression, DiceError> {
+    Roller::default().parse(dice_notation)
 }
 
 /// Rolls dice based on the provided dice notation string.
@@ -112,7 +613,8 @@ pub enum DiceError {
 /// Returns a `Result<Vec<i32>, DiceError>` containing the individual dice roll results and any modifiers,
 /// or an error if the input is invalid.
 /// For basic rolls like "4d10 + 17", this would return the 4 individual die results
-/// plus the modifier as separate elements.
+/// plus the modifier as separate elements. Use [`roll_value`] to get a [`Value`]
+/// that says what kind of thing the expression produced.
 ///
 /// # Errors
 ///
@@ -121,6 +623,7 @@ pub enum DiceError {
 /// * The dice notation contains invalid characters or syntax
 /// * The dice notation is malformed (e.g., missing die size, invalid operators)
 /// * Numeric values in the notation cannot be parsed
+/// * The expression exceeds the default [`RollLimits`]
 ///
 /// # Examples
 ///
@@ -135,41 +638,123 @@ pub enum DiceError {
 /// assert!(error.to_string().contains("invalid nonsense"));
 /// ```
 pub fn roll(dice_notation: &str) -> Result<Vec<i32>, DiceError> {
-    // Trim whitespace and check for empty input
-    let notation = dice_notation.trim();
+    roll_with_rng(dice_notation, &mut rand::rng())
+}
 
-    if notation.is_empty() {
-        return Err(DiceError::EmptyInput);
-    }
+/// Rolls dice based on the provided dice notation string, drawing every die
+/// from the given random number generator.
+///
+/// Passing a seeded RNG makes rolls reproducible, which is useful for tests
+/// and for replaying game sessions. Prefer an RNG with a stable output stream,
+/// such as `rand_chacha::ChaCha8Rng`; `StdRng` may change between `rand` releases.
+///
+/// # Errors
+///
+/// Returns the same errors as [`roll`].
+///
+/// # Examples
+///
+/// ```
+/// use rand::SeedableRng;
+/// use rand_chacha::ChaCha8Rng;
+/// use rollpoly::roll_with_rng;
+///
+/// let mut first_session = ChaCha8Rng::seed_from_u64(1234);
+/// let mut second_session = ChaCha8Rng::seed_from_u64(1234);
+///
+/// assert_eq!(
+///     roll_with_rng("4d6K3", &mut first_session).unwrap(),
+///     roll_with_rng("4d6K3", &mut second_session).unwrap()
+/// );
+/// ```
+pub fn roll_with_rng<R: Rng + ?Sized>(
+    dice_notation: &str,
+    rng: &mut R,
+) -> Result<Vec<i32>, DiceError> {
+    roll_detailed_with_rng(dice_notation, rng).map(|result| result.values())
+}
 
-    // Parse the dice notation using the recursive descent parser
-    let mut parser = DiceParser::new(notation);
-    let expression = parser.parse().map_err(|e| match e {
-        // Pass through specific errors
-        e @ (DiceError::TooManyDice { .. }
-        | DiceError::InvalidDiceCount { .. }
-        | DiceError::InvalidDieSize { .. }) => e,
-        // Wrap other errors as InvalidNotation
-        _ => DiceError::InvalidNotation {
-            input: dice_notation.to_string(),
-            reason: e.to_string(),
-        },
-    })?;
-
-    // Evaluate the parsed expression
-    evaluate(&expression).map_err(|e| match e {
-        // Pass through specific errors
-        e @ (DiceError::TooManyDice { .. }
-        | DiceError::InvalidDiceCount { .. }
-        | DiceError::InvalidDieSize { .. }) => e,
-        // Wrap other errors as InvalidNotation
-        _ => DiceError::InvalidNotation {
-            input: dice_notation.to_string(),
-            reason: e.to_string(),
-        },
-    })
+/// Rolls dice based on the provided dice notation string and returns the full
+/// [`RollResult`] tree.
+///
+/// Unlike [`roll`], the result records every die that was rolled, which dice
+/// were dropped, exploded or rerolled, and the total of every sub-expression.
+///
+/// # Errors
+///
+/// Returns the same errors as [`roll`].
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::roll_detailed;
+///
+/// let result = roll_detailed("4d6K3").unwrap();
+/// let dice = result.dice();
+/// assert_eq!(dice.len(), 4);
+/// assert_eq!(dice.iter().filter(|die| die.is_dropped()).count(), 1);
+/// ```
+pub fn roll_detailed(dice_notation: &str) -> Result<RollResult, DiceError> {
+    roll_detailed_with_rng(dice_notation, &mut rand::rng())
+}
+
+/// Rolls dice using the given random number generator and returns the full
+/// [`RollResult`] tree.
+///
+/// # Errors
+///
+/// Returns the same errors as [`roll`].
+pub fn roll_detailed_with_rng<R: Rng + ?Sized>(
+    dice_notation: &str,
+    rng: &mut R,
+) -> Result<RollResult, DiceError> {
+    Roller::default().roll(dice_notation, rng)
+}
+
+/// Rolls dice based on the provided dice notation string and returns the
+/// typed [`Value`] the expression produced.
+///
+/// Arithmetic gives a [`Value::Scalar`], so `2d6 * 2 + 3` and `(2d6 + 3) * 2`
+/// have the same shape, while a dice term gives its [`Value::Pool`] and a
+/// repeat its [`Value::List`].
+///
+/// # Errors
+///
+/// Returns the same errors as [`roll`].
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{roll_value, Value};
+///
+/// let stats = roll_value("4d6K3x6").unwrap().into_list();
+/// assert_eq!(stats.len(), 6);
+/// assert!(matches!(roll_value("2d6 * 2 + 3").unwrap(), Value::Scalar(_)));
+/// ```
+pub fn roll_value(dice_notation: &str) -> Result<Value, DiceError> {
+    roll_value_with_rng(dice_notation, &mut rand::rng())
 }
+
+/// Rolls dice using the given random number generator and returns the typed
+/// [`Value`] the expression produced.
+///
+/// # Errors
+///
+/// Returns the same errors as [`roll`].
+pub fn roll_value_with_rng<R: Rng + ?Sized>(
+    dice_notation: &str,
+    rng: &mut R,
+) -> Result<Value, DiceError> {
+    Roller::default().roll_value(dice_notation, rng)
+}
+
 #[cfg(test)]
+#[allow(
+    clippy::uninlined_format_args,
+    clippy::manual_range_contains,
+    clippy::needless_range_loop,
+    clippy::len_zero
+)]
 mod tests {
     use super::*;
 
@@ -1889,7 +2474,7 @@ mod tests {
 
             // Now check the specific error type
             match error {
-                DiceError::InvalidNotation { input, reason: _ } => {
+                DiceError::InvalidNotation { input, .. } => {
                     assert_eq!(
                         input, invalid_input,
                         "Error should contain the original input"
@@ -1944,7 +2529,7 @@ mod tests {
 
                 let error = result.unwrap_err();
                 match error {
-                    DiceError::InvalidNotation { input, reason: _ } => {
+                    DiceError::InvalidNotation { input, .. } => {
                         assert_eq!(
                             input, invalid_input,
                             "Error should contain the original input for '{}'",
@@ -2108,4 +2693,306 @@ mod tests {
             );
         }
     }
+
+    mod parse_and_evaluate {
+        use super::*;
+
+        #[test]
+        fn test_parse_returns_expression_without_rolling() {
+            // Act
+            let expression = parse("4d6K3 + 2").expect("Valid dice notation should parse");
+
+            // Assert
+            assert_eq!(
+                expression,
+                DiceExpression::Binary {
+                    left: Box::new(DiceExpression::Dice {
+                        count: 4,
+                        die: DieType::Numbered(6),
+                        modifiers: vec![Modifier::KeepHighest(3)],
+                    }),
+                    op: BinaryOp::Add,
+                    right: Box::new(DiceExpression::Constant(2)),
+                }
+            );
+        }
+
+        #[test]
+        fn test_parse_errors_match_roll_errors() {
+            for invalid_input in ["", "   ", "invalid", "4d", "30d6", "0d6", "2d6 +"] {
+                assert_eq!(
+                    parse(invalid_input).unwrap_err(),
+                    roll(invalid_input).unwrap_err(),
+                    "parse and roll should report the same error for '{invalid_input}'"
+                );
+            }
+        }
+
+        #[test]
+        fn test_evaluate_parsed_expression_repeatedly() {
+            // Arrange
+            let expression = parse("3d6 + 1").expect("Valid dice notation should parse");
+
+            // Act & Assert
+            for _ in 0..50 {
+                let results = evaluate(&expression).expect("Evaluation should succeed");
+                assert_eq!(
+                    results.len(),
+                    4,
+                    "3d6 + 1 should return 3 dice + 1 modifier"
+                );
+                for &die_result in &results[..3] {
+                    assert_die_result_in_range(die_result, MIN_DIE_VALUE, D6_MAX, "d6");
+                }
+                assert_eq!(results[3], 1, "Last element should be the modifier");
+            }
+        }
+    }
+
+    mod seeded_rolls {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test_roll_with_same_seed_produces_same_results() {
+            for notation in ["4d6K3", "3d6x6", "10d10>6f<3", "2d6! + 1d8 * 2", "4d10R<3"] {
+                let mut first = ChaCha8Rng::seed_from_u64(2025);
+                let mut second = ChaCha8Rng::seed_from_u64(2025);
+
+                // Roll a sequence to make sure the RNG state advances identically
+                for _ in 0..10 {
+                    assert_eq!(
+                        roll_with_rng(notation, &mut first).unwrap(),
+                        roll_with_rng(notation, &mut second).unwrap(),
+                        "Seeded rolls of '{notation}' should match"
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn test_roll_with_rng_reports_invalid_notation() {
+            let mut rng = ChaCha8Rng::seed_from_u64(1);
+            assert!(matches!(
+                roll_with_rng("invalid", &mut rng),
+                Err(DiceError::InvalidNotation { .. })
+            ));
+        }
+    }
+
+    mod roll_limits {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test_default_roller_matches_free_functions() {
+            // Arrange
+            let mut first = ChaCha8Rng::seed_from_u64(9);
+            let mut second = ChaCha8Rng::seed_from_u64(9);
+
+            // Act
+            let free = roll_with_rng("4d6K3 + 2", &mut first).unwrap();
+            let roller = Roller::default().roll("4d6K3 + 2", &mut second).unwrap();
+
+            // Assert
+            assert_eq!(free, roller.values());
+            assert_eq!(Roller::default().limits(), &RollLimits::default());
+        }
+
+        #[test]
+        fn test_relaxed_roller_allows_large_pools() {
+            // Arrange
+            let roller = Roller::new(RollLimits::default().with_max_dice_per_term(300));
+            let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+            // Act
+            let result = roller.roll("300d6", &mut rng).unwrap();
+
+            // Assert
+            assert_eq!(result.dice().len(), 300);
+            assert!(matches!(
+                roll("300d6"),
+                Err(DiceError::TooManyDice {
+                    count: 300,
+                    max: 25
+                })
+            ));
+        }
+
+        #[test]
+        fn test_strict_roller_rejects_total_dice() {
+            // Arrange
+            let roller = Roller::new(RollLimits::default().with_max_total_dice(12));
+
+            // Act
+            let result = roller.parse("4d6x4");
+
+            // Assert
+            assert_eq!(
+                result,
+                Err(DiceError::TooManyTotalDice { count: 16, max: 12 })
+            );
+        }
+
+        #[test]
+        fn test_roller_holds_rolled_counts_to_total_dice() {
+            // Arrange
+            let roller = Roller::new(
+                RollLimits::default()
+                    .with_max_dice_per_term(10)
+                    .with_max_total_dice(20),
+            );
+            let mut rng = rand::rng();
+
+            // Act
+            let within = roller.roll("(1d4)d6 + (1d4)d6", &mut rng);
+            let over = roller.roll("(1d1 + 9)d6 + (1d1 + 9)d6", &mut rng);
+
+            // Assert
+            assert!(within.is_ok(), "Unexpected error: {within:?}");
+            assert_eq!(
+                over,
+                Err(DiceError::TooManyTotalDice { count: 22, max: 20 })
+            );
+        }
+
+        #[test]
+        fn test_roller_checks_hand_built_expressions() {
+            // Arrange
+            let roller = Roller::new(RollLimits::default().with_max_sides(100));
+            let expression = DiceExpression::Dice {
+                count: 1,
+                die: DieType::Numbered(1000),
+                modifiers: vec![],
+            };
+            let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+            // Act
+            let result = roller.evaluate(&expression, &mut rng);
+
+            // Assert
+            assert_eq!(
+                result,
+                Err(DiceError::TooManySides {
+                    sides: 1000,
+                    max: 100
+                })
+            );
+        }
+
+        #[test]
+        fn test_deeply_nested_parentheses_are_rejected() {
+            // Arrange
+            let notation = format!("{}1d6{}", "(".repeat(500), ")".repeat(500));
+
+            // Act
+            let result = parse(&notation);
+
+            // Assert
+            assert_eq!(result, Err(DiceError::ExpressionTooDeep { max: 100 }));
+        }
+    }
+
+    mod parse_errors {
+        use super::*;
+
+        fn parse_error(notation: &str) -> (ParseErrorKind, Range<usize>) {
+            match parse(notation) {
+                Err(DiceError::InvalidNotation { kind, span, .. }) => (kind, span),
+                other => panic!("Expected InvalidNotation for '{notation}', got: {other:?}"),
+            }
+        }
+
+        #[test]
+        fn test_parse_errors_report_kind_and_span() {
+            // Arrange
+            let cases = [
+                ("4d6 + abc", ParseErrorKind::ExpectedNumber, 6..9),
+                ("4d", ParseErrorKind::ExpectedNumber, 2..2),
+                ("4x6", ParseErrorKind::UnexpectedCharacters, 1..3),
+                ("(2d6 + 3", ParseErrorKind::UnclosedParenthesis, 8..8),
+                ("2d6K3", ParseErrorKind::KeepTooMany, 3..5),
+                ("2d6X2", ParseErrorKind::DropTooMany, 3..5),
+                ("3d6x0", ParseErrorKind::InvalidRepeatCount, 4..5),
+                ("5d10>6f3", ParseErrorKind::ExpectedComparison, 7..8),
+                ("5d10>6f>3", ParseErrorKind::ConflictingConditions, 4..9),
+                ("3d6!a", ParseErrorKind::InvalidExplodeCondition, 4..5),
+                ("4d6r", ParseErrorKind::ExpectedRerollCondition, 4..4),
+                ("1d99999999999", ParseErrorKind::InvalidNumber, 2..13),
+            ];
+
+            for (notation, expected_kind, expected_span) in cases {
+                // Act
+                let (kind, span) = parse_error(notation);
+
+                // Assert
+                assert_eq!(kind, expected_kind, "Wrong kind for '{notation}'");
+                assert_eq!(span, expected_span, "Wrong span for '{notation}'");
+            }
+        }
+
+        #[test]
+        fn test_spans_refer_to_untrimmed_input() {
+            // Arrange
+            let notation = "  2d6 + 3 )  ";
+
+            // Act
+            let (kind, span) = parse_error(notation);
+
+            // Assert
+            assert_eq!(kind, ParseErrorKind::UnexpectedCharacters);
+            assert_eq!(&notation[span], ")");
+        }
+
+        #[test]
+        fn test_roll_error_mentions_input_once() {
+            // Arrange
+            let notation = "bad";
+
+            // Act
+            let message = roll(notation).unwrap_err().to_string();
+
+            // Assert
+            assert_eq!(message, "Invalid dice notation 'bad': Expected number");
+        }
+
+        #[test]
+        fn test_render_points_at_the_mistake() {
+            // Arrange
+            let error = parse("(2d6 + 3").unwrap_err();
+
+            // Act
+            let rendered = error.render();
+
+            // Assert
+            assert_eq!(
+                rendered,
+                "error: Expected closing parenthesis ')'\n  |\n  | (2d6 + 3\n  |         ^"
+            );
+        }
+
+        #[test]
+        fn test_render_without_span_is_single_line() {
+            // Arrange
+            let error = parse("30d6").unwrap_err();
+
+            // Act
+            let rendered = error.render();
+
+            // Assert
+            assert_eq!(error.span(), None);
+            assert_eq!(rendered, "error: Too many dice '30': maximum allowed is 25");
+        }
+
+        #[test]
+        fn test_division_by_zero_has_its_own_error() {
+            // Act
+            let result = roll("1d6 / 0");
+
+            // Assert
+            assert_eq!(result, Err(DiceError::DivisionByZero));
+        }
+    }
 }
@@ -0,0 +1,408 @@
+// Copyright 2025 Ray Krueger
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resource limits for parsing and rolling dice
+//!
+//! Limits keep untrusted notation from consuming excessive time or memory.
+//! The defaults match the library's historical behaviour; applications can
+//! tighten or relax them through the builder methods on [`RollLimits`].
+
+#![allow(clippy::redundant_pub_crate)] // Marks the helpers that are not re-exported
+
+use crate::parser::{DiceExpression, DieType, DynamicDie};
+use crate::DiceError;
+
+/// Limits applied while parsing and rolling dice
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{RollLimits, Roller};
+///
+/// // A bot serving untrusted users
+/// let strict = RollLimits::default()
+///     .with_max_dice_per_term(10)
+///     .with_max_sides(100)
+///     .with_max_repeat(6);
+/// assert!(Roller::new(strict).parse("20d6").is_err());
+///
+/// // Simulation tooling that needs large pools
+/// let relaxed = RollLimits::default().with_max_dice_per_term(500);
+/// assert!(Roller::new(relaxed).parse("200d6").is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RollLimits {
+    /// Maximum number of dice in a single dice term (e.g., the `25` in `25d6`)
+    pub max_dice_per_term: usize,
+    /// Maximum number of dice rolled by the whole expression, counting repeats
+    pub max_total_dice: usize,
+    /// Maximum number of sides on a die
+    pub max_sides: i32,
+    /// Maximum number of times a single die may explode
+    pub max_explosions: usize,
+    /// Maximum number of times a single die may be rerolled
+    pub max_rerolls: usize,
+    /// Maximum repeat count (e.g., the `6` in `3d6x6`)
+    pub max_repeat: usize,
+    /// Maximum nesting depth of the expression tree
+    pub max_depth: usize,
+}
+
+impl Default for RollLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RollLimits {
+    /// Creates the default limits
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_dice_per_term: 25,
+            max_total_dice: 1_000,
+            max_sides: 1_000_000,
+            max_explosions: 100,
+            max_rerolls: 100,
+            max_repeat: 100,
+            max_depth: 100,
+        }
+    }
+
+    /// Sets the maximum number of dice in a single dice term
+    #[must_use]
+    pub const fn with_max_dice_per_term(mut self, max: usize) -> Self {
+        self.max_dice_per_term = max;
+        self
+    }
+
+    /// Sets the maximum number of dice rolled by the whole expression
+    #[must_use]
+    pub const fn with_max_total_dice(mut self, max: usize) -> Self {
+        self.max_total_dice = max;
+        self
+    }
+
+    /// Sets the maximum number of sides on a die
+    #[must_use]
+    pub const fn with_max_sides(mut self, max: i32) -> Self {
+        self.max_sides = max;
+        self
+    }
+
+    /// Sets the maximum number of times a single die may explode
+    #[must_use]
+    pub const fn with_max_explosions(mut self, max: usize) -> Self {
+        self.max_explosions = max;
+        self
+    }
+
+    /// Sets the maximum number of times a single die may be rerolled
+    #[must_use]
+    pub const fn with_max_rerolls(mut self, max: usize) -> Self {
+        self.max_rerolls = max;
+        self
+    }
+
+    /// Sets the maximum repeat count
+    #[must_use]
+    pub const fn with_max_repeat(mut self, max: usize) -> Self {
+        self.max_repeat = max;
+        self
+    }
+
+    /// Sets the maximum nesting depth of the expression tree
+    #[must_use]
+    pub const fn with_max_depth(mut self, max: usize) -> Self {
+        self.max_depth = max;
+        self
+    }
+
+    /// Checks a dice count against the per-term limit
+    pub(crate) const fn check_dice_count(&self, count: usize) -> Result<(), DiceError> {
+        if count > self.max_dice_per_term {
+            return Err(DiceError::TooManyDice {
+                count,
+                max: self.max_dice_per_term,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks a die size against the sides limit
+    pub(crate) const fn check_sides(&self, sides: i32) -> Result<(), DiceError> {
+        if sides > self.max_sides {
+            return Err(DiceError::TooManySides {
+                sides,
+                max: self.max_sides,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that a die has at least one side and no more than the sides limit
+    ///
+    /// The parser never builds such a die, but expressions built by hand can.
+    pub(crate) fn check_die(&self, die: &DieType) -> Result<(), DiceError> {
+        let sides = die.sides();
+        if sides < 1 {
+            return Err(DiceError::InvalidDieSize {
+                size: die.to_string(),
+            });
+        }
+        self.check_sides(sides)
+    }
+
+    /// Checks a repeat count against the repeat limit
+    pub(crate) const fn check_repeat(&self, times: usize) -> Result<(), DiceError> {
+        if times > self.max_repeat {
+            return Err(DiceError::TooManyRepeats {
+                times,
+                max: self.max_repeat,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks a nesting depth against the depth limit
+    pub(crate) const fn check_depth(&self, depth: usize) -> Result<(), DiceError> {
+        if depth > self.max_depth {
+            return Err(DiceError::ExpressionTooDeep {
+                max: self.max_depth,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks a whole expression against every limit that can be known before rolling
+    pub(crate) fn check(&self, expr: &DiceExpression) -> Result<(), DiceError> {
+        let total = self.check_node(expr, 1)?;
+        if total > self.max_total_dice {
+            return Err(DiceError::TooManyTotalDice {
+                count: total,
+                max: self.max_total_dice,
+            });
+        }
+        Ok(())
+    }
+
+    /// Validates one node and returns the number of dice it rolls
+    fn check_node(&self, expr: &DiceExpression, depth: usize) -> Result<usize, DiceError> {
+        self.check_depth(depth)?;
+
+        match expr {
+            DiceExpression::Dice { count, die, .. } => {
+                self.check_dice_count(*count)?;
+                self.check_die(die)?;
+                Ok(*count)
+            }
+            // A rolled count is only known once it is rolled, so the term counts as
+            // the fewest dice it can roll, none; the dice it does roll are held to
+            // the total by the evaluator's DiceBudget
+            DiceExpression::DynamicDice {
+                count,
+                die,
+                modifiers,
+            } => {
+                if let DynamicDie::Fixed(die) = die {
+                    self.check_die(die)?;
+                }
+                let dice = match **count {
+                    DiceExpression::Constant(count) => {
+                        let count = usize::try_from(count).unwrap_or_default();
+                        self.check_dice_count(count)?;
+                        count
+                    }
+                    _ => 0,
+                };
+                DiceExpression::dynamic_parts(count, die, modifiers)
+                    .try_fold(dice, |total, part| {
+                        Ok(self.check_node(part, depth + 1)?.saturating_add(total))
+                    })
+            }
+            DiceExpression::Repeat { expression, times }
+            | DiceExpression::RepeatList {
+                expression, times, ..
+            } => {
+                self.check_repeat(*times)?;
+                let dice = self.check_node(expression, depth + 1)?;
+                Ok(dice.saturating_mul(*times))
+            }
+            DiceExpression::Binary { left, right, .. }
+            | DiceExpression::Compare { left, right, .. } => {
+                let left = self.check_node(left, depth + 1)?;
+                let right = self.check_node(right, depth + 1)?;
+                Ok(left.saturating_add(right))
+            }
+            // Only one branch is rolled, so the larger of the two counts
+            DiceExpression::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let condition = self.check_node(condition, depth + 1)?;
+                let then = self.check_node(then, depth + 1)?;
+                let otherwise = self.check_node(otherwise, depth + 1)?;
+                Ok(condition.saturating_add(then.max(otherwise)))
+            }
+            DiceExpression::Negate(operand) => self.check_node(operand, depth + 1),
+            DiceExpression::Function { args, .. }
+            | DiceExpression::Group {
+                expressions: args, ..
+            } => args.iter().try_fold(0, |total, arg| {
+                Ok(self.check_node(arg, depth + 1)?.saturating_add(total))
+            }),
+            DiceExpression::Constant(_) => Ok(0),
+            DiceExpression::Symbolic { count, .. } => {
+                self.check_dice_count(*count)?;
+                Ok(*count)
+            }
+        }
+    }
+}
+
+/// Running count of the dice one evaluation has rolled, held to `max_total_dice`
+///
+/// [`RollLimits::check`] cannot know how many dice a rolled count asks for, so
+/// every term is charged here as it is rolled.
+pub(crate) struct DiceBudget<'a> {
+    limits: &'a RollLimits,
+    rolled: usize,
+}
+
+impl<'a> DiceBudget<'a> {
+    /// Starts an evaluation that has rolled no dice yet
+    pub(crate) const fn new(limits: &'a RollLimits) -> Self {
+        Self { limits, rolled: 0 }
+    }
+
+    /// The limits the evaluation is held to
+    pub(crate) const fn limits(&self) -> &'a RollLimits {
+        self.limits
+    }
+
+    /// Charges a term's dice against the total, failing once it would exceed the limit
+    pub(crate) const fn spend(&mut self, count: usize) -> Result<(), DiceError> {
+        let rolled = self.rolled.saturating_add(count);
+        if rolled > self.limits.max_total_dice {
+            return Err(DiceError::TooManyTotalDice {
+                count: rolled,
+                max: self.limits.max_total_dice,
+            });
+        }
+        self.rolled = rolled;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::DiceParser;
+
+    fn check(notation: &str, limits: &RollLimits) -> Result<(), DiceError> {
+        let expr = DiceParser::new(notation).parse().unwrap();
+        limits.check(&expr)
+    }
+
+    #[test]
+    fn test_check_counts_total_dice_across_terms_and_repeats() {
+        let limits = RollLimits::default().with_max_total_dice(30);
+
+        assert!(check("10d6 + 20d6", &limits.with_max_dice_per_term(25)).is_ok());
+        assert_eq!(
+            check("5d6x7", &limits),
+            Err(DiceError::TooManyTotalDice { count: 35, max: 30 })
+        );
+        assert_eq!(
+            check("{10d6, 10d6, 10d6 + 1d6}K1", &limits),
+            Err(DiceError::TooManyTotalDice { count: 31, max: 30 })
+        );
+        assert_eq!(
+            check("(4d6K3x8)K6", &limits),
+            Err(DiceError::TooManyTotalDice { count: 32, max: 30 })
+        );
+    }
+
+    #[test]
+    fn test_check_rejects_large_dice() {
+        let limits = RollLimits::default().with_max_sides(100);
+
+        assert!(check("1d100", &limits).is_ok());
+        assert_eq!(
+            check("2d6 + 1d1000", &limits),
+            Err(DiceError::TooManySides {
+                sides: 1000,
+                max: 100
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_rejects_dice_without_sides() {
+        let dice = |die| DiceExpression::Dice {
+            count: 1,
+            die,
+            modifiers: Vec::new(),
+        };
+        let limits = RollLimits::default();
+
+        assert_eq!(
+            limits.check(&dice(DieType::Numbered(0))),
+            Err(DiceError::InvalidDieSize {
+                size: "0".to_string()
+            })
+        );
+        assert_eq!(
+            limits.check(&dice(DieType::Custom(Vec::new()))),
+            Err(DiceError::InvalidDieSize {
+                size: "{}".to_string()
+            })
+        );
+        assert!(limits.check(&dice(DieType::Custom(vec![-1]))).is_ok());
+    }
+
+    #[test]
+    fn test_check_rejects_deep_expressions() {
+        let limits = RollLimits::default().with_max_depth(3);
+
+        assert!(check("1d6 + 1", &limits).is_ok());
+        assert_eq!(
+            check("1d6 + 1 + 1 + 1", &limits),
+            Err(DiceError::ExpressionTooDeep { max: 3 })
+        );
+    }
+
+    #[test]
+    fn test_check_counts_rolled_counts_at_their_fewest() {
+        let limits = RollLimits::default()
+            .with_max_dice_per_term(10)
+            .with_max_total_dice(20);
+
+        assert!(check("(1d4)d6 + 4d6", &limits).is_ok());
+        assert!(check("(1d4)d6 + (1d4)d6", &limits).is_ok());
+        assert_eq!(
+            check("(1d4)d6 + 20d6", &limits.with_max_dice_per_term(20)),
+            Err(DiceError::TooManyTotalDice { count: 21, max: 20 })
+        );
+        assert_eq!(
+            check("(1d4)d1000", &limits.with_max_sides(100)),
+            Err(DiceError::TooManySides {
+                sides: 1000,
+                max: 100
+            })
+        );
+    }
+}
@@ -15,7 +15,11 @@
 #![allow(clippy::multiple_crate_versions)]
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rollpoly::{DiceError, RollLimits, Roller};
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(name = "rollpoly")]
@@ -26,12 +30,151 @@ struct Cli {
     command: Option<Commands>,
 
     /// Dice notation to roll (if no subcommand is provided)
-    #[arg(help = "Dice notation like '4d10 + 17', '2d20 - 3', etc.")]
+    #[arg(
+        allow_hyphen_values = true,
+        help = "Dice notation like '4d10 + 17', '2d20 - 3', etc."
+    )]
     dice: Option<String>,
 
     /// Number of times to repeat the roll
     #[arg(short = 'n', long, default_value = "1")]
     repeat: usize,
+
+    /// Seed the random number generator for reproducible rolls
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    /// Compute totals in 64-bit integers for very large results
+    #[arg(long = "i64", global = true)]
+    wide: bool,
+
+    #[command(flatten)]
+    limits: LimitArgs,
+}
+
+/// Overrides for the default roll limits
+#[derive(Args)]
+#[allow(clippy::struct_field_names)] // Field names become the --max-* flag names
+struct LimitArgs {
+    /// Maximum number of dice in a single dice term
+    #[arg(long, global = true, value_name = "N")]
+    max_dice_per_term: Option<usize>,
+
+    /// Maximum number of dice rolled by the whole expression
+    #[arg(long, global = true, value_name = "N")]
+    max_total_dice: Option<usize>,
+
+    /// Maximum number of sides on a die
+    #[arg(long, global = true, value_name = "N")]
+    max_sides: Option<i32>,
+
+    /// Maximum number of times a single die may explode
+    #[arg(long, global = true, value_name = "N")]
+    max_explosions: Option<usize>,
+
+    /// Maximum number of times a single die may be rerolled
+    #[arg(long, global = true, value_name = "N")]
+    max_rerolls: Option<usize>,
+
+    /// Maximum repeat count for 'x' rolls
+    #[arg(long, global = true, value_name = "N")]
+    max_repeat: Option<usize>,
+
+    /// Maximum nesting depth of an expression
+    #[arg(long, global = true, value_name = "N")]
+    max_depth: Option<usize>,
+}
+
+impl LimitArgs {
+    /// Applies the overrides on top of the default limits
+    fn to_limits(&self) -> RollLimits {
+        let mut limits = RollLimits::default();
+        if let Some(max) = self.max_dice_per_term {
+            limits = limits.with_max_dice_per_term(max);
+        }
+        if let Some(max) = self.max_total_dice {
+            limits = limits.with_max_total_dice(max);
+        }
+        if let Some(max) = self.max_sides {
+            limits = limits.with_max_sides(max);
+        }
+        if let Some(max) = self.max_explosions {
+            limits = limits.with_max_explosions(max);
+        }
+        if let Some(max) = self.max_rerolls {
+            limits = limits.with_max_rerolls(max);
+        }
+        if let Some(max) = self.max_repeat {
+            limits = limits.with_max_repeat(max);
+        }
+        if let Some(max) = self.max_depth {
+            limits = limits.with_max_depth(max);
+        }
+        limits
+    }
+}
+
+/// Values of one roll, whether it passed when it was a comparison, and its crits
+#[allow(clippy::struct_excessive_bools)] // Independent flags, a roll can crit both ways
+struct Outcome {
+    values: Vec<i64>,
+    passed: Option<bool>,
+    critical_success: bool,
+    critical_failure: bool,
+}
+
+impl Outcome {
+    fn sum(&self) -> i64 {
+        self.values.iter().sum()
+    }
+
+    /// Suffix noting whether a comparison passed, empty for other rolls
+    const fn verdict(&self) -> &'static str {
+        match self.passed {
+            Some(true) => " (pass)",
+            Some(false) => " (fail)",
+            None => "",
+        }
+    }
+
+    /// Suffix calling out critical successes and failures, empty when there were none
+    const fn highlight(&self) -> &'static str {
+        match (self.critical_success, self.critical_failure) {
+            (true, true) => " 🎯💀 CRITICAL SUCCESS AND FAILURE!",
+            (true, false) => " 🎯 CRITICAL SUCCESS!",
+            (false, true) => " 💀 CRITICAL FAILURE!",
+            (false, false) => "",
+        }
+    }
+}
+
+/// The roller and random number generator shared by every command
+struct Session {
+    roller: Roller,
+    rng: ChaCha8Rng,
+    wide: bool,
+}
+
+impl Session {
+    fn roll(&mut self, notation: &str) -> Result<Outcome, DiceError> {
+        if self.wide {
+            let result = self.roller.roll_i64(notation, &mut self.rng)?;
+            Ok(Outcome {
+                values: result.values(),
+                passed: result.passed(),
+                critical_success: result.has_critical_success(),
+                critical_failure: result.has_critical_failure(),
+            })
+        } else {
+            let result = self.roller.roll(notation, &mut self.rng)?;
+            Ok(Outcome {
+                values: result.values().into_iter().map(i64::from).collect(),
+                passed: result.passed(),
+                critical_success: result.has_critical_success(),
+                critical_failure: result.has_critical_failure(),
+            })
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -39,7 +182,10 @@ enum Commands {
     /// Roll dice using the specified notation
     Roll {
         /// Dice notation to roll
-        #[arg(help = "Dice notation like '4d10 + 17', '2d20 - 3', etc.")]
+        #[arg(
+            allow_hyphen_values = true,
+            help = "Dice notation like '4d10 + 17', '2d20 - 3', etc."
+        )]
         notation: String,
 
         /// Number of times to repeat the roll
@@ -51,10 +197,13 @@ enum Commands {
     /// Run statistical analysis on dice rolls
     Stats {
         /// Dice notation to analyze
-        #[arg(help = "Dice notation like '3d6', '2d20', etc.")]
+        #[arg(
+            allow_hyphen_values = true,
+            help = "Dice notation like '3d6', '2d20', etc."
+        )]
         notation: String,
 
-        /// Number of rolls for statistical analysis
+        /// Number of rolls to sample when no exact distribution is available
         #[arg(short = 'n', long, default_value = "1000")]
         rolls: usize,
 
@@ -62,6 +211,15 @@ enum Commands {
         #[arg(short, long)]
         verbose: bool,
     },
+    /// Print dice notation in its normalized form
+    Fmt {
+        /// Dice notation to normalize
+        #[arg(
+            allow_hyphen_values = true,
+            help = "Dice notation like '4d6K3+2', '(2 d 6+3)*2', etc."
+        )]
+        notation: String,
+    },
     /// Start interactive shell for continuous dice rolling
     Shell,
     /// Roll Daggerheart Duality dice (2d12 with Hope/Fear mechanics)
@@ -69,12 +227,37 @@ enum Commands {
     Dh,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            // Dice errors are rendered with a caret under the mistake
+            if let Some(dice_error) = error
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<DiceError>())
+            {
+                eprintln!("{}", dice_error.render());
+            } else {
+                eprintln!("Error: {error:?}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let mut session = Session {
+        roller: Roller::new(cli.limits.to_limits()),
+        rng: cli.seed.map_or_else(
+            || ChaCha8Rng::from_rng(&mut rand::rng()),
+            ChaCha8Rng::seed_from_u64,
+        ),
+        wide: cli.wide,
+    };
 
     match cli.command {
         Some(Commands::Roll { notation, repeat }) => {
-            roll_dice(&notation, repeat)
+            roll_dice(&notation, repeat, &mut session)
                 .with_context(|| format!("Failed to roll dice with notation '{notation}'"))?;
         }
         Some(Commands::Examples) => {
@@ -85,20 +268,27 @@ fn main() -> Result<()> {
             rolls,
             verbose,
         }) => {
-            run_statistics(&notation, rolls, verbose)
+            run_statistics(&notation, rolls, verbose, &mut session)
                 .with_context(|| format!("Failed to run statistics for notation '{notation}'"))?;
         }
+        Some(Commands::Fmt { notation }) => {
+            let expression = session
+                .roller
+                .parse(&notation)
+                .with_context(|| format!("Invalid dice notation: '{notation}'"))?;
+            println!("{expression}");
+        }
         Some(Commands::Shell) => {
-            run_interactive_shell();
+            run_interactive_shell(&mut session);
         }
         Some(Commands::Dh) => {
-            roll_daggerheart_duality()
+            roll_daggerheart_duality(&mut session)
                 .with_context(|| "Failed to roll Daggerheart duality dice")?;
         }
         None => {
             // Handle direct dice notation or show help
             if let Some(dice_notation) = cli.dice {
-                roll_dice(&dice_notation, cli.repeat).with_context(|| {
+                roll_dice(&dice_notation, cli.repeat, &mut session).with_context(|| {
                     format!("Failed to roll dice with notation '{dice_notation}'")
                 })?;
             } else {
@@ -111,30 +301,40 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn roll_dice(notation: &str, repeat: usize) -> Result<()> {
+fn roll_dice(notation: &str, repeat: usize, session: &mut Session) -> Result<()> {
     if repeat > 1 {
         println!("Rolling '{notation}' {repeat} time(s)");
     }
 
     for i in 1..=repeat {
-        let results = rollpoly::roll(notation)
+        let outcome = session
+            .roll(notation)
             .with_context(|| format!("Invalid dice notation: '{notation}'"))?;
 
-        let sum = results.iter().sum::<i32>();
+        let sum = outcome.sum();
+        let results = &outcome.values;
+        let verdict = outcome.verdict();
+        let highlight = outcome.highlight();
 
         if repeat > 1 {
-            println!("Roll {i}: You rolled a {sum} with {notation} using dice {results:?}");
+            println!(
+                "Roll {i}: You rolled a {sum}{verdict} with {notation} using dice {results:?}{highlight}"
+            );
         } else {
-            println!("You rolled a {sum} with {notation} using dice {results:?}");
+            println!(
+                "You rolled a {sum}{verdict} with {notation} using dice {results:?}{highlight}"
+            );
         }
     }
 
     Ok(())
 }
 
-fn roll_daggerheart_duality() -> Result<()> {
-    let results = rollpoly::roll("2d12")
-        .with_context(|| "Failed to roll 2d12 for Daggerheart duality dice")?;
+fn roll_daggerheart_duality(session: &mut Session) -> Result<()> {
+    let results = session
+        .roll("2d12")
+        .with_context(|| "Failed to roll 2d12 for Daggerheart duality dice")?
+        .values;
 
     let hope_die = results[0]; // First die represents Hope
     let fear_die = results[1]; // Second die represents Fear
@@ -151,6 +351,7 @@ fn roll_daggerheart_duality() -> Result<()> {
     Ok(())
 }
 
+#[allow(clippy::too_many_lines)] // A flat list of examples, one line each
 fn show_examples() {
     println!("Rollpoly - Dice Notation Examples");
     println!("=================================");
@@ -166,6 +367,24 @@ fn show_examples() {
     println!("  rollpoly '1d4 * 2'  # Roll 1d4 and multiply by 2");
     println!("  rollpoly '5d6 / 3'  # Roll 5d6 and divide by 3");
     println!("  rollpoly '4d8 // 2' # Roll 4d8 and floor divide by 2");
+    println!("  rollpoly 'd100 % 10' # Roll d100 and keep the remainder after dividing by 10");
+    println!("  rollpoly '2 ^ 1d4'  # Raise 2 to the power of 1d4 (also '**')");
+    println!();
+    println!("Negative terms:");
+    println!("  rollpoly '-1d6 + 10'   # Subtract a die from a fixed value");
+    println!("  rollpoly '2d6 - -3'    # Subtract a negative number");
+    println!();
+    println!("Comparisons and conditionals:");
+    println!("  rollpoly '1d20 + 5 >= 15'         # Pass (1) or fail (0) against a target");
+    println!("  rollpoly '1d20 >= 15 ? 2d6 : 0'   # Roll damage only on a hit");
+    println!("  rollpoly '1d20 = 20 ? 4d6 : 2d6'  # Double damage dice on a natural 20");
+    println!();
+    println!("Functions:");
+    println!("  rollpoly 'max(1d20, 1d20) + 5' # Roll with advantage, add 5");
+    println!("  rollpoly 'max(1, 1d4 - 2)'     # Damage of at least 1");
+    println!("  rollpoly 'ceil(3d6 / 2)'       # Halve, rounding up (also floor, round)");
+    println!("  rollpoly 'clamp(1d20 + 8, 1, 20)' # Keep the result between 1 and 20");
+    println!("  rollpoly 'abs(1d6 - 1d6)'      # Difference between two dice");
     println!();
     println!("Dice-to-dice operations:");
     println!("  rollpoly '2d12 + 1d6' # Daggerheart with Advantage");
@@ -176,33 +395,46 @@ fn show_examples() {
     println!("Game-specific commands:");
     println!("  rollpoly dh             # Daggerheart Duality dice (2d12 Hope/Fear)");
     println!();
-    println!("Keep highest (K) and keep lowest (k):");
+    println!("Fate and percentile dice:");
+    println!("  rollpoly 4dF        # Four Fate dice, -4 to +4 (Fate Core)");
+    println!("  rollpoly 4dF.1      # Fate dice with a single +/- face");
+    println!("  rollpoly d%         # Percentile die, same as d100");
+    println!("  rollpoly 'd{{1,1,2,3,5,8}}' # Custom faces, one side per listed face");
+    println!("  rollpoly '3d{{2..7}}'  # Custom faces from a range, 2 to 7");
+    println!();
+    println!("Keep highest (K or kh), keep lowest (k or kl) and keep middle (km):");
     println!("  rollpoly 4d10K      # Roll 4d10 and keep only the highest");
+    println!("  rollpoly 4d6kh3     # Roll 4d6 and keep the highest 3");
     println!("  rollpoly 7d12K3     # Roll 7d12 and keep the highest 3");
     println!("  rollpoly 3d6k       # Roll 3d6 and keep only the lowest");
     println!("  rollpoly 5d6k3      # Roll 5d6 and keep the lowest 3");
     println!("  rollpoly 2d20K      # Advantage roll (D&D 5e)");
     println!("  rollpoly 2d20k      # Disadvantage roll (D&D 5e)");
+    println!("  rollpoly 7d6km3     # Roll 7d6 and keep the middle 3");
     println!("  rollpoly '4d6K3 + 2' # Keep highest 3 of 4d6, then add 2");
     println!();
-    println!("Drop highest (X) and drop lowest (x):");
+    println!("Drop highest (X or dh) and drop lowest (dl):");
     println!("  rollpoly 6d8X       # Roll 6d8 and drop the highest");
     println!("  rollpoly 5d10X3     # Roll 5d10 and drop the highest 3");
-    println!("  rollpoly 6d8x       # Roll 6d8 and drop the lowest");
-    println!("  rollpoly 5d10x3     # Roll 5d10 and drop the lowest 3");
-    println!("  rollpoly 4d6x       # Character generation (drop lowest)");
+    println!("  rollpoly 6d8dl      # Roll 6d8 and drop the lowest");
+    println!("  rollpoly 5d10dl3    # Roll 5d10 and drop the lowest 3");
+    println!("  rollpoly 4d6dl1     # Character generation (drop lowest)");
     println!("  rollpoly '6d6X2 + 5' # Drop highest 2 of 6d6, then add 5");
+    println!("  rollpoly 6d6dh1dl1  # Trim the highest and lowest, keep the middle 4");
     println!();
-    println!("Count successes (> or <):");
+    println!("Count successes (>, <, >=, <=, =, != or a value set):");
     println!("  rollpoly '5d10>7'   # Count rolls above 7 (World of Darkness)");
     println!("  rollpoly '12d6>4'   # Count rolls above 4 (Shadowrun)");
     println!("  rollpoly '8d6<3'    # Count rolls below 3");
     println!("  rollpoly 'd20>15'   # Single die success check");
+    println!("  rollpoly '10d10>=8' # Count rolls of 8 or higher");
+    println!("  rollpoly '6d6{{5,6}}' # Count 5s and 6s");
     println!();
     println!("Count successes with failures (f):");
     println!("  rollpoly '10d10>6f<3' # Successes >6, failures <3");
     println!("  rollpoly '4d20<5f>19' # Successes <5, failures >19");
     println!("  rollpoly '6d6>4f<2'   # Advanced dice pool mechanics");
+    println!("  rollpoly '10d10>=8f=1' # Successes 8+, failures on 1s");
     println!();
     println!("Exploding dice (!):");
     println!("  rollpoly '2d6!'       # Explode on max (6s)");
@@ -210,40 +442,93 @@ fn show_examples() {
     println!("  rollpoly '3d10!10'    # Explode on 10s");
     println!("  rollpoly 'd20!>15'    # Explode on 16+ (Rule of 6 variant)");
     println!("  rollpoly '2d12!<3'    # Explode on 1s and 2s");
+    println!("  rollpoly '3d10!>=9'   # Explode on 9s and 10s");
+    println!("  rollpoly '1d6!!'      # Compounding: add explosions into the die (Savage Worlds)");
+    println!("  rollpoly '1d6!p'      # Penetrating: each explosion counts 1 less (HackMaster)");
     println!();
     println!("Rerolling Dice (r/R):");
     println!("  rollpoly '4d6r1'      # Reroll any 1s once (Great Weapon Fighting)");
     println!("  rollpoly '2d6r<3'     # Reroll anything under 3 once");
     println!("  rollpoly '3d8R1'      # Keep rerolling 1s until no 1s remain");
     println!("  rollpoly '4d10R<3'    # Keep rerolling anything under 3");
+    println!("  rollpoly '4d6r{{1,2}}'  # Reroll 1s and 2s once");
+    println!("  rollpoly '2d6r<=2'    # Reroll 2 or lower once");
+    println!();
+    println!("Chaining modifiers (reroll, explode, keep/drop, then count):");
+    println!("  rollpoly '4d6r1K3'    # Reroll 1s once, keep the highest 3");
+    println!("  rollpoly '5d10!>8>7'  # Explode on 9+, count rolls above 7");
+    println!("  rollpoly '6d6R1X2'    # Keep rerolling 1s, drop the highest 2");
+    println!("  rollpoly '1d20r1r2'   # Reroll 1s and 2s once");
+    println!();
+    println!("Critical successes and failures (cs/cf):");
+    println!("  rollpoly '1d20cscf + 5'  # Call out natural 20s and natural 1s");
+    println!("  rollpoly '1d20cs>18 + 5' # Critical success on 19 or 20");
+    println!("  rollpoly '2d20K1cs20cf1' # Advantage, judged on the kept die");
+    println!();
+    println!("Rolled counts and sides:");
+    println!("  rollpoly '(1d4)d6'    # Roll 1d4, then roll that many d6");
+    println!("  rollpoly '2d(1d3*4)'  # Two dice with 4, 8 or 12 sides");
+    println!("  rollpoly '4d6K(1d3)'  # Keep the highest 1 to 3, decided by 1d3");
+    println!();
+    println!("Grouped pools ({{...}}):");
+    println!("  rollpoly '{{2d6, 1d8, 1d10}}K1'     # Keep the highest of three totals");
+    println!("  rollpoly '{{1d20+5, 1d20+3}}kh1'    # Keep the better of two attacks");
+    println!("  rollpoly '{{3d6, 3d6, 3d6}}>10'     # Count totals over 10");
     println!();
     println!("Repeat Rolls (x):");
     println!("  rollpoly '3d6x6'      # Roll 3d6 six times, return 6 roll results");
     println!("  rollpoly '2d20x3'     # Roll 2d20 three times, return 3 roll results");
     println!("  rollpoly '1d4x10'     # Roll 1d4 ten times, return 10 roll results");
     println!("  rollpoly '4d6K3x4'    # Roll 4d6K3 four times, return 4 roll results");
+    println!("  rollpoly '(4d6K3x7)K6' # Roll seven stats, keep the best six");
+    println!("  rollpoly '(3d6x6)>=15' # Count rolls of 15 or more");
+    println!("  rollpoly 'sort(4d6K3x6)' # List the rolls highest first");
     println!();
     println!("Using subcommands:");
     println!("  rollpoly roll '2d6 + 3' -n 5    # Roll 5 times");
     println!("  rollpoly roll '4d6K3' -n 3      # Roll multiple times");
-    println!("  rollpoly stats 3d6 -n 10000     # Statistical analysis");
-    println!("  rollpoly stats 2d6 -n 100 -v    # Stats with verbose distribution");
+    println!("  rollpoly fmt '(2 d 6+3)*2'      # Normalize notation: (2d6 + 3) * 2");
+    println!("  rollpoly stats 3d6              # Exact statistical analysis");
+    println!("  rollpoly stats 2d6 -v           # Stats with full probability distribution");
+    println!("  rollpoly stats 3d6! -n 10000    # Sampled analysis for exploding dice");
     println!();
     println!("Options:");
-    println!("  -n, --repeat N    # Repeat the roll N times");
+    println!("  -n, --repeat N             # Repeat the roll N times");
+    println!("  --seed N                   # Seed the dice for reproducible rolls");
+    println!("  --i64                      # Compute totals in 64-bit integers");
+    println!("  --max-dice-per-term N      # Allow up to N dice per term (default 25)");
+    println!("  --max-sides N              # Also --max-total-dice, --max-explosions,");
+    println!("                             #      --max-rerolls, --max-repeat and --max-depth");
 }
 
-fn run_statistics(notation: &str, rolls: usize, verbose: bool) -> Result<()> {
+fn run_statistics(
+    notation: &str,
+    rolls: usize,
+    verbose: bool,
+    session: &mut Session,
+) -> Result<()> {
+    let expression = session
+        .roller
+        .parse(notation)
+        .with_context(|| format!("Invalid dice notation for statistics: '{notation}'"))?;
+
+    if let Some(distribution) = session.roller.distribution(&expression) {
+        show_exact_statistics(notation, &distribution, verbose);
+        return Ok(());
+    }
+
     if verbose {
+        println!("No exact distribution available for '{notation}', sampling instead");
         println!("Running statistical analysis for '{notation}' over {rolls} rolls");
     }
 
     let mut sums = Vec::with_capacity(rolls);
 
     for _ in 0..rolls {
-        let roll_result = rollpoly::roll(notation)
+        let roll_result = session
+            .roll(notation)
             .with_context(|| format!("Invalid dice notation for statistics: '{notation}'"))?;
-        let sum: i32 = roll_result.iter().sum();
+        let sum = roll_result.sum();
         sums.push(sum);
     }
 
@@ -251,16 +536,17 @@ fn run_statistics(notation: &str, rolls: usize, verbose: bool) -> Result<()> {
     let min_sum = *sums.iter().min().unwrap();
     let max_sum = *sums.iter().max().unwrap();
     #[allow(clippy::cast_precision_loss)]
-    let average = f64::from(sums.iter().sum::<i32>()) / sums.len() as f64;
+    let average = sums.iter().map(|&sum| sum as f64).sum::<f64>() / sums.len() as f64;
 
     // Calculate median
     let mut sorted_sums = sums.clone();
     sorted_sums.sort_unstable();
+    #[allow(clippy::cast_precision_loss)]
     let median = if sorted_sums.len() % 2 == 0 {
         let mid = sorted_sums.len() / 2;
-        f64::from(sorted_sums[mid - 1] + sorted_sums[mid]) / 2.0
+        (sorted_sums[mid - 1] as f64 + sorted_sums[mid] as f64) / 2.0
     } else {
-        f64::from(sorted_sums[sorted_sums.len() / 2])
+        sorted_sums[sorted_sums.len() / 2] as f64
     };
 
     println!("Statistical Analysis for '{notation}' ({rolls} rolls)");
@@ -295,6 +581,24 @@ fn run_statistics(notation: &str, rolls: usize, verbose: bool) -> Result<()> {
     Ok(())
 }
 
+fn show_exact_statistics(notation: &str, distribution: &rollpoly::Distribution, verbose: bool) {
+    println!("Exact Analysis for '{notation}'");
+    println!("==========================================");
+    println!("Minimum sum: {}", distribution.min());
+    println!("Maximum sum: {}", distribution.max());
+    println!("Average sum: {:.2}", distribution.mean());
+    println!("Median sum:  {}", distribution.median());
+    println!("Std. dev.:   {:.2}", distribution.standard_deviation());
+
+    if verbose {
+        println!("\nDistribution:");
+        for (sum, probability) in distribution.iter() {
+            let percentage = probability * 100.0;
+            println!("  Sum {sum}: {percentage:.2}%");
+        }
+    }
+}
+
 fn show_interactive_mode() {
     println!("Rollpoly - Advanced Dice Rolling");
     println!("================================");
@@ -306,6 +610,7 @@ fn show_interactive_mode() {
     println!("  rollpoly shell               # Start interactive shell");
     println!("  rollpoly examples            # Show notation examples");
     println!("  rollpoly stats <NOTATION>    # Run statistical analysis");
+    println!("  rollpoly fmt <NOTATION>      # Print notation in normalized form");
     println!("  rollpoly --help              # Show detailed help");
     println!();
     println!("Examples:");
@@ -316,7 +621,7 @@ fn show_interactive_mode() {
     println!("  rollpoly roll 4d10 -n 5");
 }
 
-fn run_interactive_shell() {
+fn run_interactive_shell(session: &mut Session) {
     use rustyline::error::ReadlineError;
     use rustyline::{DefaultEditor, Result as RustylineResult};
 
@@ -334,7 +639,7 @@ fn run_interactive_shell() {
         Err(e) => {
             println!("❌ Failed to initialize readline: {e}");
             println!("💡 Falling back to basic input mode...");
-            run_basic_shell();
+            run_basic_shell(session);
             return;
         }
     };
@@ -388,7 +693,7 @@ fn run_interactive_shell() {
                         continue;
                     }
                     "dh" | "daggerheart" => {
-                        match roll_daggerheart_duality() {
+                        match roll_daggerheart_duality(session) {
                             Ok(()) => {}
                             Err(e) => println!("❌ Error rolling Daggerheart duality dice: {e}"),
                         }
@@ -398,14 +703,13 @@ fn run_interactive_shell() {
                 }
 
                 // Try to parse and roll dice
-                match rollpoly::roll(input) {
-                    Ok(results) => {
-                        let sum = results.iter().sum::<i32>();
-                        let response = generate_roll_response(sum, &results);
+                match session.roll(input) {
+                    Ok(outcome) => {
+                        let response = generate_roll_response(&outcome);
                         println!("{response}");
                     }
                     Err(e) => {
-                        println!("❌ Error: {e}");
+                        println!("❌ {}", e.render());
                         println!("Type 'help' for available commands or 'examples' for dice notation examples.");
                     }
                 }
@@ -435,7 +739,7 @@ fn run_interactive_shell() {
 }
 
 // Fallback function for basic shell without readline
-fn run_basic_shell() {
+fn run_basic_shell(session: &mut Session) {
     use std::io::{self, Write};
 
     loop {
@@ -485,14 +789,13 @@ fn run_basic_shell() {
                 }
 
                 // Try to parse and roll dice
-                match rollpoly::roll(input) {
-                    Ok(results) => {
-                        let sum = results.iter().sum::<i32>();
-                        let response = generate_roll_response(sum, &results);
+                match session.roll(input) {
+                    Ok(outcome) => {
+                        let response = generate_roll_response(&outcome);
                         println!("{response}");
                     }
                     Err(e) => {
-                        println!("❌ Error: {e}");
+                        println!("❌ {}", e.render());
                         println!("Type 'help' for available commands or 'examples' for dice notation examples.");
                     }
                 }
@@ -536,15 +839,18 @@ fn show_command_history(editor: &rustyline::DefaultEditor) {
     println!("Use Up/Down arrows to navigate history");
 }
 
-fn generate_roll_response(sum: i32, results: &[i32]) -> String {
+fn generate_roll_response(outcome: &Outcome) -> String {
     // Format the dice results
-    let dice_display = format_dice_results(results);
+    let dice_display = format_dice_results(&outcome.values);
+    let sum = outcome.sum();
+    let verdict = outcome.verdict();
+    let highlight = outcome.highlight();
 
     // Simple, clean format with colon - no grammar issues
-    format!("🎲 You rolled: {sum}! {dice_display}")
+    format!("🎲 You rolled: {sum}{verdict}! {dice_display}{highlight}")
 }
 
-fn format_dice_results(results: &[i32]) -> String {
+fn format_dice_results(results: &[i64]) -> String {
     if results.len() == 1 {
         format!("{}", results[0])
     } else if results.len() <= 6 {
@@ -567,6 +873,7 @@ fn show_shell_help() {
     println!("  2d6               Roll two 6-sided dice");
     println!("  3d6 + 5           Roll 3d6 and add 5");
     println!("  4d10K3            Roll 4d10 and keep highest 3");
+    println!("  1d20cscf + 5      Roll 1d20 + 5, calling out natural 20s and 1s");
     println!();
     println!("Shell Commands:");
     println!("  help, h           Show this help message");
@@ -19,124 +19,359 @@
 
 #![allow(clippy::cast_sign_loss)] // All casts are validated to be positive
 
-use crate::DiceError;
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+use rand::Rng;
+
+use crate::limits::RollLimits;
+use crate::{DiceError, ParseErrorKind};
 
 /// Abstract Syntax Tree for dice expressions
+///
+/// Produced by [`parse`](crate::parse) and rolled by [`evaluate`](crate::evaluate).
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum DiceExpression {
-    /// Simple dice roll (e.g., "3d6")
-    Simple { count: usize, sides: i32 },
-
-    /// Keep highest dice (e.g., "4d6K3")
-    KeepHighest {
+    /// Dice term with its modifiers (e.g., "3d6", "4dF", "4d6r1K3")
+    ///
+    /// Modifiers are stored in the order they are applied; see [`Modifier`].
+    Dice {
         count: usize,
-        sides: i32,
-        keep: usize,
+        die: DieType,
+        modifiers: Vec<Modifier>,
     },
 
-    /// Keep lowest dice (e.g., "4d6k2")
-    KeepLowest {
-        count: usize,
-        sides: i32,
-        keep: usize,
+    /// Dice term whose count, sides or keep/drop counts are rolled first
+    /// (e.g., "(1d4)d6", "2d(1d3*4)", "4d6K(1d3)")
+    ///
+    /// A written-out count is a [`Constant`](Self::Constant), and each modifier
+    /// carries the expression its count is rolled from, if any. The rolled
+    /// values are checked against the limits when the term is rolled. A rolled
+    /// count of zero rolls no dice, and a rolled keep or drop count larger than
+    /// the pool keeps or drops every die.
+    DynamicDice {
+        count: Box<Self>,
+        die: DynamicDie,
+        modifiers: Vec<(Modifier, Option<Self>)>,
     },
 
-    /// Drop highest dice (e.g., "5d6X2")
-    DropHighest {
-        count: usize,
-        sides: i32,
-        drop: usize,
+    /// Group of expressions whose totals are kept, dropped or counted the way
+    /// a dice term's dice are (e.g., "{2d6, 1d8, 1d10}K1", "{1d20+5, 1d20+3}kh1")
+    ///
+    /// Only keep/drop selections and success counting apply to a group.
+    Group {
+        expressions: Vec<Self>,
+        modifiers: Vec<Modifier>,
     },
 
-    /// Exploding dice (e.g., "3d6!", "2d10!>8")
-    Exploding {
-        count: usize,
-        sides: i32,
-        condition: ExplodeCondition,
+    /// Repeat rolls (e.g., "3d6x6", "2d20x3")
+    Repeat { expression: Box<Self>, times: usize },
+
+    /// Repeat in parentheses whose rolls are kept, dropped or counted as a
+    /// list (e.g., "(4d6K3x7)K6", "(3d6x6)>=15")
+    ///
+    /// The rolls are treated like the members of a [`Group`](Self::Group),
+    /// so the same modifiers apply.
+    RepeatList {
+        expression: Box<Self>,
+        times: usize,
+        modifiers: Vec<Modifier>,
     },
 
-    /// Success counting (e.g., "5d10>6")
-    SuccessCounting {
-        count: usize,
-        sides: i32,
-        target: i32,
+    /// Binary arithmetic operation (e.g., "2d6 + 3", "4d8 * 2d4")
+    Binary {
+        left: Box<Self>,
+        op: BinaryOp,
+        right: Box<Self>,
+    },
+
+    /// Constant value (e.g., "5" in "2d6 + 5")
+    Constant(i32),
+
+    /// Unary negation (e.g., "-1d6", "-(2d4 + 1)")
+    ///
+    /// A minus sign directly before a number is part of the number instead,
+    /// so "-3" parses as `Constant(-3)`.
+    Negate(Box<Self>),
+
+    /// Comparison of two totals, 1 when it holds and 0 otherwise (e.g., "1d20 + 5 >= 15")
+    Compare {
+        left: Box<Self>,
         comparison: Comparison,
+        right: Box<Self>,
     },
 
-    /// Success/failure counting (e.g., "10d10>6f<3")
-    SuccessFailure {
-        count: usize,
-        sides: i32,
-        success_target: i32,
-        success_comparison: Comparison,
-        failure_target: i32,
-        failure_comparison: Comparison,
+    /// Conditional that rolls only the branch chosen by a non-zero or zero
+    /// condition (e.g., "1d20 >= 15 ? 2d6 : 0")
+    Conditional {
+        condition: Box<Self>,
+        then: Box<Self>,
+        otherwise: Box<Self>,
     },
 
-    /// Rerolling dice (e.g., "4d6r1", "3d8R<3")
-    Rerolling {
-        count: usize,
-        sides: i32,
-        condition: RerollCondition,
+    /// Function call (e.g., "max(1d20, 1d20)", "floor(3d6 / 2)")
+    Function { function: Function, args: Vec<Self> },
+
+    /// Symbolic dice rolled by a [`SymbolRegistry`](crate::SymbolRegistry) (e.g., "2dAbility")
+    Symbolic { count: usize, name: String },
+}
+
+/// The kind of die rolled by a dice term
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DieType {
+    /// Die numbered from 1 to the number of sides (e.g., "d6"; "d%" is a d100)
+    Numbered(i32),
+    /// Fate/Fudge die with two -1, two 0 and two +1 faces (e.g., "dF", "dF.2")
+    Fate,
+    /// Fate/Fudge variant with one -1, four 0 and one +1 face (e.g., "dF.1")
+    FateSingle,
+    /// Die with one side per listed face (e.g., "d{1,1,2,3,5,8}", "d{2..7}")
+    Custom(Vec<i32>),
+}
+
+/// The die of a [`DiceExpression::DynamicDice`] term
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DynamicDie {
+    /// A die written out (e.g., the "d6" in "(1d4)d6")
+    Fixed(DieType),
+    /// A numbered die whose sides are rolled (e.g., "d(1d3*4)")
+    Rolled(Box<DiceExpression>),
+}
+
+/// Modifier applied to the dice of a single term
+///
+/// A term may chain several modifiers (e.g., "4d6r1K3"). They are applied as
+/// a pipeline regardless of the order they were written in: rerolls first,
+/// then explosions, then keep/drop selections, then success counting, and
+/// finally critical marking.
+/// Selections are applied in the order they were written, each one working
+/// on the dice kept by the previous one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Modifier {
+    /// Reroll dice (e.g., "r1", "R<3", "r{1,2}")
+    Reroll {
+        condition: Condition,
         reroll_type: RerollType,
     },
-
-    /// Repeat rolls (e.g., "3d6x6", "2d20x3")
-    Repeat {
-        expression: Box<DiceExpression>,
-        times: usize,
+    /// Exploding dice (e.g., "!", "!>=8", "!!", "!p")
+    Explode {
+        condition: ExplodeCondition,
+        explode_type: ExplodeType,
     },
-
-    /// Binary arithmetic operation (e.g., "2d6 + 3", "4d8 * 2d4")
-    Binary {
-        left: Box<DiceExpression>,
-        op: BinaryOp,
-        right: Box<DiceExpression>,
+    /// Keep highest dice (e.g., "K3", "kh3")
+    KeepHighest(usize),
+    /// Keep lowest dice (e.g., "k2", "kl2")
+    KeepLowest(usize),
+    /// Keep the middle dice, dropping the rest evenly from both ends (e.g., "km3")
+    ///
+    /// When an odd number of dice is dropped, the extra one is the highest.
+    KeepMiddle(usize),
+    /// Drop highest dice (e.g., "X2", "dh2")
+    DropHighest(usize),
+    /// Drop lowest dice (e.g., "dl1", "d1")
+    DropLowest(usize),
+    /// Success counting with an optional failure condition (e.g., ">6", ">=10f1", "{5,6}f=1")
+    CountSuccesses {
+        success: Condition,
+        failure: Option<Condition>,
     },
+    /// Marks kept dice as critical successes, on the highest face when there
+    /// is no condition (e.g., "cs", "cs>19")
+    CriticalSuccess(Option<Condition>),
+    /// Marks kept dice as critical failures, on the lowest face when there
+    /// is no condition (e.g., "cf", "cf<3")
+    CriticalFailure(Option<Condition>),
+}
 
-    /// Constant value (e.g., "5" in "2d6 + 5")
-    Constant(i32),
+impl Modifier {
+    /// Pipeline stage of the modifier; lower stages are applied first
+    const fn stage(&self) -> u8 {
+        match self {
+            Self::Reroll { .. } => 0,
+            Self::Explode { .. } => 1,
+            Self::KeepHighest(_)
+            | Self::KeepLowest(_)
+            | Self::KeepMiddle(_)
+            | Self::DropHighest(_)
+            | Self::DropLowest(_) => 2,
+            Self::CountSuccesses { .. } => 3,
+            Self::CriticalSuccess(_) | Self::CriticalFailure(_) => 4,
+        }
+    }
+
+    /// Why the modifier may not be repeated on a term, or `None` if it may
+    const fn once_per_term(&self) -> Option<&'static str> {
+        match self {
+            Self::Explode { .. } => Some("Dice can only explode once per term"),
+            Self::CountSuccesses { .. } => Some("Successes can only be counted once per term"),
+            Self::CriticalSuccess(_) => Some("Critical successes can only be set once per term"),
+            Self::CriticalFailure(_) => Some("Critical failures can only be set once per term"),
+            _ => None,
+        }
+    }
+
+    /// Notation written before a keep or drop count, or `None` for other modifiers
+    pub(crate) const fn selection_prefix(&self) -> Option<&'static str> {
+        match self {
+            Self::KeepHighest(_) => Some("K"),
+            Self::KeepLowest(_) => Some("k"),
+            Self::KeepMiddle(_) => Some("km"),
+            Self::DropHighest(_) => Some("X"),
+            Self::DropLowest(_) => Some("dl"),
+            _ => None,
+        }
+    }
+
+    /// The same keep or drop selection with a different count; other modifiers are unchanged
+    pub(crate) fn with_count(&self, count: usize) -> Self {
+        match self {
+            Self::KeepHighest(_) => Self::KeepHighest(count),
+            Self::KeepLowest(_) => Self::KeepLowest(count),
+            Self::KeepMiddle(_) => Self::KeepMiddle(count),
+            Self::DropHighest(_) => Self::DropHighest(count),
+            Self::DropLowest(_) => Self::DropLowest(count),
+            other => other.clone(),
+        }
+    }
 }
 
 /// Binary arithmetic operators
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum BinaryOp {
+    /// Addition (`+`)
     Add,
+    /// Subtraction (`-`)
     Subtract,
+    /// Multiplication (`*`)
     Multiply,
+    /// Truncating division (`/`)
     Divide,
+    /// Floor division (`//`)
     FloorDivide,
+    /// Remainder of floor division, never negative (`%`)
+    Modulo,
+    /// Exponentiation, right associative (`^` or `**`)
+    Power,
+}
+
+/// Functions that can be called in an expression
+///
+/// `floor`, `ceil` and `round` change how a division directly inside them is
+/// rounded (e.g., "ceil(1d6 / 2)"); on any other argument they return it unchanged,
+/// since totals are always whole numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Function {
+    /// Largest argument (`max`)
+    Max,
+    /// Smallest argument (`min`)
+    Min,
+    /// Absolute value (`abs`)
+    Abs,
+    /// Division rounded down (`floor`)
+    Floor,
+    /// Division rounded up (`ceil`)
+    Ceil,
+    /// Division rounded to the nearest whole number, halves away from zero (`round`)
+    Round,
+    /// First argument limited to the range given by the other two (`clamp`)
+    Clamp,
+    /// Sum of every argument (`sum`)
+    Sum,
+    /// The argument, with its values listed highest first (`sort`)
+    Sort,
+}
+
+impl Function {
+    /// Looks up a function by the name used in notation
+    fn from_name(name: &str) -> Option<Self> {
+        let function = match name {
+            "max" => Self::Max,
+            "min" => Self::Min,
+            "abs" => Self::Abs,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            "round" => Self::Round,
+            "clamp" => Self::Clamp,
+            "sum" => Self::Sum,
+            "sort" => Self::Sort,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    /// Smallest and largest number of arguments accepted
+    pub(crate) const fn arity(self) -> (usize, usize) {
+        match self {
+            Self::Max | Self::Min | Self::Sum => (1, usize::MAX),
+            Self::Abs | Self::Floor | Self::Ceil | Self::Round | Self::Sort => (1, 1),
+            Self::Clamp => (3, 3),
+        }
+    }
 }
 
 /// Exploding dice conditions
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ExplodeCondition {
     /// Explode on maximum value (e.g., "3d6!")
     Max,
-    /// Explode on specific value (e.g., "3d6!5")
-    Value(i32),
-    /// Explode on comparison (e.g., "3d6!>4", "2d10!<3")
-    Comparison(Comparison, i32),
+    /// Explode on faces meeting a condition (e.g., "3d6!5", "3d6!>4", "2d10!{1,10}")
+    Matches(Condition),
+}
+
+/// Condition on a single die's face
+///
+/// Shared by success counting, failures, explosions and rerolls. After 'r'
+/// and '!' a bare number means equal to (e.g., "r1" is the same as "r=1").
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Condition {
+    /// Face compares to a target (e.g., ">6", ">=10", "=1", "!=3")
+    Compare(Comparison, i32),
+    /// Face is one of the listed values (e.g., "{1,2}", "{1..3,6}")
+    OneOf(Vec<i32>),
 }
 
 /// Comparison operators
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Comparison {
+    /// Strictly greater than (`>`)
     GreaterThan,
+    /// Strictly less than (`<`)
     LessThan,
+    /// Greater than or equal to (`>=`)
+    GreaterOrEqual,
+    /// Less than or equal to (`<=`)
+    LessOrEqual,
+    /// Equal to (`=` or `==`)
+    Equal,
+    /// Not equal to (`!=`)
+    NotEqual,
 }
 
-/// Reroll conditions
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum RerollCondition {
-    /// Reroll specific value (e.g., "4d6r1")
-    Value(i32),
-    /// Reroll on comparison (e.g., "4d6r>4", "3d8r<3")
-    Comparison(Comparison, i32),
+/// Explosion types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExplodeType {
+    /// Each explosion rolls a new die (e.g., "3d6!")
+    Standard,
+    /// Explosions add into the die that exploded (e.g., "3d6!!")
+    Compounding,
+    /// Each explosion rolls a new die with 1 subtracted (e.g., "3d6!p")
+    Penetrating,
 }
 
 /// Reroll types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum RerollType {
     /// Reroll once (e.g., "4d6r1")
     Once,
@@ -144,33 +379,589 @@ pub enum RerollType {
     Continuous,
 }
 
+impl DieType {
+    /// Number of physical sides on the die
+    pub(crate) fn sides(&self) -> i32 {
+        match self {
+            Self::Numbered(sides) => *sides,
+            Self::Fate | Self::FateSingle => 6,
+            Self::Custom(faces) => i32::try_from(faces.len()).unwrap_or(i32::MAX),
+        }
+    }
+
+    /// Highest face on the die, used by explosions on the maximum
+    pub(crate) fn max_face(&self) -> i32 {
+        match self {
+            Self::Numbered(sides) => *sides,
+            Self::Fate | Self::FateSingle => 1,
+            Self::Custom(faces) => faces.iter().copied().max().unwrap_or_default(),
+        }
+    }
+
+    /// Lowest face on the die, used by critical failures without a condition
+    pub(crate) fn min_face(&self) -> i32 {
+        match self {
+            Self::Numbered(_) => 1,
+            Self::Fate | Self::FateSingle => -1,
+            Self::Custom(faces) => faces.iter().copied().min().unwrap_or_default(),
+        }
+    }
+
+    /// Every side's face value; each side is equally likely
+    pub(crate) fn faces(&self) -> Vec<i32> {
+        match self {
+            Self::Numbered(sides) => (1..=*sides).collect(),
+            Self::Fate => vec![-1, -1, 0, 0, 1, 1],
+            Self::FateSingle => vec![-1, 0, 0, 0, 0, 1],
+            Self::Custom(faces) => faces.clone(),
+        }
+    }
+
+    /// Rolls the die once
+    pub(crate) fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> i32 {
+        match self {
+            Self::Numbered(sides) => rng.random_range(1..=*sides),
+            Self::Fate => rng.random_range(-1..=1),
+            Self::FateSingle => match rng.random_range(1..=6) {
+                1 => -1,
+                6 => 1,
+                _ => 0,
+            },
+            Self::Custom(faces) => faces[rng.random_range(0..faces.len())],
+        }
+    }
+}
+
+impl Comparison {
+    /// Whether `value` compares to `target` as this operator requires
+    pub(crate) fn compare<V: Ord + Copy>(self, value: V, target: V) -> bool {
+        match self {
+            Self::GreaterThan => value > target,
+            Self::LessThan => value < target,
+            Self::GreaterOrEqual => value >= target,
+            Self::LessOrEqual => value <= target,
+            Self::Equal => value == target,
+            Self::NotEqual => value != target,
+        }
+    }
+}
+
+impl ExplodeCondition {
+    /// Whether a die showing `face` with the given highest face explodes
+    pub(crate) fn matches(&self, face: i32, max_face: i32) -> bool {
+        match self {
+            Self::Max => face == max_face,
+            Self::Matches(condition) => condition.matches(face),
+        }
+    }
+}
+
+impl Condition {
+    /// Whether a die showing `face`, or a group member totaling it, meets the condition
+    pub(crate) fn matches<V: Copy + Ord + From<i32>>(&self, face: V) -> bool {
+        match self {
+            Self::Compare(comparison, target) => comparison.compare(face, V::from(*target)),
+            Self::OneOf(values) => values.iter().any(|&value| V::from(value) == face),
+        }
+    }
+
+    /// Whether some face meets both conditions
+    fn overlaps(&self, other: &Self) -> bool {
+        // Where two conditions both hold, they also hold at one of these points
+        let candidates = |condition: &Self| match condition {
+            Self::Compare(_, target) => {
+                vec![target.saturating_sub(1), *target, target.saturating_add(1)]
+            }
+            Self::OneOf(values) => values.clone(),
+        };
+        candidates(self)
+            .into_iter()
+            .chain(candidates(other))
+            .chain([i32::MIN, i32::MAX])
+            .any(|face| self.matches(face) && other.matches(face))
+    }
+
+    /// Writes the condition, leaving out '=' before a non-negative value (e.g., "1" in "r1")
+    fn fmt_bare(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compare(Comparison::Equal, target) if *target >= 0 => write!(f, "{target}"),
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+impl DiceExpression {
+    /// Binding strength used to decide where parentheses are needed when formatting
+    ///
+    /// Mirrors the grammar: `parse_conditional` handles `? :`, `parse_comparison`
+    /// comparisons, `parse_expression` additive operators, `parse_term`
+    /// multiplicative ones, `parse_factor` negation, `parse_power` exponents, and
+    /// everything else is a primary. A negative constant reads like a negation.
+    const fn precedence(&self) -> u8 {
+        match self {
+            Self::Conditional { .. } => 1,
+            Self::Compare { .. } => 2,
+            Self::Binary {
+                op: BinaryOp::Add | BinaryOp::Subtract,
+                ..
+            } => 3,
+            Self::Binary {
+                op: BinaryOp::Power,
+                ..
+            } => 6,
+            Self::Binary { .. } => 4,
+            Self::Negate(_) => 5,
+            Self::Constant(value) if *value < 0 => 5,
+            _ => 7,
+        }
+    }
+
+    /// The rolled parts of a dynamic dice term, in the order they are rolled:
+    /// the count, the sides if rolled, then each rolled keep or drop count
+    pub(crate) fn dynamic_parts<'e>(
+        count: &'e Self,
+        die: &'e DynamicDie,
+        modifiers: &'e [(Modifier, Option<Self>)],
+    ) -> impl Iterator<Item = &'e Self> {
+        let sides = match die {
+            DynamicDie::Fixed(_) => None,
+            DynamicDie::Rolled(sides) => Some(&**sides),
+        };
+        std::iter::once(count)
+            .chain(sides)
+            .chain(modifiers.iter().filter_map(|(_, rolled)| rolled.as_ref()))
+    }
+
+    /// Writes expressions separated by commas, as in group members and function arguments
+    fn fmt_list(f: &mut fmt::Formatter<'_>, expressions: &[Self]) -> fmt::Result {
+        for (index, expression) in expressions.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{expression}")?;
+        }
+        Ok(())
+    }
+
+    /// Writes a dice term's modifiers, each with the expression its count is rolled from, if any
+    fn fmt_modifiers<'m>(
+        f: &mut fmt::Formatter<'_>,
+        modifiers: impl Iterator<Item = (&'m Modifier, Option<&'m Self>)>,
+    ) -> fmt::Result {
+        let mut modifiers = modifiers.peekable();
+        while let Some((modifier, rolled)) = modifiers.next() {
+            // "!>6" would read back as an explode condition, so a
+            // success count directly after a max explosion goes first
+            if let (
+                Modifier::Explode {
+                    condition: ExplodeCondition::Max,
+                    ..
+                },
+                Some((next, _)),
+            ) = (modifier, modifiers.peek())
+            {
+                if matches!(next, Modifier::CountSuccesses { .. }) {
+                    write!(f, "{next}{modifier}")?;
+                    modifiers.next();
+                    continue;
+                }
+            }
+            match (modifier.selection_prefix(), rolled) {
+                (Some(prefix), Some(rolled)) => write!(f, "{prefix}({rolled})")?,
+                _ => write!(f, "{modifier}")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes `expr`, wrapping it in parentheses when it binds looser than `min_precedence`
+    fn fmt_operand(f: &mut fmt::Formatter<'_>, expr: &Self, min_precedence: u8) -> fmt::Result {
+        if expr.precedence() < min_precedence {
+            write!(f, "({expr})")
+        } else {
+            write!(f, "{expr}")
+        }
+    }
+}
+
+/// Formats the expression as canonical dice notation
+///
+/// Dice terms are written without spaces (e.g., `4d6K3`), binary operators are
+/// surrounded by single spaces, and parentheses are only added where precedence
+/// or left associativity requires them. Parsing the formatted string yields the
+/// same expression.
+///
+/// ```
+/// use rollpoly::parse;
+///
+/// let expression = parse("(2 d 6+3)*2").unwrap();
+/// assert_eq!(expression.to_string(), "(2d6 + 3) * 2");
+/// assert_eq!(parse(&expression.to_string()).unwrap(), expression);
+/// ```
+impl fmt::Display for DiceExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dice {
+                count,
+                die,
+                modifiers,
+            } => {
+                write!(f, "{count}d{die}")?;
+                Self::fmt_modifiers(f, modifiers.iter().map(|modifier| (modifier, None)))
+            }
+            Self::DynamicDice {
+                count,
+                die,
+                modifiers,
+            } => {
+                if let Self::Constant(count) = **count {
+                    write!(f, "{count}d")?;
+                } else {
+                    write!(f, "({count})d")?;
+                }
+                match die {
+                    DynamicDie::Fixed(die) => write!(f, "{die}")?,
+                    DynamicDie::Rolled(sides) => write!(f, "({sides})")?,
+                }
+                let modifiers = modifiers
+                    .iter()
+                    .map(|(modifier, rolled)| (modifier, rolled.as_ref()));
+                Self::fmt_modifiers(f, modifiers)
+            }
+            Self::Group {
+                expressions,
+                modifiers,
+            } => {
+                f.write_str("{")?;
+                Self::fmt_list(f, expressions)?;
+                f.write_str("}")?;
+                Self::fmt_modifiers(f, modifiers.iter().map(|modifier| (modifier, None)))
+            }
+            Self::Repeat { expression, times } => {
+                Self::fmt_operand(f, expression, 7)?;
+                write!(f, "x{times}")
+            }
+            Self::RepeatList {
+                expression,
+                times,
+                modifiers,
+            } => {
+                f.write_str("(")?;
+                Self::fmt_operand(f, expression, 7)?;
+                write!(f, "x{times})")?;
+                Self::fmt_modifiers(f, modifiers.iter().map(|modifier| (modifier, None)))
+            }
+            // Power is right associative: its base must be a primary, while
+            // its exponent may be another power or a negation
+            Self::Binary {
+                left,
+                op: BinaryOp::Power,
+                right,
+            } => {
+                Self::fmt_operand(f, left, 7)?;
+                f.write_str(" ^ ")?;
+                Self::fmt_operand(f, right, 5)
+            }
+            Self::Binary { left, op, right } => {
+                let precedence = self.precedence();
+                // Operators are left associative, so an equal-precedence right
+                // operand needs parentheses to keep its grouping
+                Self::fmt_operand(f, left, precedence)?;
+                write!(f, " {op} ")?;
+                Self::fmt_operand(f, right, precedence + 1)
+            }
+            Self::Constant(value) => write!(f, "{value}"),
+            // "-3" would read back as a negative constant
+            Self::Negate(operand) if matches!(**operand, Self::Constant(_)) => {
+                write!(f, "-({operand})")
+            }
+            Self::Negate(operand) => {
+                f.write_str("-")?;
+                Self::fmt_operand(f, operand, 5)
+            }
+            // Comparisons do not chain, so neither side may be another comparison
+            Self::Compare {
+                left,
+                comparison,
+                right,
+            } => {
+                Self::fmt_operand(f, left, 3)?;
+                write!(f, " {comparison} ")?;
+                Self::fmt_operand(f, right, 3)
+            }
+            Self::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                Self::fmt_operand(f, condition, 2)?;
+                write!(f, " ? {then} : {otherwise}")
+            }
+            Self::Function { function, args } => {
+                write!(f, "{function}(")?;
+                Self::fmt_list(f, args)?;
+                f.write_str(")")
+            }
+            Self::Symbolic { count, name } => write!(f, "{count}d{name}"),
+        }
+    }
+}
+
+impl fmt::Display for DieType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Numbered(sides) => write!(f, "{sides}"),
+            Self::Fate => f.write_str("F"),
+            Self::FateSingle => f.write_str("F.1"),
+            Self::Custom(faces) => write_value_list(f, faces),
+        }
+    }
+}
+
+/// Writes a brace-delimited value list, as used by custom faces and value sets
+fn write_value_list(f: &mut fmt::Formatter<'_>, values: &[i32]) -> fmt::Result {
+    f.write_str("{")?;
+    let mut start = 0;
+    while start < values.len() {
+        if start > 0 {
+            f.write_str(",")?;
+        }
+        // Runs of three or more consecutive values, negative or not, are
+        // written as a range
+        let mut end = start;
+        while values
+            .get(end + 1)
+            .is_some_and(|&next| values[end].checked_add(1) == Some(next))
+        {
+            end += 1;
+        }
+        if end - start >= 2 {
+            write!(f, "{}..{}", values[start], values[end])?;
+            start = end + 1;
+        } else {
+            write!(f, "{}", values[start])?;
+            start += 1;
+        }
+    }
+    f.write_str("}")
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reroll {
+                condition,
+                reroll_type,
+            } => {
+                write!(f, "{reroll_type}")?;
+                condition.fmt_bare(f)
+            }
+            Self::Explode {
+                condition,
+                explode_type,
+            } => write!(f, "{explode_type}{condition}"),
+            Self::KeepHighest(count)
+            | Self::KeepLowest(count)
+            | Self::KeepMiddle(count)
+            | Self::DropHighest(count)
+            | Self::DropLowest(count) => {
+                write!(f, "{}{count}", self.selection_prefix().unwrap_or_default())
+            }
+            Self::CountSuccesses { success, failure } => {
+                write!(f, "{success}")?;
+                if let Some(failure) = failure {
+                    write!(f, "f{failure}")?;
+                }
+                Ok(())
+            }
+            Self::CriticalSuccess(condition) | Self::CriticalFailure(condition) => {
+                let prefix = if matches!(self, Self::CriticalSuccess(_)) {
+                    "cs"
+                } else {
+                    "cf"
+                };
+                f.write_str(prefix)?;
+                condition
+                    .as_ref()
+                    .map_or(Ok(()), |condition| condition.fmt_bare(f))
+            }
+        }
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+            Self::FloorDivide => "//",
+            Self::Modulo => "%",
+            Self::Power => "^",
+        };
+        f.write_str(symbol)
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Max => "max",
+            Self::Min => "min",
+            Self::Abs => "abs",
+            Self::Floor => "floor",
+            Self::Ceil => "ceil",
+            Self::Round => "round",
+            Self::Clamp => "clamp",
+            Self::Sum => "sum",
+            Self::Sort => "sort",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Self::GreaterThan => ">",
+            Self::LessThan => "<",
+            Self::GreaterOrEqual => ">=",
+            Self::LessOrEqual => "<=",
+            Self::Equal => "=",
+            Self::NotEqual => "!=",
+        };
+        f.write_str(symbol)
+    }
+}
+
+impl fmt::Display for ExplodeCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Max => Ok(()),
+            Self::Matches(condition) => condition.fmt_bare(f),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compare(comparison, target) => write!(f, "{comparison}{target}"),
+            Self::OneOf(values) => write_value_list(f, values),
+        }
+    }
+}
+
+impl fmt::Display for ExplodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard => f.write_str("!"),
+            Self::Compounding => f.write_str("!!"),
+            Self::Penetrating => f.write_str("!p"),
+        }
+    }
+}
+
+impl fmt::Display for RerollType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Once => f.write_str("r"),
+            Self::Continuous => f.write_str("R"),
+        }
+    }
+}
+
 /// Recursive descent parser for dice notation
 pub struct DiceParser<'a> {
     input: &'a str,
     position: usize,
+    limits: RollLimits,
+    depth: usize,
 }
 
 impl<'a> DiceParser<'a> {
-    /// Create a new parser for the given input
+    /// Create a new parser for the given input with the default limits
+    #[cfg(test)]
     pub const fn new(input: &'a str) -> Self {
-        Self { input, position: 0 }
+        Self::with_limits(input, RollLimits::new())
+    }
+
+    /// Create a new parser that enforces the given limits
+    pub const fn with_limits(input: &'a str, limits: RollLimits) -> Self {
+        Self {
+            input,
+            position: 0,
+            limits,
+            depth: 1,
+        }
     }
 
     /// Parse the input into a dice expression
     pub fn parse(&mut self) -> Result<DiceExpression, DiceError> {
-        let expr = self.parse_expression()?;
+        let expr = self.parse_conditional()?;
         self.skip_whitespace();
 
         if !self.is_at_end() {
-            return Err(DiceError::InvalidNotation {
-                input: self.input.to_string(),
-                reason: format!("Unexpected characters after position {}", self.position),
-            });
+            let rest = self.input[self.position..].trim_end();
+            return Err(self.error(
+                ParseErrorKind::UnexpectedCharacters,
+                self.position..self.position + rest.len(),
+                format!("Unexpected characters '{rest}'"),
+            ));
         }
 
         Ok(expr)
     }
 
+    /// Grammar: conditional = comparison ('?' conditional ':' conditional)?
+    fn parse_conditional(&mut self) -> Result<DiceExpression, DiceError> {
+        let condition = self.parse_comparison()?;
+        self.skip_whitespace();
+        if self.peek_char() != Some('?') {
+            return Ok(condition);
+        }
+        self.advance(); // consume '?'
+        self.depth += 1;
+        self.limits.check_depth(self.depth)?;
+
+        let then = self.parse_conditional()?;
+        self.skip_whitespace();
+        if self.peek_char() != Some(':') {
+            return Err(self.error(
+                ParseErrorKind::IncompleteConditional,
+                self.token_span(),
+                "Expected ':' in conditional expression",
+            ));
+        }
+        self.advance(); // consume ':'
+        let otherwise = self.parse_conditional()?;
+        self.depth -= 1;
+
+        Ok(DiceExpression::Conditional {
+            condition: Box::new(condition),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        })
+    }
+
+    /// Grammar: comparison = expression (('>' | '<' | '>=' | '<=' | '=' | '==' | '!=') expression)?
+    ///
+    /// A comparison directly after a dice term with no space in between counts
+    /// successes instead (e.g., "5d10>6"); see [`Modifier::CountSuccesses`].
+    fn parse_comparison(&mut self) -> Result<DiceExpression, DiceError> {
+        let left = self.parse_expression()?;
+        let Some(comparison) = self.consume_comparison_op() else {
+            return Ok(left);
+        };
+        let right = self.parse_expression()?;
+        Ok(DiceExpression::Compare {
+            left: Box::new(left),
+            comparison,
+            right: Box::new(right),
+        })
+    }
+
     /// Grammar: expression = term (('+' | '-') term)*
     fn parse_expression(&mut self) -> Result<DiceExpression, DiceError> {
         let mut left = self.parse_term()?;
@@ -188,7 +979,7 @@ impl<'a> DiceParser<'a> {
         Ok(left)
     }
 
-    /// Grammar: term = factor (('*' | '/' | '//') factor)*
+    /// Grammar: term = factor (('*' | '/' | '//' | '%') factor)*
     fn parse_term(&mut self) -> Result<DiceExpression, DiceError> {
         let mut left = self.parse_factor()?;
 
@@ -205,22 +996,62 @@ impl<'a> DiceParser<'a> {
         Ok(left)
     }
 
-    /// Grammar: factor = dice | constant | '(' expression ')'
+    /// Grammar: factor = '-' factor | power
     fn parse_factor(&mut self) -> Result<DiceExpression, DiceError> {
         self.skip_whitespace();
 
-        if self.peek_char() == Some('(') {
-            self.advance(); // consume '('
-            let expr = self.parse_expression()?;
-            self.skip_whitespace();
-            if self.peek_char() != Some(')') {
-                return Err(DiceError::InvalidNotation {
-                    input: self.input.to_string(),
-                    reason: "Expected closing parenthesis ')'".to_string(),
-                });
-            }
-            self.advance(); // consume ')'
-            Ok(expr)
+        if self.is_negation() {
+            self.advance(); // consume '-'
+            self.depth += 1;
+            self.limits.check_depth(self.depth)?;
+            let operand = self.parse_factor()?;
+            self.depth -= 1;
+            Ok(DiceExpression::Negate(Box::new(operand)))
+        } else {
+            self.parse_power()
+        }
+    }
+
+    /// Grammar: power = primary (('^' | '**') factor)?
+    ///
+    /// The exponent is a factor, so powers chain to the right and may be negated
+    /// (e.g., "2^3^2" is "2^(3^2)", "2^-1" is "2^(-1)").
+    fn parse_power(&mut self) -> Result<DiceExpression, DiceError> {
+        let base = self.parse_primary()?;
+        if !self.consume_power_op() {
+            return Ok(base);
+        }
+        self.depth += 1;
+        self.limits.check_depth(self.depth)?;
+        let exponent = self.parse_factor()?;
+        self.depth -= 1;
+        Ok(DiceExpression::Binary {
+            left: Box::new(base),
+            op: BinaryOp::Power,
+            right: Box::new(exponent),
+        })
+    }
+
+    /// Grammar: primary = function | dice | constant | parenthesized | parenthesized dice
+    ///                  | repeat list | group
+    fn parse_primary(&mut self) -> Result<DiceExpression, DiceError> {
+        self.skip_whitespace();
+
+        if let Some(name) = self.peek_function_name() {
+            self.parse_function(name)
+        } else if self.peek_char() == Some('(') {
+            let expr = self.parse_parenthesized()?;
+            // Parentheses directly followed by 'd' hold a rolled dice count (e.g., "(1d4)d6"),
+            // unless it starts a "dh" or "dl" selector on a repeat list
+            if self.peek_char() == Some('d') && !self.is_drop_selector() {
+                self.parse_dice_term(expr)
+            } else if let DiceExpression::Repeat { expression, times } = expr {
+                self.parse_repeat_list(expression, times)
+            } else {
+                Ok(expr)
+            }
+        } else if self.peek_char() == Some('{') {
+            self.parse_group()
         } else if self.is_dice_notation() {
             self.parse_dice()
         } else {
@@ -228,294 +1059,764 @@ impl<'a> DiceParser<'a> {
         }
     }
 
+    /// Grammar: parenthesized = '(' conditional ')'
+    fn parse_parenthesized(&mut self) -> Result<DiceExpression, DiceError> {
+        self.advance(); // consume '('
+        self.depth += 1;
+        self.limits.check_depth(self.depth)?;
+        let expr = self.parse_conditional()?;
+        self.depth -= 1;
+        self.skip_whitespace();
+        if self.peek_char() != Some(')') {
+            return Err(self.error(
+                ParseErrorKind::UnclosedParenthesis,
+                self.token_span(),
+                "Expected closing parenthesis ')'",
+            ));
+        }
+        self.advance(); // consume ')'
+        Ok(expr)
+    }
+
+    /// Grammar: group = '{' conditional (',' conditional)* '}' modifiers
+    fn parse_group(&mut self) -> Result<DiceExpression, DiceError> {
+        self.advance(); // consume '{'
+        self.depth += 1;
+        self.limits.check_depth(self.depth)?;
+
+        let mut expressions = vec![self.parse_conditional()?];
+        loop {
+            self.skip_whitespace();
+            match self.peek_char() {
+                Some(',') => {
+                    self.advance(); // consume ','
+                    expressions.push(self.parse_conditional()?);
+                }
+                Some('}') => {
+                    self.advance(); // consume '}'
+                    break;
+                }
+                _ => {
+                    return Err(self.error(
+                        ParseErrorKind::UnclosedBrace,
+                        self.token_span(),
+                        "Expected ',' or '}' in group",
+                    ))
+                }
+            }
+        }
+        self.depth -= 1;
+
+        let modifiers = self.parse_pool_modifiers(expressions.len())?;
+        self.parse_optional_repeat(DiceExpression::Group {
+            expressions,
+            modifiers,
+        })
+    }
+
+    /// Grammar: repeat list = '(' repeat ')' modifiers
+    ///
+    /// A parenthesized repeat without modifiers stays a plain repeat.
+    fn parse_repeat_list(
+        &mut self,
+        expression: Box<DiceExpression>,
+        times: usize,
+    ) -> Result<DiceExpression, DiceError> {
+        let modifiers = self.parse_pool_modifiers(times)?;
+        if modifiers.is_empty() {
+            return Ok(DiceExpression::Repeat { expression, times });
+        }
+        self.parse_optional_repeat(DiceExpression::RepeatList {
+            expression,
+            times,
+            modifiers,
+        })
+    }
+
+    /// Parses the modifiers of a group or repeat list of `available` totals,
+    /// which only take keep/drop selections and success counting
+    fn parse_pool_modifiers(&mut self, available: usize) -> Result<Vec<Modifier>, DiceError> {
+        let start = self.position;
+        self.parse_dice_modifiers(available)?
+            .into_iter()
+            .map(|(modifier, rolled)| {
+                let applies = modifier.selection_prefix().is_some()
+                    || matches!(modifier, Modifier::CountSuccesses { .. });
+                if applies && rolled.is_none() {
+                    Ok(modifier)
+                } else {
+                    Err(self.error(
+                        ParseErrorKind::InvalidGroupModifier,
+                        start..self.position,
+                        "Groups and lists only take keep, drop and success modifiers with written-out counts",
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    /// Whether a '-' at the current position negates a factor rather than
+    /// starting a negative number
+    ///
+    /// A number raised to a power is negated as a whole, so "-3^2" is -9.
+    fn is_negation(&self) -> bool {
+        if self.peek_char() != Some('-') {
+            return false;
+        }
+        let after = self.position + 1;
+        if !self.char_at(after).is_some_and(|c| c.is_ascii_digit())
+            || self.is_dice_notation_at(after)
+        {
+            return true;
+        }
+        let rest = self.input[after..]
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start();
+        rest.starts_with('^') || rest.starts_with("**")
+    }
+
+    /// Returns the word at the current position if a '(' follows it
+    ///
+    /// A lone 'd' is a die with an implicit count, not a function.
+    fn peek_function_name(&self) -> Option<&'a str> {
+        let rest = &self.input[self.position..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_lowercase())
+            .unwrap_or(rest.len());
+        if len < 2 || !rest[len..].trim_start().starts_with('(') {
+            return None;
+        }
+        Some(&rest[..len])
+    }
+
+    /// Grammar: function = name '(' expression (',' expression)* ')'
+    fn parse_function(&mut self, name: &str) -> Result<DiceExpression, DiceError> {
+        let start = self.position;
+        let Some(function) = Function::from_name(name) else {
+            return Err(self.error(
+                ParseErrorKind::UnknownFunction,
+                start..start + name.len(),
+                format!("Unknown function '{name}'"),
+            ));
+        };
+        self.position += name.len();
+        self.skip_whitespace();
+        self.advance(); // consume '('
+        self.depth += 1;
+        self.limits.check_depth(self.depth)?;
+
+        let mut args = vec![self.parse_conditional()?];
+        loop {
+            self.skip_whitespace();
+            match self.peek_char() {
+                Some(',') => {
+                    self.advance(); // consume ','
+                    args.push(self.parse_conditional()?);
+                }
+                Some(')') => {
+                    self.advance(); // consume ')'
+                    break;
+                }
+                _ => {
+                    return Err(self.error(
+                        ParseErrorKind::UnclosedParenthesis,
+                        self.token_span(),
+                        "Expected ',' or closing parenthesis ')'",
+                    ))
+                }
+            }
+        }
+        self.depth -= 1;
+
+        let (min, max) = function.arity();
+        if args.len() < min || args.len() > max {
+            let expected = match (min, max) {
+                (1, usize::MAX) => "at least 1 argument".to_string(),
+                (1, 1) => "1 argument".to_string(),
+                _ => format!("{min} arguments"),
+            };
+            return Err(self.error(
+                ParseErrorKind::WrongArgumentCount,
+                start..self.position,
+                format!("Function '{function}' takes {expected}, got {}", args.len()),
+            ));
+        }
+
+        Ok(DiceExpression::Function { function, args })
+    }
+
     /// Parse dice notation with optional modifiers
     fn parse_dice(&mut self) -> Result<DiceExpression, DiceError> {
         self.skip_whitespace();
 
         // Parse optional count (defaults to 1)
         let count = if self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
-            self.parse_number()? as usize
+            self.parse_number()?
         } else {
             1
         };
+        self.parse_dice_term(DiceExpression::Constant(count))
+    }
 
-        // Validate dice count
-        if count == 0 {
-            return Err(DiceError::InvalidDiceCount {
-                count: count.to_string(),
-            });
-        }
-        if count > 25 {
-            return Err(DiceError::TooManyDice { count, max: 25 });
-        }
+    /// Parse the rest of a dice term from the 'd' on, given its count
+    ///
+    /// The count, the sides and any keep/drop count may be an expression in
+    /// parentheses rolled before the dice (e.g., "(1d4)d6", "2d(1d3*4)",
+    /// "4d6K(1d3)"). Without one the term is a plain [`DiceExpression::Dice`].
+    fn parse_dice_term(&mut self, count: DiceExpression) -> Result<DiceExpression, DiceError> {
+        // Validate a written-out count, including a parenthesized constant such as "(3)d6"
+        let fixed_count = match count {
+            DiceExpression::Constant(value) => {
+                let fixed = usize::try_from(value)
+                    .ok()
+                    .filter(|&fixed| fixed > 0)
+                    .ok_or_else(|| DiceError::InvalidDiceCount {
+                        count: value.to_string(),
+                    })?;
+                self.limits.check_dice_count(fixed)?;
+                Some(fixed)
+            }
+            _ => None,
+        };
 
         self.skip_whitespace();
 
         // Expect 'd'
         if self.peek_char() != Some('d') {
-            return Err(DiceError::InvalidNotation {
-                input: self.input.to_string(),
-                reason: "Expected 'd' in dice notation".to_string(),
-            });
+            return Err(self.error(
+                ParseErrorKind::ExpectedDie,
+                self.token_span(),
+                "Expected 'd' in dice notation",
+            ));
         }
         self.advance(); // consume 'd'
 
         self.skip_whitespace();
 
-        // Parse sides
-        let sides = self.parse_number()?;
+        if let Some(fixed) = fixed_count {
+            if let Some(name) = self.parse_symbol_name() {
+                return Ok(DiceExpression::Symbolic { count: fixed, name });
+            }
+        }
+
+        let die = if self.peek_char() == Some('(') {
+            match self.parse_parenthesized()? {
+                DiceExpression::Constant(sides) => DynamicDie::Fixed(self.numbered_die(sides)?),
+                sides => DynamicDie::Rolled(Box::new(sides)),
+            }
+        } else {
+            DynamicDie::Fixed(self.parse_die_type()?)
+        };
+
+        // Check for modifiers; keep and drop counts can only be validated
+        // against a written-out count
+        let modifiers = self.parse_dice_modifiers(fixed_count.unwrap_or(usize::MAX))?;
+        let fixed = modifiers.iter().all(|(_, rolled)| rolled.is_none());
+        let expr = match (fixed_count, die) {
+            (Some(count), DynamicDie::Fixed(die)) if fixed => DiceExpression::Dice {
+                count,
+                die,
+                modifiers: modifiers
+                    .into_iter()
+                    .map(|(modifier, _)| modifier)
+                    .collect(),
+            },
+            (_, die) => DiceExpression::DynamicDice {
+                count: Box::new(count),
+                die,
+                modifiers,
+            },
+        };
+
+        self.parse_optional_repeat(expr)
+    }
+
+    /// Wraps `expr` in a repeat when 'x' and a number follow it (e.g., "3d6x6")
+    fn parse_optional_repeat(&mut self, expr: DiceExpression) -> Result<DiceExpression, DiceError> {
+        self.skip_whitespace();
+        if !self.is_repeat() {
+            return Ok(expr);
+        }
+        self.advance(); // consume 'x'
+        self.skip_whitespace();
+        let start = self.position;
+        let times = self.parse_number()? as usize;
+        if times == 0 {
+            return Err(self.error(
+                ParseErrorKind::InvalidRepeatCount,
+                start..self.position,
+                "Repeat count must be positive",
+            ));
+        }
+        self.limits.check_repeat(times)?;
+        Ok(DiceExpression::Repeat {
+            expression: Box::new(expr),
+            times,
+        })
+    }
+
+    /// Parse the name of a symbolic die: a capital letter followed by lowercase letters
+    ///
+    /// Returns `None` without consuming anything for Fate dice, including a Fate
+    /// die followed directly by a lowercase modifier (e.g., "dFdl1", "dFx3", "dFcs").
+    fn parse_symbol_name(&mut self) -> Option<String> {
+        let rest = &self.input[self.position..];
+        let len = Self::symbol_name_len(rest)?;
+        self.position += len;
+        Some(rest[..len].to_string())
+    }
+
+    /// Whether `name` reads back as a symbolic die name, see [`Self::parse_symbol_name`]
+    pub(crate) fn is_symbol_name(name: &str) -> bool {
+        Self::symbol_name_len(name) == Some(name.len())
+    }
+
+    /// Length of the symbolic die name at the start of `text`, if it starts with one
+    fn symbol_name_len(text: &str) -> Option<usize> {
+        let first = text.chars().next().filter(char::is_ascii_uppercase)?;
+        let len = 1 + text[1..]
+            .find(|c: char| !c.is_ascii_lowercase())
+            .unwrap_or(text.len() - 1);
+        if first == 'F'
+            && (len == 1 || matches!(text.as_bytes()[1], b'c' | b'd' | b'k' | b'r' | b'x'))
+        {
+            return None;
+        }
+        Some(len)
+    }
+
+    /// Parse the die after 'd': a number of sides, '%', a Fate die or a face list
+    fn parse_die_type(&mut self) -> Result<DieType, DiceError> {
+        match self.peek_char() {
+            Some('{') => Ok(DieType::Custom(self.parse_value_list("face list")?)),
+            Some('%') => {
+                self.advance(); // consume '%'
+                Ok(DieType::Numbered(100))
+            }
+            Some('F') => {
+                let start = self.position;
+                self.advance(); // consume 'F'
+                if self.peek_char() != Some('.') {
+                    return Ok(DieType::Fate);
+                }
+                self.advance(); // consume '.'
+                match self.peek_char() {
+                    Some('1') => {
+                        self.advance();
+                        Ok(DieType::FateSingle)
+                    }
+                    Some('2') => {
+                        self.advance();
+                        Ok(DieType::Fate)
+                    }
+                    _ => Err(self.error(
+                        ParseErrorKind::InvalidDieType,
+                        start..self.token_span().end,
+                        "Expected 'dF', 'dF.1' or 'dF.2'",
+                    )),
+                }
+            }
+            _ => {
+                let sides = self.parse_number()?;
+                self.numbered_die(sides)
+            }
+        }
+    }
+
+    /// Validate the sides of a numbered die
+    fn numbered_die(&self, sides: i32) -> Result<DieType, DiceError> {
         if sides <= 0 {
             return Err(DiceError::InvalidDieSize {
                 size: sides.to_string(),
             });
         }
+        self.limits.check_sides(sides)?;
+        Ok(DieType::Numbered(sides))
+    }
 
-        // Check for modifiers
-        let mut expr = self.parse_dice_modifiers(count, sides)?;
+    /// Parse a brace-delimited value list with optional inclusive ranges (e.g., "{1,1,2}", "{-1..1}")
+    ///
+    /// `what` names the list in error messages, such as "face list".
+    fn parse_value_list(&mut self, what: &str) -> Result<Vec<i32>, DiceError> {
+        self.advance(); // consume '{'
+        let mut values = Vec::new();
 
-        // Check for repeat modifier (x followed by number)
-        self.skip_whitespace();
-        if self.peek_char() == Some('x') && self.position + 1 < self.input.len() {
-            // Look ahead to see if there's a digit after potential whitespace
-            let mut lookahead_pos = self.position + 1;
-            while lookahead_pos < self.input.len()
-                && self
-                    .input
-                    .chars()
-                    .nth(lookahead_pos)
-                    .unwrap()
-                    .is_whitespace()
-            {
-                lookahead_pos += 1;
-            }
-            let next_char = if lookahead_pos < self.input.len() {
-                self.input.chars().nth(lookahead_pos)
+        loop {
+            self.skip_whitespace();
+            let start = self.position;
+            let low = self.parse_number()?;
+            self.skip_whitespace();
+            let high = if self.input[self.position..].starts_with("..") {
+                self.advance(); // consume '.'
+                self.advance(); // consume '.'
+                self.parse_number()?
             } else {
-                None
+                low
             };
+            if high < low {
+                return Err(self.error(
+                    ParseErrorKind::InvalidFaceRange,
+                    start..self.position,
+                    format!("Range {low}..{high} in {what} is empty"),
+                ));
+            }
+
+            // Check the size against the sides limit before expanding, so huge
+            // ranges are never allocated
+            let range_len =
+                usize::try_from(i64::from(high) - i64::from(low) + 1).unwrap_or(usize::MAX);
+            let len = values.len().saturating_add(range_len);
+            self.limits
+                .check_sides(i32::try_from(len).unwrap_or(i32::MAX))?;
+            values.extend(low..=high);
 
-            if next_char.is_some_and(|c| c.is_ascii_digit()) {
-                self.advance(); // consume 'x'
-                self.skip_whitespace();
-                let times = self.parse_number()? as usize;
-                if times == 0 {
-                    return Err(DiceError::InvalidNotation {
-                        input: self.input.to_string(),
-                        reason: "Repeat count must be positive".to_string(),
-                    });
+            self.skip_whitespace();
+            match self.peek_char() {
+                Some(',') => {
+                    self.advance(); // consume ','
+                }
+                Some('}') => {
+                    self.advance(); // consume '}'
+                    return Ok(values);
+                }
+                _ => {
+                    return Err(self.error(
+                        ParseErrorKind::UnclosedBrace,
+                        self.token_span(),
+                        format!("Expected ',' or '}}' in {what}"),
+                    ))
                 }
-                expr = DiceExpression::Repeat {
-                    expression: Box::new(expr),
-                    times,
-                };
             }
         }
+    }
 
-        Ok(expr)
+    /// Check if the current position starts a repeat (`x` followed by a number)
+    fn is_repeat(&self) -> bool {
+        if self.peek_char() != Some('x') {
+            return false;
+        }
+
+        // Look ahead to see if there's a digit after potential whitespace
+        let mut lookahead_pos = self.position + 1;
+        while let Some(c) = self.char_at(lookahead_pos).filter(|c| c.is_whitespace()) {
+            lookahead_pos += c.len_utf8();
+        }
+        self.char_at(lookahead_pos)
+            .is_some_and(|c| c.is_ascii_digit())
+    }
+
+    /// Check if the current position starts a "dh" or "dl" selector
+    fn is_drop_selector(&self) -> bool {
+        self.peek_char() == Some('d') && matches!(self.char_at(self.position + 1), Some('h' | 'l'))
     }
 
-    /// Parse dice modifiers (keep, drop, exploding, success counting, rerolling)
-    #[allow(clippy::too_many_lines)] // Complex but well-structured function
+    /// Parse a keep or drop selector, tracking how many dice remain kept.
+    ///
+    /// "kh", "kl" and "km" keep the highest, lowest or middle dice, with a bare
+    /// 'K' or 'k' meaning highest or lowest. "dh" and "dl" drop the highest or
+    /// lowest, with 'X' dropping the highest and a bare 'd' the lowest. A
+    /// rolled count is returned alongside the selection, which holds 0 until
+    /// it is rolled.
+    fn parse_selection_modifier(
+        &mut self,
+        kept: &mut usize,
+    ) -> Result<(Modifier, Option<DiceExpression>), DiceError> {
+        let start = self.position;
+        let c = self.advance().unwrap_or_default();
+        let which = match self.peek_char() {
+            Some(which @ ('h' | 'l' | 'm')) if c == 'k' => self.advance().unwrap_or(which),
+            Some(which @ ('h' | 'l')) if c == 'd' => self.advance().unwrap_or(which),
+            _ if matches!(c, 'K' | 'X') => 'h',
+            _ => 'l',
+        };
+        let (count, rolled) = self.parse_modifier_count()?;
+        let keep = matches!(c, 'K' | 'k');
+        let modifier = match (keep, which) {
+            (true, 'h') => Modifier::KeepHighest(count),
+            (true, 'm') => Modifier::KeepMiddle(count),
+            (true, _) => Modifier::KeepLowest(count),
+            (false, 'h') => Modifier::DropHighest(count),
+            (false, _) => Modifier::DropLowest(count),
+        };
+
+        if rolled.is_some() {
+            // The dice left after a rolled count are only known once it is rolled
+            *kept = usize::MAX;
+        } else if keep {
+            if count > *kept {
+                return Err(self.error(
+                    ParseErrorKind::KeepTooMany,
+                    start..self.position,
+                    "Cannot keep more dice than rolled",
+                ));
+            }
+            *kept = count;
+        } else {
+            if count >= *kept {
+                return Err(self.error(
+                    ParseErrorKind::DropTooMany,
+                    start..self.position,
+                    "Cannot drop all dice",
+                ));
+            }
+            *kept -= count;
+        }
+        Ok((modifier, rolled))
+    }
+
+    /// Parse the chain of modifiers following a dice term
+    ///
+    /// Keep and drop counts are validated against the dice left by the
+    /// selections written before them. The returned modifiers are ordered by
+    /// pipeline stage, see [`Modifier`], each with the expression its count
+    /// is rolled from, if any.
     fn parse_dice_modifiers(
         &mut self,
         count: usize,
-        sides: i32,
-    ) -> Result<DiceExpression, DiceError> {
-        self.skip_whitespace();
+    ) -> Result<Vec<(Modifier, Option<DiceExpression>)>, DiceError> {
+        let mut modifiers: Vec<(Modifier, Option<DiceExpression>)> = Vec::new();
+        let mut kept = count;
 
-        match self.peek_char() {
-            Some('K') => {
-                self.advance(); // consume 'K'
-                self.skip_whitespace();
-                let keep = if self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
-                    self.parse_number()? as usize
-                } else {
-                    1
-                };
-                if keep > count {
-                    return Err(DiceError::InvalidNotation {
-                        input: self.input.to_string(),
-                        reason: "Cannot keep more dice than rolled".to_string(),
-                    });
+        loop {
+            let end_of_term = self.position;
+            self.skip_whitespace();
+            let start = self.position;
+            let mut rolled = None;
+            let modifier = match self.peek_char() {
+                Some('K' | 'k' | 'X' | 'd') => {
+                    let (modifier, count) = self.parse_selection_modifier(&mut kept)?;
+                    rolled = count;
+                    modifier
                 }
-                Ok(DiceExpression::KeepHighest { count, sides, keep })
-            }
-            Some('k') => {
-                self.advance(); // consume 'k'
-                self.skip_whitespace();
-                let keep = if self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
-                    self.parse_number()? as usize
-                } else {
-                    1
-                };
-                if keep > count {
-                    return Err(DiceError::InvalidNotation {
-                        input: self.input.to_string(),
-                        reason: "Cannot keep more dice than rolled".to_string(),
-                    });
+                // Legacy drop lowest: a bare 'x' was documented as dropping the
+                // lowest die, while 'x' followed by a number remains a repeat
+                Some('x') if !self.is_repeat() => {
+                    self.advance(); // consume 'x'
+                    if kept <= 1 {
+                        return Err(self.error(
+                            ParseErrorKind::DropTooMany,
+                            start..self.position,
+                            "Cannot drop all dice",
+                        ));
+                    }
+                    kept -= 1;
+                    Modifier::DropLowest(1)
                 }
-                Ok(DiceExpression::KeepLowest { count, sides, keep })
-            }
-            Some('X') => {
-                self.advance(); // consume 'X'
-                self.skip_whitespace();
-                let drop = if self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
-                    self.parse_number()? as usize
-                } else {
-                    1
-                };
-                if drop >= count {
-                    return Err(DiceError::InvalidNotation {
-                        input: self.input.to_string(),
-                        reason: "Cannot drop all dice".to_string(),
-                    });
+                Some('!') if self.char_at(self.position + 1) != Some('=') => {
+                    self.parse_explode_modifier()?
                 }
-                Ok(DiceExpression::DropHighest { count, sides, drop })
-            }
-            Some('!') => {
-                self.advance(); // consume '!'
-                self.skip_whitespace();
-                let condition = self.parse_explode_condition()?;
-                Ok(DiceExpression::Exploding {
-                    count,
-                    sides,
-                    condition,
-                })
-            }
-            Some('>' | '<') => {
-                let comparison = if self.peek_char() == Some('>') {
-                    self.advance();
-                    Comparison::GreaterThan
-                } else {
-                    self.advance();
-                    Comparison::LessThan
-                };
-                self.skip_whitespace();
-                let target = self.parse_number()?;
-
-                self.skip_whitespace();
-                // Check for failure condition
-                if self.peek_char() == Some('f') {
-                    self.advance(); // consume 'f'
-                    self.skip_whitespace();
-                    let failure_comparison = if self.peek_char() == Some('>') {
-                        self.advance();
-                        Comparison::GreaterThan
-                    } else if self.peek_char() == Some('<') {
-                        self.advance();
-                        Comparison::LessThan
+                // After a space, comparisons compare the total instead
+                Some('>' | '<' | '=' | '!' | '{') if start == end_of_term => {
+                    self.parse_success_modifier()?
+                }
+                Some('r' | 'R') => self.parse_reroll_modifier()?,
+                Some('c') if matches!(self.char_at(self.position + 1), Some('s' | 'f')) => {
+                    self.advance(); // consume 'c'
+                    let success = self.advance() == Some('s');
+                    let condition = self.parse_optional_condition()?;
+                    if success {
+                        Modifier::CriticalSuccess(condition)
                     } else {
-                        return Err(DiceError::InvalidNotation {
-                            input: self.input.to_string(),
-                            reason: "Expected '>' or '<' after 'f'".to_string(),
-                        });
-                    };
-                    self.skip_whitespace();
-                    let failure_target = self.parse_number()?;
-
-                    // Validate that success and failure conditions don't conflict
-                    match (comparison, failure_comparison) {
-                        (Comparison::GreaterThan, Comparison::GreaterThan)
-                        | (Comparison::LessThan, Comparison::LessThan) => {
-                            return Err(DiceError::InvalidNotation {
-                                input: self.input.to_string(),
-                                reason: "Success and failure conditions cannot both be greater than or both less than".to_string(),
-                            });
-                        }
-                        _ => {}
+                        Modifier::CriticalFailure(condition)
                     }
-
-                    Ok(DiceExpression::SuccessFailure {
-                        count,
-                        sides,
-                        success_target: target,
-                        success_comparison: comparison,
-                        failure_target,
-                        failure_comparison,
-                    })
-                } else {
-                    Ok(DiceExpression::SuccessCounting {
-                        count,
-                        sides,
-                        target,
-                        comparison,
-                    })
                 }
+                _ => break,
+            };
+
+            let duplicate = modifier.once_per_term().filter(|_| {
+                modifiers
+                    .iter()
+                    .any(|(m, _)| mem::discriminant(m) == mem::discriminant(&modifier))
+            });
+            if let Some(reason) = duplicate {
+                return Err(self.error(
+                    ParseErrorKind::DuplicateModifier,
+                    start..self.position,
+                    reason,
+                ));
             }
-            Some('r' | 'R') => {
-                let reroll_type = if self.peek_char() == Some('r') {
-                    self.advance();
-                    RerollType::Once
-                } else {
-                    self.advance();
-                    RerollType::Continuous
-                };
-                self.skip_whitespace();
-                let condition = self.parse_reroll_condition()?;
-                Ok(DiceExpression::Rerolling {
-                    count,
-                    sides,
-                    condition,
-                    reroll_type,
-                })
-            }
-            _ => Ok(DiceExpression::Simple { count, sides }),
+
+            modifiers.push((modifier, rolled));
+        }
+
+        // Stable, so selections keep the order they were written in
+        modifiers.sort_by_key(|(modifier, _)| modifier.stage());
+        Ok(modifiers)
+    }
+
+    /// Parse an optional keep/drop count, defaulting to 1
+    ///
+    /// A parenthesized expression that is not a constant is rolled with the term (e.g., "K(1d3)")
+    /// and returned in place of the count, which is then 0.
+    fn parse_modifier_count(&mut self) -> Result<(usize, Option<DiceExpression>), DiceError> {
+        self.skip_whitespace();
+        let start = self.position;
+        match self.peek_char() {
+            Some(c) if c.is_ascii_digit() => Ok((self.parse_number()? as usize, None)),
+            Some('(') => match self.parse_parenthesized()? {
+                DiceExpression::Constant(count) => usize::try_from(count)
+                    .map(|count| (count, None))
+                    .map_err(|_| {
+                        self.error(
+                            ParseErrorKind::InvalidNumber,
+                            start..self.position,
+                            "Keep and drop counts cannot be negative",
+                        )
+                    }),
+                rolled => Ok((0, Some(rolled))),
+            },
+            _ => Ok((1, None)),
         }
     }
 
+    /// Parse success counting with an optional failure condition (e.g., ">6f<3", ">=10f1")
+    fn parse_success_modifier(&mut self) -> Result<Modifier, DiceError> {
+        let start = self.position;
+        let Some(success) = self.parse_condition(false)? else {
+            return Err(self.error(
+                ParseErrorKind::ExpectedComparison,
+                self.token_span(),
+                "Expected a comparison or value set",
+            ));
+        };
+
+        self.skip_whitespace();
+        // Check for failure condition
+        let failure = if self.peek_char() == Some('f') {
+            self.advance(); // consume 'f'
+            let Some(failure) = self.parse_condition(false)? else {
+                return Err(self.error(
+                    ParseErrorKind::ExpectedComparison,
+                    self.token_span(),
+                    "Expected a comparison or value set after 'f'",
+                ));
+            };
+
+            // Validate that success and failure conditions don't conflict
+            if success.overlaps(&failure) {
+                return Err(self.error(
+                    ParseErrorKind::ConflictingConditions,
+                    start..self.position,
+                    "Success and failure conditions cannot match the same face",
+                ));
+            }
+
+            Some(failure)
+        } else {
+            None
+        };
+
+        Ok(Modifier::CountSuccesses { success, failure })
+    }
+
+    /// Parse an explosion with its type and condition (e.g., "!", "!!>8", "!p5")
+    fn parse_explode_modifier(&mut self) -> Result<Modifier, DiceError> {
+        self.advance(); // consume '!'
+        let explode_type = match self.peek_char() {
+            // "!!=" is a standard explosion on faces other than a value
+            Some('!') if self.char_at(self.position + 1) != Some('=') => {
+                self.advance(); // consume the second '!'
+                ExplodeType::Compounding
+            }
+            Some('p') => {
+                self.advance(); // consume 'p'
+                ExplodeType::Penetrating
+            }
+            _ => ExplodeType::Standard,
+        };
+        let condition = self.parse_explode_condition()?;
+        Ok(Modifier::Explode {
+            condition,
+            explode_type,
+        })
+    }
+
+    /// Parse a reroll with its condition (e.g., "r1", "R<3", "r{1,2}")
+    fn parse_reroll_modifier(&mut self) -> Result<Modifier, DiceError> {
+        let reroll_type = if self.advance() == Some('r') {
+            RerollType::Once
+        } else {
+            RerollType::Continuous
+        };
+        let Some(condition) = self.parse_condition(true)? else {
+            return Err(self.error(
+                ParseErrorKind::ExpectedRerollCondition,
+                self.token_span(),
+                "Expected reroll condition after 'r' or 'R'",
+            ));
+        };
+        Ok(Modifier::Reroll {
+            condition,
+            reroll_type,
+        })
+    }
+
     /// Parse exploding dice condition
     fn parse_explode_condition(&mut self) -> Result<ExplodeCondition, DiceError> {
+        if let Some(condition) = self.parse_optional_condition()? {
+            return Ok(ExplodeCondition::Matches(condition));
+        }
+
         match self.peek_char() {
-            None | Some(' ' | '+' | '-' | '*' | '/' | ')') => {
+            // End of the term or the start of another modifier
+            None
+            | Some(
+                ' ' | '+' | '-' | '*' | '/' | '%' | '^' | ')' | ',' | '?' | ':' | 'x' | 'K' | 'k'
+                | 'X' | 'd' | 'r' | 'R' | 'c' | '!',
+            ) => {
                 // Simple exploding on max value
                 Ok(ExplodeCondition::Max)
             }
-            Some('>') => {
-                self.advance(); // consume '>'
-                let target = self.parse_number()?;
-                Ok(ExplodeCondition::Comparison(
-                    Comparison::GreaterThan,
-                    target,
-                ))
-            }
-            Some('<') => {
-                self.advance(); // consume '<'
-                let target = self.parse_number()?;
-                Ok(ExplodeCondition::Comparison(Comparison::LessThan, target))
-            }
-            Some(c) if c.is_ascii_digit() => {
-                let target = self.parse_number()?;
-                Ok(ExplodeCondition::Value(target))
-            }
-            Some(c) => Err(DiceError::InvalidNotation {
-                input: self.input.to_string(),
-                reason: format!("Unexpected character '{c}' in exploding condition"),
-            }),
+            Some(c) => Err(self.error(
+                ParseErrorKind::InvalidExplodeCondition,
+                self.position..self.position + c.len_utf8(),
+                format!("Unexpected character '{c}' in exploding condition"),
+            )),
         }
     }
 
-    /// Parse reroll condition
-    fn parse_reroll_condition(&mut self) -> Result<RerollCondition, DiceError> {
-        match self.peek_char() {
-            Some('>') => {
-                self.advance(); // consume '>'
-                let target = self.parse_number()?;
-                Ok(RerollCondition::Comparison(Comparison::GreaterThan, target))
-            }
-            Some('<') => {
-                self.advance(); // consume '<'
-                let target = self.parse_number()?;
-                Ok(RerollCondition::Comparison(Comparison::LessThan, target))
-            }
-            Some(c) if c.is_ascii_digit() => {
-                let target = self.parse_number()?;
-                Ok(RerollCondition::Value(target))
-            }
-            _ => Err(DiceError::InvalidNotation {
-                input: self.input.to_string(),
-                reason: "Expected reroll condition after 'r' or 'R'".to_string(),
-            }),
+    /// Parse a condition that may be left out, as after '!' or "cs"
+    ///
+    /// After a space, a comparison compares the total instead (e.g., "3d6! > 10"),
+    /// so it is left unconsumed. A bare negative value is left for subtraction,
+    /// so "3d6!-1" explodes on the maximum and subtracts 1.
+    fn parse_optional_condition(&mut self) -> Result<Option<Condition>, DiceError> {
+        let end_of_modifier = self.position;
+        self.skip_whitespace();
+        let spaced_comparison = self.position > end_of_modifier
+            && matches!(self.peek_char(), Some('>' | '<' | '=' | '!'));
+        if spaced_comparison || self.peek_char() == Some('-') {
+            self.position = end_of_modifier;
+            return Ok(None);
         }
+        self.parse_condition(true)
+    }
+
+    /// Parse a face condition: a comparison with its target or a value set
+    ///
+    /// Returns `None` without consuming anything but whitespace when no
+    /// condition comes next. With `bare_value`, a number on its own means
+    /// equal to (e.g., the "1" in "r1" or the "-1" in "dF.1r-1").
+    fn parse_condition(&mut self, bare_value: bool) -> Result<Option<Condition>, DiceError> {
+        self.skip_whitespace();
+        if self.peek_char() == Some('{') {
+            let values = self.parse_value_list("value set")?;
+            return Ok(Some(Condition::OneOf(values)));
+        }
+        if let Some(comparison) = self.consume_comparison_op() {
+            let target = self.parse_number()?;
+            return Ok(Some(Condition::Compare(comparison, target)));
+        }
+        let signed = usize::from(self.peek_char() == Some('-'));
+        if bare_value
+            && self
+                .char_at(self.position + signed)
+                .is_some_and(|c| c.is_ascii_digit())
+        {
+            let target = self.parse_number()?;
+            return Ok(Some(Condition::Compare(Comparison::Equal, target)));
+        }
+        Ok(None)
     }
 
     /// Parse a constant number
@@ -543,41 +1844,49 @@ impl<'a> DiceParser<'a> {
         }
 
         if start == self.position
-            || (self.position == start + 1 && self.input.chars().nth(start) == Some('-'))
+            || (self.position == start + 1 && self.char_at(start) == Some('-'))
         {
-            return Err(DiceError::InvalidNotation {
-                input: self.input.to_string(),
-                reason: "Expected number".to_string(),
-            });
+            self.position = start;
+            return Err(self.error(
+                ParseErrorKind::ExpectedNumber,
+                self.token_span(),
+                "Expected number",
+            ));
         }
 
         let number_str = &self.input[start..self.position];
-        number_str.parse().map_err(|_| DiceError::InvalidNotation {
-            input: self.input.to_string(),
-            reason: format!("Invalid number: '{number_str}'"),
+        number_str.parse().map_err(|_| {
+            self.error(
+                ParseErrorKind::InvalidNumber,
+                start..self.position,
+                format!("Invalid number: '{number_str}'"),
+            )
         })
     }
 
     /// Check if current position looks like dice notation
     fn is_dice_notation(&self) -> bool {
-        let mut pos = self.position;
+        self.is_dice_notation_at(self.position)
+    }
 
+    /// Check if the text at byte offset `pos` looks like dice notation
+    fn is_dice_notation_at(&self, mut pos: usize) -> bool {
         // Skip whitespace
-        while pos < self.input.len() && self.input.chars().nth(pos).unwrap().is_whitespace() {
-            pos += 1;
+        while let Some(c) = self.char_at(pos).filter(|c| c.is_whitespace()) {
+            pos += c.len_utf8();
         }
 
         // Check for optional number followed by 'd'
-        while pos < self.input.len() && self.input.chars().nth(pos).unwrap().is_ascii_digit() {
+        while self.char_at(pos).is_some_and(|c| c.is_ascii_digit()) {
             pos += 1;
         }
 
         // Skip whitespace after number
-        while pos < self.input.len() && self.input.chars().nth(pos).unwrap().is_whitespace() {
-            pos += 1;
+        while let Some(c) = self.char_at(pos).filter(|c| c.is_whitespace()) {
+            pos += c.len_utf8();
         }
 
-        pos < self.input.len() && self.input.chars().nth(pos) == Some('d')
+        self.char_at(pos) == Some('d')
     }
 
     /// Peek at additive operators
@@ -585,26 +1894,9 @@ impl<'a> DiceParser<'a> {
         self.skip_whitespace();
         match self.peek_char() {
             Some('+') => Some(BinaryOp::Add),
-            Some('-') => {
-                // Make sure it's not a negative number
-                if self.position + 1 < self.input.len() {
-                    let next_char = self.input.chars().nth(self.position + 1);
-                    if next_char.is_some_and(|c| c.is_ascii_digit()) {
-                        // Check if there's whitespace or alphanumeric before the minus (indicating subtraction)
-                        if self.position > 0 {
-                            let prev_char = self.input.chars().nth(self.position - 1);
-                            if prev_char
-                                .is_some_and(|c| c.is_whitespace() || c.is_ascii_alphanumeric())
-                            {
-                                return Some(BinaryOp::Subtract);
-                            }
-                        }
-                    } else {
-                        return Some(BinaryOp::Subtract);
-                    }
-                }
-                None
-            }
+            // Only called after a complete term, so a minus here always
+            // subtracts; a sign on the next operand is handled by parse_factor
+            Some('-') => Some(BinaryOp::Subtract),
             _ => None,
         }
     }
@@ -613,11 +1905,10 @@ impl<'a> DiceParser<'a> {
     fn peek_multiplicative_op(&mut self) -> Option<BinaryOp> {
         self.skip_whitespace();
         match self.peek_char() {
-            Some('*') => Some(BinaryOp::Multiply),
+            Some('*') if self.char_at(self.position + 1) != Some('*') => Some(BinaryOp::Multiply),
+            Some('%') => Some(BinaryOp::Modulo),
             Some('/') => {
-                if self.position + 1 < self.input.len()
-                    && self.input.chars().nth(self.position + 1) == Some('/')
-                {
+                if self.char_at(self.position + 1) == Some('/') {
                     Some(BinaryOp::FloorDivide)
                 } else {
                     Some(BinaryOp::Divide)
@@ -639,7 +1930,7 @@ impl<'a> DiceParser<'a> {
     fn consume_multiplicative_op(&mut self) {
         self.skip_whitespace();
         match self.peek_char() {
-            Some('*') => {
+            Some('*' | '%') => {
                 self.advance();
             }
             Some('/') => {
@@ -652,20 +1943,91 @@ impl<'a> DiceParser<'a> {
         }
     }
 
+    /// Consume a comparison operator if one comes next
+    fn consume_comparison_op(&mut self) -> Option<Comparison> {
+        self.skip_whitespace();
+        let rest = &self.input[self.position..];
+        let (comparison, len) = [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            (">", Comparison::GreaterThan),
+            ("<", Comparison::LessThan),
+            ("=", Comparison::Equal),
+        ]
+        .into_iter()
+        .find(|(symbol, _)| rest.starts_with(symbol))
+        .map(|(symbol, comparison)| (comparison, symbol.len()))?;
+        self.position += len;
+        Some(comparison)
+    }
+
+    /// Consume a power operator ('^' or '**') if one comes next
+    fn consume_power_op(&mut self) -> bool {
+        self.skip_whitespace();
+        if self.peek_char() == Some('^') {
+            self.advance();
+            true
+        } else if self.input[self.position..].starts_with("**") {
+            self.advance();
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
     /// Peek at the current character without advancing
     fn peek_char(&self) -> Option<char> {
-        self.input.chars().nth(self.position)
+        self.char_at(self.position)
+    }
+
+    /// The character starting at byte offset `pos`, if any
+    fn char_at(&self, pos: usize) -> Option<char> {
+        self.input.get(pos..)?.chars().next()
     }
 
     /// Advance to the next character
     fn advance(&mut self) -> Option<char> {
         let ch = self.peek_char();
-        if ch.is_some() {
-            self.position += 1;
+        if let Some(c) = ch {
+            self.position += c.len_utf8();
         }
         ch
     }
 
+    /// Byte span of the token at the current position
+    ///
+    /// A run of letters and digits is treated as one token so a misspelled word
+    /// is underlined in full; at the end of input the span is empty.
+    fn token_span(&self) -> Range<usize> {
+        let rest = &self.input[self.position..];
+        let len = match rest.chars().next() {
+            None => 0,
+            Some(c) if c.is_alphanumeric() => rest
+                .find(|c: char| !c.is_alphanumeric())
+                .unwrap_or(rest.len()),
+            Some(c) => c.len_utf8(),
+        };
+        self.position..self.position + len
+    }
+
+    /// Builds a parse error located at `span`
+    fn error(
+        &self,
+        kind: ParseErrorKind,
+        span: Range<usize>,
+        reason: impl Into<String>,
+    ) -> DiceError {
+        DiceError::InvalidNotation {
+            input: self.input.to_string(),
+            reason: reason.into(),
+            span,
+            kind,
+        }
+    }
+
     /// Skip whitespace characters
     fn skip_whitespace(&mut self) {
         while let Some(c) = self.peek_char() {
@@ -691,7 +2053,14 @@ mod tests {
     fn test_parse_simple_dice() {
         let mut parser = DiceParser::new("2d6");
         let expr = parser.parse().unwrap();
-        assert_eq!(expr, DiceExpression::Simple { count: 2, sides: 6 });
+        assert_eq!(
+            expr,
+            DiceExpression::Dice {
+                count: 2,
+                die: DieType::Numbered(6),
+                modifiers: vec![],
+            }
+        );
     }
 
     #[test]
@@ -700,9 +2069,10 @@ mod tests {
         let expr = parser.parse().unwrap();
         assert_eq!(
             expr,
-            DiceExpression::Simple {
+            DiceExpression::Dice {
                 count: 1,
-                sides: 20
+                die: DieType::Numbered(20),
+                modifiers: vec![],
             }
         );
     }
@@ -713,10 +2083,10 @@ mod tests {
         let expr = parser.parse().unwrap();
         assert_eq!(
             expr,
-            DiceExpression::KeepHighest {
+            DiceExpression::Dice {
                 count: 4,
-                sides: 6,
-                keep: 3
+                die: DieType::Numbered(6),
+                modifiers: vec![Modifier::KeepHighest(3)],
             }
         );
     }
@@ -727,7 +2097,14 @@ mod tests {
         let expr = parser.parse().unwrap();
         match expr {
             DiceExpression::Binary { left, op, right } => {
-                assert_eq!(*left, DiceExpression::Simple { count: 2, sides: 6 });
+                assert_eq!(
+                    *left,
+                    DiceExpression::Dice {
+                        count: 2,
+                        die: DieType::Numbered(6),
+                        modifiers: vec![],
+                    }
+                );
                 assert_eq!(op, BinaryOp::Add);
                 assert_eq!(*right, DiceExpression::Constant(3));
             }
@@ -741,7 +2118,14 @@ mod tests {
         let expr = parser.parse().unwrap();
         match expr {
             DiceExpression::Binary { left, op, right } => {
-                assert_eq!(*left, DiceExpression::Simple { count: 2, sides: 6 });
+                assert_eq!(
+                    *left,
+                    DiceExpression::Dice {
+                        count: 2,
+                        die: DieType::Numbered(6),
+                        modifiers: vec![],
+                    }
+                );
                 assert_eq!(op, BinaryOp::Add);
                 assert_eq!(*right, DiceExpression::Constant(3));
             }
@@ -757,13 +2141,21 @@ mod tests {
             DiceExpression::Binary { left, op, right } => {
                 assert_eq!(
                     *left,
-                    DiceExpression::Simple {
+                    DiceExpression::Dice {
                         count: 2,
-                        sides: 12
+                        die: DieType::Numbered(12),
+                        modifiers: vec![],
                     }
                 );
                 assert_eq!(op, BinaryOp::Add);
-                assert_eq!(*right, DiceExpression::Simple { count: 1, sides: 6 });
+                assert_eq!(
+                    *right,
+                    DiceExpression::Dice {
+                        count: 1,
+                        die: DieType::Numbered(6),
+                        modifiers: vec![],
+                    }
+                );
             }
             _ => panic!("Expected binary expression"),
         }
@@ -789,13 +2181,20 @@ mod tests {
                     } => {
                         assert_eq!(
                             *inner_left,
-                            DiceExpression::KeepHighest {
+                            DiceExpression::Dice {
                                 count: 4,
-                                sides: 6,
-                                keep: 3
+                                die: DieType::Numbered(6),
+                                modifiers: vec![Modifier::KeepHighest(3)],
+                            }
+                        );
+                        assert_eq!(
+                            *inner_right,
+                            DiceExpression::Dice {
+                                count: 2,
+                                die: DieType::Numbered(8),
+                                modifiers: vec![],
                             }
                         );
-                        assert_eq!(*inner_right, DiceExpression::Simple { count: 2, sides: 8 });
                     }
                     _ => panic!("Expected nested binary expression"),
                 }
@@ -810,10 +2209,13 @@ mod tests {
         let expr = parser.parse().unwrap();
         assert_eq!(
             expr,
-            DiceExpression::Exploding {
+            DiceExpression::Dice {
                 count: 3,
-                sides: 6,
-                condition: ExplodeCondition::Max
+                die: DieType::Numbered(6),
+                modifiers: vec![Modifier::Explode {
+                    condition: ExplodeCondition::Max,
+                    explode_type: ExplodeType::Standard,
+                }],
             }
         );
     }
@@ -824,13 +2226,1108 @@ mod tests {
         let expr = parser.parse().unwrap();
         assert_eq!(
             expr,
-            DiceExpression::SuccessCounting {
+            DiceExpression::Dice {
                 count: 5,
-                sides: 10,
-                target: 6,
-                comparison: Comparison::GreaterThan
+                die: DieType::Numbered(10),
+                modifiers: vec![Modifier::CountSuccesses {
+                    success: Condition::Compare(Comparison::GreaterThan, 6),
+                    failure: None,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_drop_lowest() {
+        for notation in ["4d6dl1", "4d6dl", "4d6d1", "4d6 dl 1"] {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(
+                expr,
+                DiceExpression::Dice {
+                    count: 4,
+                    die: DieType::Numbered(6),
+                    modifiers: vec![Modifier::DropLowest(1)],
+                },
+                "Unexpected parse for '{notation}'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_legacy_bare_x_drops_lowest() {
+        let expr = DiceParser::new("4d6x + 2").parse().unwrap();
+        assert_eq!(
+            expr,
+            DiceExpression::Binary {
+                left: Box::new(DiceExpression::Dice {
+                    count: 4,
+                    die: DieType::Numbered(6),
+                    modifiers: vec![Modifier::DropLowest(1)],
+                }),
+                op: BinaryOp::Add,
+                right: Box::new(DiceExpression::Constant(2)),
+            }
+        );
+
+        // 'x' followed by a number is still a repeat
+        let expr = DiceParser::new("3d6x6").parse().unwrap();
+        assert!(matches!(expr, DiceExpression::Repeat { times: 6, .. }));
+        let expr = DiceParser::new("4d6dl1x6").parse().unwrap();
+        assert!(matches!(expr, DiceExpression::Repeat { times: 6, .. }));
+    }
+
+    #[test]
+    fn test_parse_die_types() {
+        let cases = [
+            ("4dF", 4, DieType::Fate),
+            ("4dF.2", 4, DieType::Fate),
+            ("4dF.1", 4, DieType::FateSingle),
+            ("d%", 1, DieType::Numbered(100)),
+            ("3 d %", 3, DieType::Numbered(100)),
+        ];
+
+        for (notation, count, die) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(
+                expr,
+                DiceExpression::Dice {
+                    count,
+                    die,
+                    modifiers: vec![],
+                },
+                "Unexpected parse for '{notation}'"
+            );
+        }
+
+        let error = DiceParser::new("dF.3").parse().unwrap_err();
+        assert_eq!(error.span(), Some(1..4));
+        assert!(matches!(
+            error,
+            DiceError::InvalidNotation {
+                kind: ParseErrorKind::InvalidDieType,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_custom_faces() {
+        let cases = [
+            ("d{1,1,2,3,5,8}", 1, vec![1, 1, 2, 3, 5, 8]),
+            ("2d{-1,0,0,1}", 2, vec![-1, 0, 0, 1]),
+            ("d{2..7}", 1, vec![2, 3, 4, 5, 6, 7]),
+            ("3d{ 0, -1..1 }", 3, vec![0, -1, 0, 1]),
+        ];
+
+        for (notation, count, faces) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(
+                expr,
+                DiceExpression::Dice {
+                    count,
+                    die: DieType::Custom(faces),
+                    modifiers: vec![],
+                },
+                "Unexpected parse for '{notation}'"
+            );
+        }
+
+        let errors = [
+            ("d{5..2}", ParseErrorKind::InvalidFaceRange, 2..6),
+            ("d{1,2", ParseErrorKind::UnclosedBrace, 5..5),
+            ("d{1;2}", ParseErrorKind::UnclosedBrace, 3..4),
+            ("d{}", ParseErrorKind::ExpectedNumber, 2..3),
+        ];
+        for (notation, kind, span) in errors {
+            let error = DiceParser::new(notation).parse().unwrap_err();
+            assert!(
+                matches!(&error, DiceError::InvalidNotation { kind: k, span: s, .. } if *k == kind && *s == span),
+                "Unexpected error for '{notation}': {error:?}"
+            );
+        }
+
+        assert_eq!(
+            DiceParser::new("d{1..2000000}").parse(),
+            Err(DiceError::TooManySides {
+                sides: 2_000_000,
+                max: 1_000_000
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_signed_condition_values() {
+        let modifiers = |notation| match DiceParser::new(notation).parse().unwrap() {
+            DiceExpression::Dice { modifiers, .. } => modifiers,
+            other => panic!("Expected dice for '{notation}', got {other:?}"),
+        };
+        let reroll = |condition| Modifier::Reroll {
+            condition,
+            reroll_type: RerollType::Once,
+        };
+        assert_eq!(
+            modifiers("2dF.1r-1"),
+            vec![reroll(Condition::Compare(Comparison::Equal, -1))]
+        );
+        assert_eq!(
+            modifiers("4dFr<=-1"),
+            vec![reroll(Condition::Compare(Comparison::LessOrEqual, -1))]
+        );
+        assert_eq!(
+            modifiers("4dF!>-1"),
+            vec![Modifier::Explode {
+                condition: ExplodeCondition::Matches(Condition::Compare(
+                    Comparison::GreaterThan,
+                    -1
+                )),
+                explode_type: ExplodeType::Standard,
+            }]
+        );
+        assert_eq!(
+            modifiers("4dFr{-1,0}"),
+            vec![reroll(Condition::OneOf(vec![-1, 0]))]
+        );
+        for notation in ["2dF.1r-1", "4dFr<=-1", "4dF!>-1", "4dFr{-1,0}"] {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(DiceParser::new(&expr.to_string()).parse().unwrap(), expr);
+        }
+
+        // Without a comparison, a minus after an explosion still subtracts
+        let expr = DiceParser::new("3d6!-1").parse().unwrap();
+        assert!(matches!(
+            expr,
+            DiceExpression::Binary {
+                op: BinaryOp::Subtract,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_conditions() {
+        let compare = Condition::Compare;
+        let cases = [
+            ("10d10>=8", compare(Comparison::GreaterOrEqual, 8)),
+            ("10d10<=2", compare(Comparison::LessOrEqual, 2)),
+            ("6d6=6", compare(Comparison::Equal, 6)),
+            ("6d6==6", compare(Comparison::Equal, 6)),
+            ("6d6!=1", compare(Comparison::NotEqual, 1)),
+            ("6d6{5,6}", Condition::OneOf(vec![5, 6])),
+            ("6d6{4..6}", Condition::OneOf(vec![4, 5, 6])),
+        ];
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert!(
+                matches!(&expr, DiceExpression::Dice { modifiers, .. } if modifiers == &[Modifier::CountSuccesses { success: expected.clone(), failure: None }]),
+                "Unexpected parse for '{notation}': {expr:?}"
+            );
+        }
+
+        let modifiers = |notation| match DiceParser::new(notation).parse().unwrap() {
+            DiceExpression::Dice { modifiers, .. } => modifiers,
+            other => panic!("Expected dice for '{notation}', got {other:?}"),
+        };
+        let reroll = |condition| Modifier::Reroll {
+            condition,
+            reroll_type: RerollType::Once,
+        };
+        let explode = |condition, explode_type| Modifier::Explode {
+            condition: ExplodeCondition::Matches(condition),
+            explode_type,
+        };
+        assert_eq!(
+            modifiers("4d6r{1,2}"),
+            vec![reroll(Condition::OneOf(vec![1, 2]))]
+        );
+        assert_eq!(
+            modifiers("4d6r=1"),
+            vec![reroll(compare(Comparison::Equal, 1))]
+        );
+        assert_eq!(
+            modifiers("4d6r!=6"),
+            vec![reroll(compare(Comparison::NotEqual, 6))]
+        );
+        assert_eq!(
+            modifiers("3d6!>=5"),
+            vec![explode(
+                compare(Comparison::GreaterOrEqual, 5),
+                ExplodeType::Standard
+            )]
+        );
+        assert_eq!(
+            modifiers("3d6!p{5,6}"),
+            vec![explode(
+                Condition::OneOf(vec![5, 6]),
+                ExplodeType::Penetrating
+            )]
+        );
+        // "!!=" is a standard explosion on anything but a value, "!!!=" a compounding one
+        assert_eq!(
+            modifiers("3d6!!=3"),
+            vec![explode(
+                compare(Comparison::NotEqual, 3),
+                ExplodeType::Standard
+            )]
+        );
+        assert_eq!(
+            modifiers("3d6!!!=3"),
+            vec![explode(
+                compare(Comparison::NotEqual, 3),
+                ExplodeType::Compounding
+            )]
+        );
+        assert_eq!(
+            modifiers("10d10>=8f{1,2}"),
+            vec![Modifier::CountSuccesses {
+                success: compare(Comparison::GreaterOrEqual, 8),
+                failure: Some(Condition::OneOf(vec![1, 2])),
+            }]
+        );
+
+        // After a space, a comparison following an explosion compares the total
+        let expr = DiceParser::new("3d6! > 10").parse().unwrap();
+        assert!(matches!(expr, DiceExpression::Compare { .. }));
+
+        let errors = [
+            ("5d10>=6f<=6", ParseErrorKind::ConflictingConditions, 4..11),
+            ("6d6{5,6}f{6}", ParseErrorKind::ConflictingConditions, 3..12),
+            ("6d6!=1f{1,2}", ParseErrorKind::ConflictingConditions, 3..12),
+            ("4d6r{1,2", ParseErrorKind::UnclosedBrace, 8..8),
+            ("4d6r{3..1}", ParseErrorKind::InvalidFaceRange, 5..9),
+            ("6d6>=", ParseErrorKind::ExpectedNumber, 5..5),
+        ];
+        for (notation, kind, span) in errors {
+            let error = DiceParser::new(notation).parse().unwrap_err();
+            assert!(
+                matches!(&error, DiceError::InvalidNotation { kind: k, span: s, .. } if *k == kind && *s == span),
+                "Unexpected error for '{notation}': {error:?}"
+            );
+        }
+
+        // Conditions that cannot both hold are not conflicting
+        assert!(DiceParser::new("6d6!=1f=1").parse().is_ok());
+        assert!(DiceParser::new("6d6{5,6}f<=1").parse().is_ok());
+    }
+
+    #[test]
+    fn test_parse_selectors() {
+        let cases = [
+            ("4d6kh3", vec![Modifier::KeepHighest(3)]),
+            ("4d6kl", vec![Modifier::KeepLowest(1)]),
+            ("6d6km4", vec![Modifier::KeepMiddle(4)]),
+            ("4d6dh1", vec![Modifier::DropHighest(1)]),
+            (
+                "6d6dh1dl1",
+                vec![Modifier::DropHighest(1), Modifier::DropLowest(1)],
+            ),
+            (
+                "8d6dl2km3kh2",
+                vec![
+                    Modifier::DropLowest(2),
+                    Modifier::KeepMiddle(3),
+                    Modifier::KeepHighest(2),
+                ],
+            ),
+        ];
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert!(
+                matches!(&expr, DiceExpression::Dice { modifiers, .. } if *modifiers == expected),
+                "Unexpected parse for '{notation}': {expr:?}"
+            );
+        }
+
+        let errors = [
+            ("6d6dl2km5", ParseErrorKind::KeepTooMany, 6..9),
+            ("2d6dh1dl1", ParseErrorKind::DropTooMany, 6..9),
+        ];
+        for (notation, kind, span) in errors {
+            let error = DiceParser::new(notation).parse().unwrap_err();
+            assert!(
+                matches!(&error, DiceError::InvalidNotation { kind: k, span: s, .. } if *k == kind && *s == span),
+                "Unexpected error for '{notation}': {error:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_critical_thresholds() {
+        let modifiers = |notation| match DiceParser::new(notation).parse().unwrap() {
+            DiceExpression::Dice { modifiers, .. } => modifiers,
+            other => panic!("Expected dice for '{notation}', got {other:?}"),
+        };
+        assert_eq!(
+            modifiers("1d20cs>19cf1"),
+            vec![
+                Modifier::CriticalSuccess(Some(Condition::Compare(Comparison::GreaterThan, 19))),
+                Modifier::CriticalFailure(Some(Condition::Compare(Comparison::Equal, 1))),
+            ]
+        );
+        assert_eq!(
+            modifiers("1d20cscf"),
+            vec![
+                Modifier::CriticalSuccess(None),
+                Modifier::CriticalFailure(None)
+            ]
+        );
+        // Criticals are marked last, whatever order they are written in
+        assert_eq!(
+            modifiers("2d20cs{19,20}K1"),
+            vec![
+                Modifier::KeepHighest(1),
+                Modifier::CriticalSuccess(Some(Condition::OneOf(vec![19, 20]))),
+            ]
+        );
+        assert_eq!(modifiers("4dFcs"), vec![Modifier::CriticalSuccess(None)]);
+
+        // After a space, a comparison following "cs" compares the total
+        let expr = DiceParser::new("1d20cs >= 15").parse().unwrap();
+        assert!(matches!(expr, DiceExpression::Compare { .. }));
+
+        let error = DiceParser::new("1d20cf1cf2").parse().unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                DiceError::InvalidNotation {
+                    kind: ParseErrorKind::DuplicateModifier,
+                    span,
+                    ..
+                } if *span == (7..10)
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[test]
+    fn test_parse_explosion_types() {
+        let cases = [
+            ("3d6!!", ExplodeCondition::Max, ExplodeType::Compounding),
+            ("3d6!p", ExplodeCondition::Max, ExplodeType::Penetrating),
+            (
+                "3d6!!>4",
+                ExplodeCondition::Matches(Condition::Compare(Comparison::GreaterThan, 4)),
+                ExplodeType::Compounding,
+            ),
+            (
+                "3d6!p5",
+                ExplodeCondition::Matches(Condition::Compare(Comparison::Equal, 5)),
+                ExplodeType::Penetrating,
+            ),
+        ];
+
+        for (notation, condition, explode_type) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(
+                expr,
+                DiceExpression::Dice {
+                    count: 3,
+                    die: DieType::Numbered(6),
+                    modifiers: vec![Modifier::Explode {
+                        condition,
+                        explode_type,
+                    }],
+                },
+                "Unexpected parse for '{notation}'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_unary_minus() {
+        let d = |count, sides| DiceExpression::Dice {
+            count,
+            die: DieType::Numbered(sides),
+            modifiers: vec![],
+        };
+        let negate = |expr| DiceExpression::Negate(Box::new(expr));
+        let binary = |left, op, right| DiceExpression::Binary {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        };
+        let cases = [
+            (
+                "-1d6 + 10",
+                binary(negate(d(1, 6)), BinaryOp::Add, DiceExpression::Constant(10)),
+            ),
+            ("-(2d4)", negate(d(2, 4))),
+            ("-d20", negate(d(1, 20))),
+            ("- 3", negate(DiceExpression::Constant(3))),
+            ("-3", DiceExpression::Constant(-3)),
+            (
+                "2d6 - -3",
+                binary(d(2, 6), BinaryOp::Subtract, DiceExpression::Constant(-3)),
+            ),
+            (
+                "2d6--3",
+                binary(d(2, 6), BinaryOp::Subtract, DiceExpression::Constant(-3)),
+            ),
+            (
+                "2d6 - -1d4",
+                binary(d(2, 6), BinaryOp::Subtract, negate(d(1, 4))),
+            ),
+            (
+                "(2d6)-3",
+                binary(d(2, 6), BinaryOp::Subtract, DiceExpression::Constant(3)),
+            ),
+            // Negation binds tighter than multiplication
+            (
+                "-2d6 * 3",
+                binary(
+                    negate(d(2, 6)),
+                    BinaryOp::Multiply,
+                    DiceExpression::Constant(3),
+                ),
+            ),
+            (
+                "2 * -1d6",
+                binary(
+                    DiceExpression::Constant(2),
+                    BinaryOp::Multiply,
+                    negate(d(1, 6)),
+                ),
+            ),
+            ("--1d6", negate(negate(d(1, 6)))),
+        ];
+
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(expr, expected, "Unexpected parse for '{notation}'");
+        }
+
+        let error = DiceParser::new("2d6 -").parse().unwrap_err();
+        assert_eq!(error.span(), Some(5..5));
+    }
+
+    #[test]
+    fn test_parse_modulo_and_power() {
+        let constant = DiceExpression::Constant;
+        let binary = |left, op, right| DiceExpression::Binary {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        };
+        let negate = |expr| DiceExpression::Negate(Box::new(expr));
+        let cases = [
+            (
+                "7 % 3 * 2",
+                binary(
+                    binary(constant(7), BinaryOp::Modulo, constant(3)),
+                    BinaryOp::Multiply,
+                    constant(2),
+                ),
+            ),
+            (
+                "1 + 2 ^ 3",
+                binary(
+                    constant(1),
+                    BinaryOp::Add,
+                    binary(constant(2), BinaryOp::Power, constant(3)),
+                ),
+            ),
+            (
+                "2 * 3 ** 2",
+                binary(
+                    constant(2),
+                    BinaryOp::Multiply,
+                    binary(constant(3), BinaryOp::Power, constant(2)),
+                ),
+            ),
+            // Powers are right associative and bind tighter than negation
+            (
+                "2^3^2",
+                binary(
+                    constant(2),
+                    BinaryOp::Power,
+                    binary(constant(3), BinaryOp::Power, constant(2)),
+                ),
+            ),
+            (
+                "-3^2",
+                negate(binary(constant(3), BinaryOp::Power, constant(2))),
+            ),
+            ("2^-1", binary(constant(2), BinaryOp::Power, constant(-1))),
+            ("(-3)^2", binary(constant(-3), BinaryOp::Power, constant(2))),
+        ];
+
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(expr, expected, "Unexpected parse for '{notation}'");
+        }
+
+        // '%' after 'd' is still a percentile die
+        let expr = DiceParser::new("d% % 10").parse().unwrap();
+        assert!(matches!(
+            expr,
+            DiceExpression::Binary {
+                op: BinaryOp::Modulo,
+                ..
+            }
+        ));
+        assert_eq!(expr.to_string(), "1d100 % 10");
+    }
+
+    #[test]
+    fn test_parse_comparisons_and_conditionals() {
+        let constant = DiceExpression::Constant;
+        let d20 = || DiceExpression::Dice {
+            count: 1,
+            die: DieType::Numbered(20),
+            modifiers: vec![],
+        };
+        let compare = |left, comparison, right| DiceExpression::Compare {
+            left: Box::new(left),
+            comparison,
+            right: Box::new(right),
+        };
+        let conditional = |condition, then, otherwise| DiceExpression::Conditional {
+            condition: Box::new(condition),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        };
+        let cases = [
+            (
+                "1d20 + 5 >= 15",
+                compare(
+                    DiceExpression::Binary {
+                        left: Box::new(d20()),
+                        op: BinaryOp::Add,
+                        right: Box::new(constant(5)),
+                    },
+                    Comparison::GreaterOrEqual,
+                    constant(15),
+                ),
+            ),
+            (
+                "1d20 > 10",
+                compare(d20(), Comparison::GreaterThan, constant(10)),
+            ),
+            (
+                "1d20 <= 3",
+                compare(d20(), Comparison::LessOrEqual, constant(3)),
+            ),
+            (
+                "1d20 == 20",
+                compare(d20(), Comparison::Equal, constant(20)),
+            ),
+            ("1d20 = 20", compare(d20(), Comparison::Equal, constant(20))),
+            (
+                "1d20 != 1",
+                compare(d20(), Comparison::NotEqual, constant(1)),
+            ),
+            (
+                "3 < 1d20",
+                compare(constant(3), Comparison::LessThan, d20()),
+            ),
+            (
+                "1d20 >= 15 ? 1d20 : 0",
+                conditional(
+                    compare(d20(), Comparison::GreaterOrEqual, constant(15)),
+                    d20(),
+                    constant(0),
+                ),
+            ),
+            // Conditionals nest to the right
+            (
+                "1 ? 2 : 3 ? 4 : 5",
+                conditional(
+                    constant(1),
+                    constant(2),
+                    conditional(constant(3), constant(4), constant(5)),
+                ),
+            ),
+        ];
+
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(expr, expected, "Unexpected parse for '{notation}'");
+        }
+
+        // Without whitespace a comparison after a dice term still counts successes
+        let expr = DiceParser::new("5d10>6").parse().unwrap();
+        assert!(matches!(expr, DiceExpression::Dice { .. }));
+        let expr = DiceParser::new("5d10 > 6").parse().unwrap();
+        assert!(matches!(expr, DiceExpression::Compare { .. }));
+
+        // '!=' is not an explosion
+        let expr = DiceParser::new("1d6 != 3").parse().unwrap();
+        assert_eq!(
+            expr,
+            compare(
+                DiceExpression::Dice {
+                    count: 1,
+                    die: DieType::Numbered(6),
+                    modifiers: vec![],
+                },
+                Comparison::NotEqual,
+                constant(3),
+            )
+        );
+
+        let error = DiceParser::new("1d20 >= 15 ? 2d6").parse().unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                DiceError::InvalidNotation {
+                    kind: ParseErrorKind::IncompleteConditional,
+                    span,
+                    ..
+                } if *span == (16..16)
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[test]
+    fn test_parse_functions() {
+        let d20 = || DiceExpression::Dice {
+            count: 1,
+            die: DieType::Numbered(20),
+            modifiers: vec![],
+        };
+        let expr = DiceParser::new("max(1d20, 1d20) + 5").parse().unwrap();
+        assert_eq!(
+            expr,
+            DiceExpression::Binary {
+                left: Box::new(DiceExpression::Function {
+                    function: Function::Max,
+                    args: vec![d20(), d20()],
+                }),
+                op: BinaryOp::Add,
+                right: Box::new(DiceExpression::Constant(5)),
             }
         );
+
+        let cases = [
+            ("min(1d4-2, 3)", Function::Min, 2),
+            ("abs (1d6 - 1d6)", Function::Abs, 1),
+            ("floor(3d6 / 2)", Function::Floor, 1),
+            ("ceil(1d6/2)", Function::Ceil, 1),
+            ("round(2d6 / 3)", Function::Round, 1),
+            ("clamp(1d20 + 5, 1, 20)", Function::Clamp, 3),
+            ("sum(2d6, 1d8, -1)", Function::Sum, 3),
+            ("sort(4d6K3x6)", Function::Sort, 1),
+            ("max((1d20))", Function::Max, 1),
+        ];
+        for (notation, expected, count) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert!(
+                matches!(&expr, DiceExpression::Function { function, args } if *function == expected && args.len() == count),
+                "Unexpected parse for '{notation}': {expr:?}"
+            );
+        }
+
+        // A lone 'd' is still a die with an implicit count
+        assert_eq!(DiceParser::new("d20").parse().unwrap(), d20());
+    }
+
+    #[test]
+    fn test_parse_function_errors() {
+        let cases = [
+            ("avg(1d6, 1d8)", ParseErrorKind::UnknownFunction, 0..3),
+            ("abs(1, 2)", ParseErrorKind::WrongArgumentCount, 0..9),
+            ("clamp(1d20, 1)", ParseErrorKind::WrongArgumentCount, 0..14),
+            (
+                "max(1d20, 1d20",
+                ParseErrorKind::UnclosedParenthesis,
+                14..14,
+            ),
+            ("max(1d20 1d20)", ParseErrorKind::UnclosedParenthesis, 9..13),
+            ("max()", ParseErrorKind::ExpectedNumber, 4..5),
+        ];
+
+        for (notation, kind, span) in cases {
+            let error = DiceParser::new(notation).parse().unwrap_err();
+            assert!(
+                matches!(&error, DiceError::InvalidNotation { kind: k, span: s, .. } if *k == kind && *s == span),
+                "Unexpected error for '{notation}': {error:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_dynamic_dice() {
+        let d = |count, sides| DiceExpression::Dice {
+            count,
+            die: DieType::Numbered(sides),
+            modifiers: vec![],
+        };
+        let cases = [
+            (
+                "(1d4)d6",
+                DiceExpression::DynamicDice {
+                    count: Box::new(d(1, 4)),
+                    die: DynamicDie::Fixed(DieType::Numbered(6)),
+                    modifiers: vec![],
+                },
+            ),
+            (
+                "2d(1d3*4)",
+                DiceExpression::DynamicDice {
+                    count: Box::new(DiceExpression::Constant(2)),
+                    die: DynamicDie::Rolled(Box::new(DiceExpression::Binary {
+                        left: Box::new(d(1, 3)),
+                        op: BinaryOp::Multiply,
+                        right: Box::new(DiceExpression::Constant(4)),
+                    })),
+                    modifiers: vec![],
+                },
+            ),
+            (
+                "4d6r1K(1d3)",
+                DiceExpression::DynamicDice {
+                    count: Box::new(DiceExpression::Constant(4)),
+                    die: DynamicDie::Fixed(DieType::Numbered(6)),
+                    modifiers: vec![
+                        (
+                            Modifier::Reroll {
+                                condition: Condition::Compare(Comparison::Equal, 1),
+                                reroll_type: RerollType::Once,
+                            },
+                            None,
+                        ),
+                        (Modifier::KeepHighest(0), Some(d(1, 3))),
+                    ],
+                },
+            ),
+            // Parenthesized constants are written-out values
+            (
+                "(3)d(6)K(2)",
+                DiceExpression::Dice {
+                    count: 3,
+                    die: DieType::Numbered(6),
+                    modifiers: vec![Modifier::KeepHighest(2)],
+                },
+            ),
+        ];
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(expr, expected, "Unexpected parse for '{notation}'");
+        }
+
+        // Keep and drop counts after a rolled count are checked when rolled
+        assert!(DiceParser::new("(1d4)d6K5dl2").parse().is_ok());
+        assert!(matches!(
+            DiceParser::new("(1d4)x2").parse(),
+            Err(DiceError::InvalidNotation { .. })
+        ));
+
+        assert_eq!(
+            DiceParser::new("(0)d6").parse(),
+            Err(DiceError::InvalidDiceCount {
+                count: "0".to_string()
+            })
+        );
+        assert_eq!(
+            DiceParser::new("2d(-6)").parse(),
+            Err(DiceError::InvalidDieSize {
+                size: "-6".to_string()
+            })
+        );
+        let errors = [
+            ("4d6K(-1)", ParseErrorKind::InvalidNumber, 4..8),
+            ("4d6K(5)", ParseErrorKind::KeepTooMany, 3..7),
+            ("(1d4)dAbility", ParseErrorKind::ExpectedNumber, 6..13),
+            ("2d(1d6", ParseErrorKind::UnclosedParenthesis, 6..6),
+        ];
+        for (notation, kind, span) in errors {
+            let error = DiceParser::new(notation).parse().unwrap_err();
+            assert!(
+                matches!(&error, DiceError::InvalidNotation { kind: k, span: s, .. } if *k == kind && *s == span),
+                "Unexpected error for '{notation}': {error:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        let d = |count, sides| DiceExpression::Dice {
+            count,
+            die: DieType::Numbered(sides),
+            modifiers: vec![],
+        };
+        let cases = [
+            (
+                "{2d6, 1d8, 1d10}K1",
+                DiceExpression::Group {
+                    expressions: vec![d(2, 6), d(1, 8), d(1, 10)],
+                    modifiers: vec![Modifier::KeepHighest(1)],
+                },
+            ),
+            (
+                "{1d20+5,1d20+3}kh1",
+                DiceExpression::Group {
+                    expressions: vec![
+                        DiceExpression::Binary {
+                            left: Box::new(d(1, 20)),
+                            op: BinaryOp::Add,
+                            right: Box::new(DiceExpression::Constant(5)),
+                        },
+                        DiceExpression::Binary {
+                            left: Box::new(d(1, 20)),
+                            op: BinaryOp::Add,
+                            right: Box::new(DiceExpression::Constant(3)),
+                        },
+                    ],
+                    modifiers: vec![Modifier::KeepHighest(1)],
+                },
+            ),
+            (
+                "{3d6, 3d6}>10",
+                DiceExpression::Group {
+                    expressions: vec![d(3, 6), d(3, 6)],
+                    modifiers: vec![Modifier::CountSuccesses {
+                        success: Condition::Compare(Comparison::GreaterThan, 10),
+                        failure: None,
+                    }],
+                },
+            ),
+            (
+                "{1d20, 1d20}K1x3",
+                DiceExpression::Repeat {
+                    expression: Box::new(DiceExpression::Group {
+                        expressions: vec![d(1, 20), d(1, 20)],
+                        modifiers: vec![Modifier::KeepHighest(1)],
+                    }),
+                    times: 3,
+                },
+            ),
+            (
+                "{1d6}",
+                DiceExpression::Group {
+                    expressions: vec![d(1, 6)],
+                    modifiers: vec![],
+                },
+            ),
+        ];
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(expr, expected, "Unexpected parse for '{notation}'");
+        }
+
+        // A comparison after a space compares the group's total
+        assert!(matches!(
+            DiceParser::new("{1d6, 1d8} > 3").parse(),
+            Ok(DiceExpression::Compare { .. })
+        ));
+
+        let errors = [
+            ("{1d6, 1d8}!", ParseErrorKind::InvalidGroupModifier, 10..11),
+            ("{1d6, 1d8}r1", ParseErrorKind::InvalidGroupModifier, 10..12),
+            (
+                "{1d6,1d8}K(1d2)",
+                ParseErrorKind::InvalidGroupModifier,
+                9..15,
+            ),
+            ("{1d6,1d8}K3", ParseErrorKind::KeepTooMany, 9..11),
+            ("{1d6, 1d8", ParseErrorKind::UnclosedBrace, 9..9),
+            ("{1d6; 1d8}", ParseErrorKind::UnclosedBrace, 4..5),
+        ];
+        for (notation, kind, span) in errors {
+            let error = DiceParser::new(notation).parse().unwrap_err();
+            assert!(
+                matches!(&error, DiceError::InvalidNotation { kind: k, span: s, .. } if *k == kind && *s == span),
+                "Unexpected error for '{notation}': {error:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_repeat_lists() {
+        let stat = || DiceExpression::Dice {
+            count: 4,
+            die: DieType::Numbered(6),
+            modifiers: vec![Modifier::KeepHighest(3)],
+        };
+        let cases = [
+            (
+                "(4d6K3x7)K6",
+                DiceExpression::RepeatList {
+                    expression: Box::new(stat()),
+                    times: 7,
+                    modifiers: vec![Modifier::KeepHighest(6)],
+                },
+            ),
+            (
+                "(4d6K3 x 7)dl1>=12",
+                DiceExpression::RepeatList {
+                    expression: Box::new(stat()),
+                    times: 7,
+                    modifiers: vec![
+                        Modifier::DropLowest(1),
+                        Modifier::CountSuccesses {
+                            success: Condition::Compare(Comparison::GreaterOrEqual, 12),
+                            failure: None,
+                        },
+                    ],
+                },
+            ),
+            (
+                "(4d6K3x7)K6x2",
+                DiceExpression::Repeat {
+                    expression: Box::new(DiceExpression::RepeatList {
+                        expression: Box::new(stat()),
+                        times: 7,
+                        modifiers: vec![Modifier::KeepHighest(6)],
+                    }),
+                    times: 2,
+                },
+            ),
+            // Without modifiers the parentheses hold a plain repeat
+            (
+                "(4d6K3x7)",
+                DiceExpression::Repeat {
+                    expression: Box::new(stat()),
+                    times: 7,
+                },
+            ),
+        ];
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(expr, expected, "Unexpected parse for '{notation}'");
+        }
+
+        // A bare 'd' after the parentheses still rolls a dice count
+        assert!(matches!(
+            DiceParser::new("(1d4x2)d6").parse(),
+            Ok(DiceExpression::DynamicDice { .. })
+        ));
+        assert!(matches!(
+            DiceParser::new("(3d6x6) >= 15").parse(),
+            Ok(DiceExpression::Compare { .. })
+        ));
+
+        let errors = [
+            ("(3d6x6)r1", ParseErrorKind::InvalidGroupModifier, 7..9),
+            ("(3d6x6)K(1d2)", ParseErrorKind::InvalidGroupModifier, 7..13),
+            ("(3d6x6)K7", ParseErrorKind::KeepTooMany, 7..9),
+            ("(3d6)K1", ParseErrorKind::UnexpectedCharacters, 5..7),
+        ];
+        for (notation, kind, span) in errors {
+            let error = DiceParser::new(notation).parse().unwrap_err();
+            assert!(
+                matches!(&error, DiceError::InvalidNotation { kind: k, span: s, .. } if *k == kind && *s == span),
+                "Unexpected error for '{notation}': {error:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_symbolic_dice() {
+        let symbolic = |count, name: &str| DiceExpression::Symbolic {
+            count,
+            name: name.to_string(),
+        };
+        let cases = [
+            ("2dAbility", symbolic(2, "Ability")),
+            ("dBoost", symbolic(1, "Boost")),
+            ("3 d Force", symbolic(3, "Force")),
+            (
+                "2dAbility + 1dProficiency - 1dDifficulty",
+                DiceExpression::Binary {
+                    left: Box::new(DiceExpression::Binary {
+                        left: Box::new(symbolic(2, "Ability")),
+                        op: BinaryOp::Add,
+                        right: Box::new(symbolic(1, "Proficiency")),
+                    }),
+                    op: BinaryOp::Subtract,
+                    right: Box::new(symbolic(1, "Difficulty")),
+                },
+            ),
+        ];
+
+        for (notation, expected) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert_eq!(expr, expected, "Unexpected parse for '{notation}'");
+        }
+
+        // Fate dice followed by a lowercase modifier are not symbol names
+        for notation in ["4dFdl1", "4dFk2", "4dFr1", "4dFx3", "4dFK2"] {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert!(
+                !matches!(expr, DiceExpression::Symbolic { .. }),
+                "'{notation}' parsed as a symbolic die"
+            );
+        }
+
+        // Symbolic dice take no modifiers
+        let error = DiceParser::new("2dBoostK1").parse().unwrap_err();
+        assert_eq!(error.span(), Some(7..9));
+    }
+
+    #[test]
+    fn test_parse_chained_modifiers() {
+        let reroll = |target, reroll_type| Modifier::Reroll {
+            condition: Condition::Compare(Comparison::Equal, target),
+            reroll_type,
+        };
+        let cases = [
+            (
+                "4d6r1K3",
+                vec![reroll(1, RerollType::Once), Modifier::KeepHighest(3)],
+            ),
+            (
+                "5d10!>8>7",
+                vec![
+                    Modifier::Explode {
+                        condition: ExplodeCondition::Matches(Condition::Compare(
+                            Comparison::GreaterThan,
+                            8,
+                        )),
+                        explode_type: ExplodeType::Standard,
+                    },
+                    Modifier::CountSuccesses {
+                        success: Condition::Compare(Comparison::GreaterThan, 7),
+                        failure: None,
+                    },
+                ],
+            ),
+            (
+                "6d6R1X2",
+                vec![reroll(1, RerollType::Continuous), Modifier::DropHighest(2)],
+            ),
+            (
+                "1d20r1r2",
+                vec![reroll(1, RerollType::Once), reroll(2, RerollType::Once)],
+            ),
+            // Modifiers are ordered by pipeline stage, selections as written
+            (
+                "4d6K3 r1",
+                vec![reroll(1, RerollType::Once), Modifier::KeepHighest(3)],
+            ),
+            (
+                "5d6K4dl1",
+                vec![Modifier::KeepHighest(4), Modifier::DropLowest(1)],
+            ),
+        ];
+
+        for (notation, modifiers) in cases {
+            let expr = DiceParser::new(notation).parse().unwrap();
+            assert!(
+                matches!(&expr, DiceExpression::Dice { modifiers: actual, .. } if *actual == modifiers),
+                "Unexpected parse for '{notation}': {expr:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_chained_modifier_errors() {
+        let cases = [
+            ("4d6dl2K3", ParseErrorKind::KeepTooMany, 6..8),
+            ("4d6K2X2", ParseErrorKind::DropTooMany, 5..7),
+            ("4d6!>4!5", ParseErrorKind::DuplicateModifier, 6..8),
+            ("5d10>6<3", ParseErrorKind::DuplicateModifier, 6..8),
+            ("3d6!!!", ParseErrorKind::DuplicateModifier, 5..6),
+            ("3d6!p!", ParseErrorKind::DuplicateModifier, 5..6),
+        ];
+
+        for (notation, kind, span) in cases {
+            let error = DiceParser::new(notation).parse().unwrap_err();
+            assert!(
+                matches!(&error, DiceError::InvalidNotation { kind: k, span: s, .. } if *k == kind && *s == span),
+                "Unexpected error for '{notation}': {error:?}"
+            );
+        }
     }
 
     #[test]
@@ -841,9 +3338,14 @@ mod tests {
             DiceExpression::Repeat { expression, times } => {
                 assert_eq!(times, 4);
                 match *expression {
-                    DiceExpression::Simple { count, sides } => {
+                    DiceExpression::Dice {
+                        count,
+                        die,
+                        modifiers,
+                    } => {
                         assert_eq!(count, 3);
-                        assert_eq!(sides, 6);
+                        assert_eq!(die, DieType::Numbered(6));
+                        assert!(modifiers.is_empty());
                     }
                     _ => panic!("Expected simple dice expression inside repeat"),
                 }
@@ -862,6 +3364,33 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_enforces_custom_limits() {
+        let relaxed = RollLimits::default().with_max_dice_per_term(200);
+        assert!(DiceParser::with_limits("200d6", relaxed).parse().is_ok());
+
+        let strict = RollLimits::default()
+            .with_max_sides(20)
+            .with_max_repeat(6)
+            .with_max_depth(2);
+        assert_eq!(
+            DiceParser::with_limits("1d100", strict).parse(),
+            Err(DiceError::TooManySides {
+                sides: 100,
+                max: 20
+            })
+        );
+        assert_eq!(
+            DiceParser::with_limits("3d6x7", strict).parse(),
+            Err(DiceError::TooManyRepeats { times: 7, max: 6 })
+        );
+        assert!(DiceParser::with_limits("(1d6)", strict).parse().is_ok());
+        assert_eq!(
+            DiceParser::with_limits("((1d6))", strict).parse(),
+            Err(DiceError::ExpressionTooDeep { max: 2 })
+        );
+    }
+
     #[test]
     fn test_parse_error_invalid_notation() {
         let mut parser = DiceParser::new("invalid");
@@ -874,17 +3403,24 @@ mod tests {
         // Test spaces around 'd'
         let mut parser = DiceParser::new("2 d 6");
         let expr = parser.parse().unwrap();
-        assert_eq!(expr, DiceExpression::Simple { count: 2, sides: 6 });
+        assert_eq!(
+            expr,
+            DiceExpression::Dice {
+                count: 2,
+                die: DieType::Numbered(6),
+                modifiers: vec![],
+            }
+        );
 
         // Test spaces in keep notation
         let mut parser = DiceParser::new("4 d 6 K 3");
         let expr = parser.parse().unwrap();
         assert_eq!(
             expr,
-            DiceExpression::KeepHighest {
+            DiceExpression::Dice {
                 count: 4,
-                sides: 6,
-                keep: 3
+                die: DieType::Numbered(6),
+                modifiers: vec![Modifier::KeepHighest(3)],
             }
         );
 
@@ -893,7 +3429,14 @@ mod tests {
         let expr = parser.parse().unwrap();
         match expr {
             DiceExpression::Binary { left, op, right } => {
-                assert_eq!(*left, DiceExpression::Simple { count: 2, sides: 6 });
+                assert_eq!(
+                    *left,
+                    DiceExpression::Dice {
+                        count: 2,
+                        die: DieType::Numbered(6),
+                        modifiers: vec![],
+                    }
+                );
                 assert_eq!(op, BinaryOp::Add);
                 assert_eq!(*right, DiceExpression::Constant(3));
             }
@@ -905,10 +3448,137 @@ mod tests {
         let expr = parser.parse().unwrap();
         assert_eq!(
             expr,
-            DiceExpression::Simple {
+            DiceExpression::Dice {
                 count: 1,
-                sides: 20
+                die: DieType::Numbered(20),
+                modifiers: vec![],
             }
         );
     }
+
+    fn assert_round_trip(input: &str, expected: &str) {
+        let expr = DiceParser::new(input).parse().unwrap();
+        let formatted = expr.to_string();
+        assert_eq!(formatted, expected, "Formatting '{input}'");
+        assert_eq!(
+            DiceParser::new(&formatted).parse().unwrap(),
+            expr,
+            "Re-parsing '{formatted}'"
+        );
+    }
+
+    #[test]
+    fn test_display_round_trips_every_variant() {
+        assert_round_trip("d20", "1d20");
+        assert_round_trip("4 d 6 K 3", "4d6K3");
+        assert_round_trip("3d6k", "3d6k1");
+        assert_round_trip("6d8X", "6d8X1");
+        assert_round_trip("4d6dl", "4d6dl1");
+        assert_round_trip("5d10d3", "5d10dl3");
+        assert_round_trip("3d6!", "3d6!");
+        assert_round_trip("3d6!5", "3d6!5");
+        assert_round_trip("2d10!>8", "2d10!>8");
+        assert_round_trip("2d12!<3", "2d12!<3");
+        assert_round_trip("5d10>6", "5d10>6");
+        assert_round_trip("10d10>6f<3", "10d10>6f<3");
+        assert_round_trip("4d6r1", "4d6r1");
+        assert_round_trip("3d8R<3", "3d8R<3");
+        assert_round_trip("4d6K3x6", "4d6K3x6");
+        assert_round_trip("3d6!x2", "3d6!x2");
+        assert_round_trip("3d6!!", "3d6!!");
+        assert_round_trip("4dF", "4dF");
+        assert_round_trip("dF.2", "1dF");
+        assert_round_trip("4dF.1K2", "4dF.1K2");
+        assert_round_trip("2d%", "2d100");
+        assert_round_trip("d{2..7}", "1d{2..7}");
+        assert_round_trip("3d{1,1,2,3,5,8}K2", "3d{1,1..3,5,8}K2");
+        assert_round_trip("d{-1, 0,0, 1}", "1d{-1,0,0,1}");
+        assert_round_trip("d{1..3,5..9}!", "1d{1..3,5..9}!");
+        assert_round_trip("d{-3..-1}", "1d{-3..-1}");
+        assert_round_trip("d{-6..-4,-2,-1, 0}", "1d{-6..-4,-2..0}");
+        assert_round_trip("d{1,2,4}", "1d{1,2,4}");
+        assert_round_trip("2d10!p>8", "2d10!p>8");
+        assert_round_trip("4d6 !! 5", "4d6!!5");
+        assert_round_trip("10d6>4!!", "10d6>4!!");
+        assert_round_trip("4d6 K3 r1", "4d6r1K3");
+        assert_round_trip("1d20r1r2", "1d20r1r2");
+        assert_round_trip("5d10!>8>7", "5d10!>8>7");
+        assert_round_trip("6d6!>4K2", "6d6!>4K2");
+        assert_round_trip("10d6>4!", "10d6>4!");
+        assert_round_trip("8d6!K5>4f<2", "8d6!K5>4f<2");
+        assert_round_trip("5d6K4d1", "5d6K4dl1");
+        assert_round_trip("max(1d20,1d20)+5", "max(1d20, 1d20) + 5");
+        assert_round_trip("clamp( 1d20 + 5 , 1, 20 )", "clamp(1d20 + 5, 1, 20)");
+        assert_round_trip("ceil(1d6/2)*2", "ceil(1d6 / 2) * 2");
+        assert_round_trip("-1d6+10", "-1d6 + 10");
+        assert_round_trip("-(2d4 + 1) * 2", "-(2d4 + 1) * 2");
+        assert_round_trip("2d6--3", "2d6 - -3");
+        assert_round_trip("- 3", "-(3)");
+        assert_round_trip("--d6", "--1d6");
+        assert_round_trip("-4d6K3x2", "-4d6K3x2");
+        assert_round_trip("d100%10", "1d100 % 10");
+        assert_round_trip("2**3**2", "2 ^ 3 ^ 2");
+        assert_round_trip("(2^3)^2", "(2 ^ 3) ^ 2");
+        assert_round_trip("-3^2", "-3 ^ 2");
+        assert_round_trip("(-3)^2", "(-3) ^ 2");
+        assert_round_trip("(-1d6)^2", "(-1d6) ^ 2");
+        assert_round_trip("2^-1d4", "2 ^ -1d4");
+        assert_round_trip("(1d6 + 1) ^ 2 % 7", "(1d6 + 1) ^ 2 % 7");
+        assert_round_trip("3d6!^2", "3d6! ^ 2");
+        assert_round_trip("1d20+5>=15", "1d20 + 5 >= 15");
+        assert_round_trip("1d20 == 20", "1d20 = 20");
+        assert_round_trip("1d20 >= 15 ? 2d6 : 0", "1d20 >= 15 ? 2d6 : 0");
+        assert_round_trip("(1d20 > 10) + (1d20 > 10)", "(1d20 > 10) + (1d20 > 10)");
+        assert_round_trip("1?2:3?4:5", "1 ? 2 : 3 ? 4 : 5");
+        assert_round_trip("(1 ? 2 : 3) ? 4 : 5", "(1 ? 2 : 3) ? 4 : 5");
+        assert_round_trip("max(1d20 > 10, 1)", "max(1d20 > 10, 1)");
+        assert_round_trip("dBoost", "1dBoost");
+        assert_round_trip("2dAbility-1dDifficulty", "2dAbility - 1dDifficulty");
+        assert_round_trip("10d10>=8f<=2", "10d10>=8f<=2");
+        assert_round_trip("6d6==6", "6d6=6");
+        assert_round_trip("6d6{ 6, 5 }f=1", "6d6{6,5}f=1");
+        assert_round_trip("4d6r=1", "4d6r1");
+        assert_round_trip("4dFr=-1", "4dFr=-1");
+        assert_round_trip("4d6R{1..4}", "4d6R{1..4}");
+        assert_round_trip("3d6!!=3", "3d6!!=3");
+        assert_round_trip("3d6!!!=3", "3d6!!!=3");
+        assert_round_trip("3d6!! =3", "3d6!! = 3");
+        assert_round_trip("3d6!p{1,6}", "3d6!p{1,6}");
+        assert_round_trip("1d20 cs>19 cf1", "1d20cs>19cf1");
+        assert_round_trip("1d20cf=1cs", "1d20cf1cs");
+        assert_round_trip("4d6cf{1,2}K3", "4d6K3cf{1,2}");
+        assert_round_trip("1d20cs + 5", "1d20cs + 5");
+        assert_round_trip("6d6dh1dl1", "6d6X1dl1");
+        assert_round_trip("6d6 km 4", "6d6km4");
+        assert_round_trip("4d6kh3kl2", "4d6K3k2");
+        assert_round_trip("4dFkm2", "4dFkm2");
+        assert_round_trip("(1d4)d6", "(1d4)d6");
+        assert_round_trip("2d( 1d3*4 )", "2d(1d3 * 4)");
+        assert_round_trip("4d6 K (1d3)", "4d6K(1d3)");
+        assert_round_trip("(1d4)d(1d6)kh(1d2)r1", "(1d4)d(1d6)r1K(1d2)");
+        assert_round_trip("(1d4 + 1)dF.1x3", "(1d4 + 1)dF.1x3");
+        assert_round_trip("(3)d(6)K(2)", "3d6K2");
+        assert_round_trip("42", "42");
+    }
+
+    #[test]
+    fn test_display_uses_canonical_spacing_and_minimal_parentheses() {
+        assert_round_trip("2d6+3", "2d6 + 3");
+        assert_round_trip("4d6K3+2d8-1", "4d6K3 + 2d8 - 1");
+        assert_round_trip("(2d6+3)*2", "(2d6 + 3) * 2");
+        assert_round_trip("2d6+(3*2)", "2d6 + 3 * 2");
+        assert_round_trip("2d6-(1d4+1)", "2d6 - (1d4 + 1)");
+        assert_round_trip("(2d6-1d4)+1", "2d6 - 1d4 + 1");
+        assert_round_trip("12//(2*3)", "12 // (2 * 3)");
+        assert_round_trip("((1d20))", "1d20");
+        assert_round_trip("2d6 - -3", "2d6 - -3");
+        assert_round_trip("{2d6,1d8, 1d10}K1", "{2d6, 1d8, 1d10}K1");
+        assert_round_trip("{1d20+5, 1d20+3}kh1", "{1d20 + 5, 1d20 + 3}K1");
+        assert_round_trip("{3d6, 3d6}>10f<5", "{3d6, 3d6}>10f<5");
+        assert_round_trip("{ 1d20 , 1d20 }K1x3", "{1d20, 1d20}K1x3");
+        assert_round_trip("{4d6, 4d6}dl1kh1", "{4d6, 4d6}dl1K1");
+        assert_round_trip("( 4d6K3 x 7 )kh6", "(4d6K3x7)K6");
+        assert_round_trip("(1d6x4)dl1>=4x3", "(1d6x4)dl1>=4x3");
+        assert_round_trip("sort((4d6K3x7)K6)", "sort((4d6K3x7)K6)");
+    }
 }
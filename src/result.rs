@@ -0,0 +1,490 @@
+// Copyright 2025 Ray Krueger
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structured roll results
+//!
+//! A [`RollResult`] mirrors the shape of the [`DiceExpression`](crate::DiceExpression)
+//! it was rolled from and records every die that was rolled, including dice that
+//! were dropped, rerolled or added by explosions.
+
+use std::fmt;
+
+use crate::parser::{BinaryOp, Comparison, Function};
+
+/// Integer type that roll totals are computed in
+///
+/// Implemented for `i32`, the default, and `i64` for expressions whose totals
+/// may not fit in 32 bits. All arithmetic on totals is checked.
+pub trait Total:
+    Copy + Ord + Default + fmt::Debug + fmt::Display + From<i32> + private::Sealed
+{
+    /// Checked addition, `None` on overflow
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    /// Checked subtraction, `None` on overflow
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    /// Checked multiplication, `None` on overflow
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    /// Checked truncating division, `None` on overflow or division by zero
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    /// Checked floor division, `None` on overflow or division by zero
+    fn checked_div_euclid(self, rhs: Self) -> Option<Self>;
+    /// Checked remainder of truncating division, `None` on overflow or division by zero
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    /// Checked non-negative remainder, `None` on overflow or division by zero
+    fn checked_rem_euclid(self, rhs: Self) -> Option<Self>;
+    /// Checked exponentiation, `None` on overflow or a negative exponent
+    fn checked_pow(self, exp: Self) -> Option<Self>;
+    /// Negation that saturates instead of overflowing
+    #[must_use]
+    fn saturating_neg(self) -> Self;
+    /// Conversion to `i32`, `None` if the value does not fit
+    fn to_i32(self) -> Option<i32>;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_total {
+    ($($ty:ty),*) => {$(
+        impl private::Sealed for $ty {}
+
+        impl Total for $ty {
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_mul(self, rhs)
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_div(self, rhs)
+            }
+            fn checked_div_euclid(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_div_euclid(self, rhs)
+            }
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_rem(self, rhs)
+            }
+            fn checked_rem_euclid(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_rem_euclid(self, rhs)
+            }
+            fn checked_pow(self, exp: Self) -> Option<Self> {
+                <$ty>::checked_pow(self, u32::try_from(exp).ok()?)
+            }
+            fn saturating_neg(self) -> Self {
+                <$ty>::saturating_neg(self)
+            }
+            fn to_i32(self) -> Option<i32> {
+                i32::try_from(self).ok()
+            }
+        }
+    )*};
+}
+
+impl_total!(i32, i64);
+
+/// A single die rolled while evaluating an expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+#[allow(clippy::struct_excessive_bools)] // Independent flags, a die can be both success and failure
+pub struct Die {
+    /// The face showing on the die
+    pub face: i32,
+    /// Number of sides on the die
+    pub sides: i32,
+    /// Whether the die counts toward the result (`false` when dropped by keep/drop)
+    pub kept: bool,
+    /// Whether the die was added to the pool by an explosion
+    pub exploded: bool,
+    /// Rolls added into the face by compounding explosions, oldest first
+    pub compounded: Vec<i32>,
+    /// Faces the die showed before it was rerolled, oldest first
+    pub rerolled_from: Vec<i32>,
+    /// Whether the die met the success condition of a success counting roll
+    pub success: bool,
+    /// Whether the die met the failure condition of a success counting roll
+    pub failure: bool,
+    /// Whether the die met the critical success condition ("cs") of its term
+    pub critical_success: bool,
+    /// Whether the die met the critical failure condition ("cf") of its term
+    pub critical_failure: bool,
+}
+
+impl Die {
+    /// Creates a kept die with no other flags set
+    pub(crate) const fn new(face: i32, sides: i32) -> Self {
+        Self {
+            face,
+            sides,
+            kept: true,
+            exploded: false,
+            compounded: Vec::new(),
+            rerolled_from: Vec::new(),
+            success: false,
+            failure: false,
+            critical_success: false,
+            critical_failure: false,
+        }
+    }
+
+    /// Whether the die was dropped by a keep or drop modifier
+    #[must_use]
+    pub const fn is_dropped(&self) -> bool {
+        !self.kept
+    }
+
+    /// Whether the die was rerolled at least once
+    #[must_use]
+    pub fn is_rerolled(&self) -> bool {
+        !self.rerolled_from.is_empty()
+    }
+}
+
+/// One expression of a group, with how the group's modifiers treated its total
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GroupMember<T = i32> {
+    /// The result of the expression
+    pub result: RollResult<T>,
+    /// Whether the total counts toward the group (`false` when dropped by keep/drop)
+    pub kept: bool,
+    /// Whether the total met the success condition of a success counting group
+    pub success: bool,
+    /// Whether the total met the failure condition of a success counting group
+    pub failure: bool,
+}
+
+impl<T> GroupMember<T> {
+    /// Creates a kept member with no other flags set
+    pub(crate) const fn new(result: RollResult<T>) -> Self {
+        Self {
+            result,
+            kept: true,
+            success: false,
+            failure: false,
+        }
+    }
+}
+
+/// Result tree produced by evaluating a dice expression
+///
+/// Each node carries its own total, so callers can show intermediate values as
+/// well as the final result. Totals are `i32` unless the expression was
+/// evaluated with [`Roller::evaluate_i64`](crate::Roller::evaluate_i64).
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{roll_detailed, RollResult};
+///
+/// let result = roll_detailed("4d6K3 + 2").unwrap();
+/// if let RollResult::Binary { left, .. } = &result {
+///     let dropped = left.dice().iter().filter(|die| die.is_dropped()).count();
+///     assert_eq!(dropped, 1);
+/// }
+/// assert_eq!(result.total(), result.values().iter().sum::<i32>());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RollResult<T = i32> {
+    /// Dice rolled for a single dice term (e.g., "3d6", "4d6K3", "2d6!")
+    ///
+    /// Dice dropped by keep/drop modifiers are listed after the kept dice.
+    Dice { dice: Vec<Die>, total: T },
+
+    /// Success counting term (e.g., "5d10>6"); the total is the net number of successes
+    Successes { dice: Vec<Die>, total: T },
+
+    /// Dice term with rolled parts (e.g., "(1d4)d6", "4d6K(1d3)"), with the
+    /// result of each part in the order rolled and of the dice they resolved to
+    DynamicDice {
+        parts: Vec<Self>,
+        dice: Box<Self>,
+        total: T,
+    },
+
+    /// Group of expressions (e.g., "{2d6, 1d8, 1d10}K1"); the total is the sum
+    /// of the kept members' totals
+    Group {
+        members: Vec<GroupMember<T>>,
+        total: T,
+    },
+
+    /// Group counting its members' totals as successes (e.g., "{3d6, 3d6}>10");
+    /// the total is the net number of successes
+    GroupSuccesses {
+        members: Vec<GroupMember<T>>,
+        total: T,
+    },
+
+    /// Repeated roll (e.g., "3d6x6"); the total is the sum of every roll
+    Repeat { rolls: Vec<Self>, total: T },
+
+    /// Binary arithmetic operation, with the unmodified result of each side
+    Binary {
+        left: Box<Self>,
+        op: BinaryOp,
+        right: Box<Self>,
+        total: T,
+    },
+
+    /// Negated expression, with the result of the operand before negation
+    Negate { operand: Box<Self>, total: T },
+
+    /// Comparison of two totals; the total is 1 when it holds and 0 otherwise
+    Compare {
+        left: Box<Self>,
+        comparison: Comparison,
+        right: Box<Self>,
+        total: T,
+    },
+
+    /// Conditional, with the result of its condition and of the branch it rolled
+    Conditional {
+        condition: Box<Self>,
+        branch: Box<Self>,
+        total: T,
+    },
+
+    /// Function call, with the result of each argument
+    Function {
+        function: Function,
+        args: Vec<Self>,
+        total: T,
+    },
+
+    /// Constant value
+    Constant(T),
+}
+
+impl<T: Total> RollResult<T> {
+    /// The final total of this node
+    #[must_use]
+    pub const fn total(&self) -> T {
+        match self {
+            Self::Dice { total, .. }
+            | Self::Successes { total, .. }
+            | Self::DynamicDice { total, .. }
+            | Self::Group { total, .. }
+            | Self::GroupSuccesses { total, .. }
+            | Self::Repeat { total, .. }
+            | Self::Binary { total, .. }
+            | Self::Negate { total, .. }
+            | Self::Compare { total, .. }
+            | Self::Conditional { total, .. }
+            | Self::Function { total, .. }
+            | Self::Constant(total) => *total,
+        }
+    }
+
+    /// Every die rolled within this node, in evaluation order
+    #[must_use]
+    pub fn dice(&self) -> Vec<&Die> {
+        match self {
+            Self::Dice { dice, .. } | Self::Successes { dice, .. } => dice.iter().collect(),
+            Self::Repeat { rolls, .. } | Self::Function { args: rolls, .. } => {
+                rolls.iter().flat_map(Self::dice).collect()
+            }
+            Self::Binary { left, right, .. }
+            | Self::Compare { left, right, .. }
+            | Self::Conditional {
+                condition: left,
+                branch: right,
+                ..
+            } => {
+                let mut dice = left.dice();
+                dice.extend(right.dice());
+                dice
+            }
+            Self::Group { members, .. } | Self::GroupSuccesses { members, .. } => members
+                .iter()
+                .flat_map(|member| member.result.dice())
+                .collect(),
+            Self::DynamicDice { parts, dice, .. } => {
+                let mut rolled: Vec<&Die> = parts.iter().flat_map(Self::dice).collect();
+                rolled.extend(dice.dice());
+                rolled
+            }
+            Self::Negate { operand, .. } => operand.dice(),
+            Self::Constant(_) => Vec::new(),
+        }
+    }
+
+    /// Whether any kept die rolled a critical success
+    #[must_use]
+    pub fn has_critical_success(&self) -> bool {
+        self.dice()
+            .iter()
+            .any(|die| die.kept && die.critical_success)
+    }
+
+    /// Whether any kept die rolled a critical failure
+    #[must_use]
+    pub fn has_critical_failure(&self) -> bool {
+        self.dice()
+            .iter()
+            .any(|die| die.kept && die.critical_failure)
+    }
+
+    /// Whether a comparison held, or `None` if this node is not a comparison
+    #[must_use]
+    pub fn passed(&self) -> Option<bool> {
+        match self {
+            Self::Compare { total, .. } => Some(*total != T::default()),
+            _ => None,
+        }
+    }
+
+    /// Flattens the result into the values returned by [`roll`](crate::roll)
+    ///
+    /// Kept dice and added constants are listed individually, subtracted and
+    /// negated terms are negated, the arguments of `sum`, the branch a
+    /// conditional rolled and the dice of a term with rolled parts are
+    /// flattened the same way, `sort` lists its argument's values highest
+    /// first, groups and repeat lists list the totals of their kept members,
+    /// and multiplication, division, comparisons, other functions, success
+    /// counting and repeats contribute their totals. The values always sum to [`total`](Self::total).
+    ///
+    /// Use [`roll_value`](crate::roll_value) to see what kind of value the expression produced.
+    #[must_use]
+    pub fn values(&self) -> Vec<T> {
+        match self {
+            Self::Dice { dice, .. } => dice
+                .iter()
+                .filter(|die| die.kept)
+                .map(|die| T::from(die.face))
+                .collect(),
+            Self::Negate { operand, .. } => operand
+                .values()
+                .into_iter()
+                .map(Total::saturating_neg)
+                .collect(),
+            Self::Function {
+                function: Function::Sum,
+                args,
+                ..
+            } => args.iter().flat_map(Self::values).collect(),
+            Self::Function {
+                function: Function::Sort,
+                args,
+                ..
+            } => {
+                let mut values: Vec<T> = args.iter().flat_map(Self::values).collect();
+                values.sort_unstable_by(|a, b| b.cmp(a));
+                values
+            }
+            Self::Conditional { branch, .. } => branch.values(),
+            Self::DynamicDice { dice, .. } => dice.values(),
+            Self::Group { members, .. } => members
+                .iter()
+                .filter(|member| member.kept)
+                .map(|member| member.result.total())
+                .collect(),
+            Self::Successes { total, .. }
+            | Self::GroupSuccesses { total, .. }
+            | Self::Compare { total, .. }
+            | Self::Function { total, .. }
+            | Self::Constant(total) => vec![*total],
+            Self::Repeat { rolls, .. } => rolls.iter().map(Self::total).collect(),
+            Self::Binary {
+                left, op, right, ..
+            } => match op {
+                BinaryOp::Add => {
+                    let mut values = left.values();
+                    values.extend(right.values());
+                    values
+                }
+                BinaryOp::Subtract => {
+                    let mut values = left.values();
+                    values.extend(right.values().into_iter().map(Total::saturating_neg));
+                    values
+                }
+                _ => vec![self.total()],
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dice_result(faces: &[i32], sides: i32) -> RollResult {
+        RollResult::Dice {
+            dice: faces.iter().map(|&face| Die::new(face, sides)).collect(),
+            total: faces.iter().sum(),
+        }
+    }
+
+    #[test]
+    fn test_values_skip_dropped_dice() {
+        let mut dropped = Die::new(1, 6);
+        dropped.kept = false;
+        let result = RollResult::Dice {
+            dice: vec![Die::new(6, 6), Die::new(4, 6), dropped],
+            total: 10,
+        };
+
+        assert_eq!(result.values(), vec![6, 4]);
+        assert_eq!(result.dice().len(), 3);
+    }
+
+    #[test]
+    fn test_values_negate_subtracted_terms() {
+        let result = RollResult::Binary {
+            left: Box::new(dice_result(&[3, 5], 6)),
+            op: BinaryOp::Subtract,
+            right: Box::new(RollResult::Constant(2)),
+            total: 6,
+        };
+
+        assert_eq!(result.values(), vec![3, 5, -2]);
+        assert_eq!(result.total(), 6);
+    }
+
+    #[test]
+    fn test_values_collapse_multiplication() {
+        let result = RollResult::Binary {
+            left: Box::new(dice_result(&[3, 5], 6)),
+            op: BinaryOp::Multiply,
+            right: Box::new(RollResult::Constant(2)),
+            total: 16,
+        };
+
+        assert_eq!(result.values(), vec![16]);
+        assert_eq!(result.dice().len(), 2);
+    }
+
+    #[test]
+    fn test_values_sort_highest_first() {
+        let result = RollResult::Function {
+            function: Function::Sort,
+            args: vec![RollResult::Repeat {
+                rolls: vec![
+                    dice_result(&[2], 6),
+                    dice_result(&[5], 6),
+                    dice_result(&[3], 6),
+                ],
+                total: 10,
+            }],
+            total: 10,
+        };
+
+        assert_eq!(result.values(), vec![5, 3, 2]);
+        assert_eq!(result.dice().len(), 3);
+    }
+}
@@ -0,0 +1,598 @@
+// Copyright 2025 Ray Krueger
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Symbolic dice whose faces show symbols instead of numbers
+//!
+//! Narrative systems such as Genesys roll dice marked with successes,
+//! advantages, threats and so on. A [`SymbolRegistry`] names those dice so they
+//! can be written in notation (e.g., `2dAbility + 1dProficiency`) and rolled
+//! into a [`SymbolTally`], with opposed symbols cancelling each other out.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rand::Rng;
+
+use crate::limits::RollLimits;
+use crate::parser::{BinaryOp, DiceExpression, DiceParser};
+use crate::value::Value;
+use crate::DiceError;
+
+/// Signed count of each symbol rolled
+///
+/// Symbols with a count of zero are never stored, so two tallies are equal
+/// exactly when every symbol has the same count.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolTally {
+    counts: BTreeMap<String, i32>,
+}
+
+impl SymbolTally {
+    /// Creates an empty tally
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            counts: BTreeMap::new(),
+        }
+    }
+
+    /// Builds a tally from whitespace-separated symbols (e.g., "success advantage")
+    ///
+    /// A symbol listed more than once is counted once per mention.
+    #[must_use]
+    pub fn parse(symbols: &str) -> Self {
+        let mut tally = Self::new();
+        for symbol in symbols.split_whitespace() {
+            tally.add(symbol, 1);
+        }
+        tally
+    }
+
+    /// Count of `symbol`, zero when it was never rolled
+    #[must_use]
+    pub fn get(&self, symbol: &str) -> i32 {
+        self.counts.get(symbol).copied().unwrap_or_default()
+    }
+
+    /// Every symbol with a non-zero count, in alphabetical order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i32)> + '_ {
+        self.counts
+            .iter()
+            .map(|(symbol, &count)| (symbol.as_str(), count))
+    }
+
+    /// Whether no symbol has a non-zero count
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Adds `count` of `symbol`, saturating instead of overflowing
+    fn add(&mut self, symbol: &str, count: i32) {
+        let total = self.get(symbol).saturating_add(count);
+        if total == 0 {
+            self.counts.remove(symbol);
+        } else {
+            self.counts.insert(symbol.to_string(), total);
+        }
+    }
+
+    /// Adds every count in `other`, negated when `subtract` is set
+    fn merge(&mut self, other: &Self, subtract: bool) {
+        for (symbol, count) in other.iter() {
+            self.add(
+                symbol,
+                if subtract {
+                    count.saturating_neg()
+                } else {
+                    count
+                },
+            );
+        }
+    }
+}
+
+/// Formats the tally as comma-separated counts (e.g., `2 success, 1 threat`)
+impl fmt::Display for SymbolTally {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no symbols");
+        }
+        for (index, (symbol, count)) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{count} {symbol}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A die whose sides show symbols
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::SymbolicDie;
+///
+/// // Each string is one side; a blank string is a blank side
+/// let boost = SymbolicDie::new(["", "", "success", "success advantage", "advantage advantage", "advantage"]);
+/// assert_eq!(boost.sides(), 6);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolicDie {
+    faces: Vec<SymbolTally>,
+}
+
+impl SymbolicDie {
+    /// Creates a die with one side per face, each listing its symbols separated by spaces
+    ///
+    /// # Panics
+    ///
+    /// Panics if `faces` is empty; use [`try_new`](Self::try_new) to handle that instead.
+    #[must_use]
+    pub fn new<I, S>(faces: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::try_new(faces).expect("A symbolic die needs at least one face")
+    }
+
+    /// Creates a die with one side per face, like [`new`](Self::new)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DiceError::InvalidDieSize`] if `faces` is empty.
+    pub fn try_new<I, S>(faces: I) -> Result<Self, DiceError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let faces: Vec<SymbolTally> = faces
+            .into_iter()
+            .map(|face| SymbolTally::parse(face.as_ref()))
+            .collect();
+        if faces.is_empty() {
+            return Err(DiceError::InvalidDieSize {
+                size: "0".to_string(),
+            });
+        }
+        Ok(Self { faces })
+    }
+
+    /// Number of sides on the die
+    #[must_use]
+    pub fn sides(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// The symbols on every side, in the order they were given
+    #[must_use]
+    pub fn faces(&self) -> &[SymbolTally] {
+        &self.faces
+    }
+
+    /// Rolls the die once
+    fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> &SymbolTally {
+        &self.faces[rng.random_range(0..self.faces.len())]
+    }
+}
+
+/// One symbolic die rolled while evaluating an expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SymbolDie {
+    /// Registered name of the die (e.g., "Ability")
+    pub name: String,
+    /// Symbols on the side that came up
+    pub face: SymbolTally,
+    /// Whether the die was subtracted from the roll
+    pub subtracted: bool,
+}
+
+/// Result of rolling symbolic dice
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SymbolRoll {
+    /// Every die rolled, in the order they appear in the expression
+    pub dice: Vec<SymbolDie>,
+    /// Net symbols after opposed symbols have cancelled out
+    pub tally: SymbolTally,
+}
+
+/// Named symbolic dice and the symbol pairs that cancel each other out
+///
+/// Register dice with [`with_die`](Self::with_die), then roll notation that
+/// adds and subtracts them. Die names are a capital letter followed by
+/// lowercase letters (e.g., `Ability`). A name starting with `F` must not
+/// continue with `c`, `d`, `k`, `r` or `x`, which read as a Fate die with a
+/// modifier.
+///
+/// Subtracting a pool subtracts its symbols, so dice that work against the
+/// roller can be registered with the symbols they take away.
+///
+/// # Examples
+///
+/// ```
+/// use rand::SeedableRng;
+/// use rand_chacha::ChaCha8Rng;
+/// use rollpoly::{SymbolRegistry, SymbolicDie};
+///
+/// let registry = SymbolRegistry::new()
+///     .with_die("Ability", SymbolicDie::new(["", "success", "success", "success success",
+///         "advantage", "advantage", "success advantage", "advantage advantage"]))
+///     .with_die("Difficulty", SymbolicDie::new(["", "success", "success success", "advantage",
+///         "advantage", "advantage", "advantage advantage", "success advantage"]))
+///     .with_opposed("success", "failure")
+///     .with_opposed("advantage", "threat");
+///
+/// let roll = registry
+///     .roll_with_rng("2dAbility - 1dDifficulty", &mut ChaCha8Rng::seed_from_u64(3))
+///     .unwrap();
+/// assert_eq!(roll.dice.len(), 3);
+///
+/// // Opposed symbols cancel, so a roll never shows both sides of a pair
+/// assert!(roll.tally.get("success") == 0 || roll.tally.get("failure") == 0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolRegistry {
+    dice: BTreeMap<String, SymbolicDie>,
+    opposed: Vec<(String, String)>,
+    limits: RollLimits,
+}
+
+impl SymbolRegistry {
+    /// Creates an empty registry using the default [`RollLimits`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a die under `name`, replacing any die already registered with it
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` could never be written in notation: it must be a
+    /// capital letter followed by lowercase letters, and a name starting with
+    /// `F` must not continue with `c`, `d`, `k`, `r` or `x` (e.g., "Fright",
+    /// which reads as a Fate die with a reroll). Use
+    /// [`try_with_die`](Self::try_with_die) to handle that instead.
+    #[must_use]
+    pub fn with_die(self, name: &str, die: SymbolicDie) -> Self {
+        match self.try_with_die(name, die) {
+            Ok(registry) => registry,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Registers a die under `name`, like [`with_die`](Self::with_die)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DiceError::InvalidDieName`] if `name` could never be written
+    /// in notation.
+    pub fn try_with_die(mut self, name: &str, die: SymbolicDie) -> Result<Self, DiceError> {
+        if !DiceParser::is_symbol_name(name) {
+            return Err(DiceError::InvalidDieName {
+                name: name.to_string(),
+            });
+        }
+        self.dice.insert(name.to_string(), die);
+        Ok(self)
+    }
+
+    /// Declares that each `negative` symbol cancels one `positive` symbol
+    ///
+    /// After cancelling, a roll reports whichever of the two is left over, so
+    /// a negative count of `positive` is reported as `negative` instead.
+    #[must_use]
+    pub fn with_opposed(mut self, positive: &str, negative: &str) -> Self {
+        self.opposed
+            .push((positive.to_string(), negative.to_string()));
+        self
+    }
+
+    /// Sets the limits applied when parsing and rolling
+    #[must_use]
+    pub const fn with_limits(mut self, limits: RollLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// The die registered under `name`, if any
+    #[must_use]
+    pub fn die(&self, name: &str) -> Option<&SymbolicDie> {
+        self.dice.get(name)
+    }
+
+    /// Parses and rolls symbolic dice notation with the thread-local RNG
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the notation is invalid, names an unregistered die,
+    /// or does anything other than add and subtract symbolic dice.
+    pub fn roll(&self, notation: &str) -> Result<SymbolRoll, DiceError> {
+        self.roll_with_rng(notation, &mut rand::rng())
+    }
+
+    /// Parses and rolls symbolic dice notation with the given RNG
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`roll`](Self::roll).
+    pub fn roll_with_rng<R: Rng + ?Sized>(
+        &self,
+        notation: &str,
+        rng: &mut R,
+    ) -> Result<SymbolRoll, DiceError> {
+        if notation.trim().is_empty() {
+            return Err(DiceError::EmptyInput);
+        }
+        let expr = DiceParser::with_limits(notation, self.limits).parse()?;
+        self.evaluate_with_rng(&expr, rng)
+    }
+
+    /// Parses and rolls symbolic dice notation to its [`Value::Symbols`] with the thread-local RNG
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`roll`](Self::roll).
+    pub fn roll_value(&self, notation: &str) -> Result<Value, DiceError> {
+        self.roll_value_with_rng(notation, &mut rand::rng())
+    }
+
+    /// Parses and rolls symbolic dice notation to its [`Value::Symbols`] with the given RNG
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`roll`](Self::roll).
+    pub fn roll_value_with_rng<R: Rng + ?Sized>(
+        &self,
+        notation: &str,
+        rng: &mut R,
+    ) -> Result<Value, DiceError> {
+        self.roll_with_rng(notation, rng)
+            .map(|roll| Value::Symbols(roll.tally))
+    }
+
+    /// Rolls a parsed expression of symbolic dice with the given RNG
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the expression exceeds the limits, names an
+    /// unregistered die, or does anything other than add and subtract
+    /// symbolic dice.
+    pub fn evaluate_with_rng<R: Rng + ?Sized>(
+        &self,
+        expr: &DiceExpression,
+        rng: &mut R,
+    ) -> Result<SymbolRoll, DiceError> {
+        self.limits.check(expr)?;
+
+        let mut dice = Vec::new();
+        self.roll_node(expr, false, &mut dice, rng)?;
+
+        let mut tally = SymbolTally::new();
+        for die in &dice {
+            tally.merge(&die.face, die.subtracted);
+        }
+
+        Ok(SymbolRoll {
+            dice,
+            tally: self.cancel(tally),
+        })
+    }
+
+    /// Rolls the dice of one node, flipping `subtract` under a subtraction
+    fn roll_node<R: Rng + ?Sized>(
+        &self,
+        expr: &DiceExpression,
+        subtract: bool,
+        dice: &mut Vec<SymbolDie>,
+        rng: &mut R,
+    ) -> Result<(), DiceError> {
+        match expr {
+            DiceExpression::Symbolic { count, name } => {
+                let symbolic_die = self
+                    .die(name)
+                    .ok_or_else(|| DiceError::UnknownDie { name: name.clone() })?;
+                for _ in 0..*count {
+                    dice.push(SymbolDie {
+                        name: name.clone(),
+                        face: symbolic_die.roll(rng).clone(),
+                        subtracted: subtract,
+                    });
+                }
+                Ok(())
+            }
+            DiceExpression::Binary {
+                left,
+                op: op @ (BinaryOp::Add | BinaryOp::Subtract),
+                right,
+            } => {
+                self.roll_node(left, subtract, dice, rng)?;
+                let subtract_right = subtract != (*op == BinaryOp::Subtract);
+                self.roll_node(right, subtract_right, dice, rng)
+            }
+            DiceExpression::Negate(operand) => self.roll_node(operand, !subtract, dice, rng),
+            _ => Err(DiceError::InvalidSymbolicExpression),
+        }
+    }
+
+    /// Cancels each opposed pair, keeping only the side that is left over
+    fn cancel(&self, mut tally: SymbolTally) -> SymbolTally {
+        for (positive, negative) in &self.opposed {
+            let net = tally.get(positive).saturating_sub(tally.get(negative));
+            tally.counts.remove(positive);
+            tally.counts.remove(negative);
+            if net > 0 {
+                tally.add(positive, net);
+            } else if net < 0 {
+                tally.add(negative, net.saturating_neg());
+            }
+        }
+        tally
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn registry() -> SymbolRegistry {
+        SymbolRegistry::new()
+            .with_die("Hit", SymbolicDie::new(["success"]))
+            .with_die("Miss", SymbolicDie::new(["failure threat"]))
+            .with_die("Edge", SymbolicDie::new(["advantage advantage"]))
+            .with_opposed("success", "failure")
+            .with_opposed("advantage", "threat")
+    }
+
+    fn roll(notation: &str) -> Result<SymbolRoll, DiceError> {
+        registry().roll_with_rng(notation, &mut ChaCha8Rng::seed_from_u64(1))
+    }
+
+    #[test]
+    fn test_tally_parse_counts_repeated_symbols() {
+        let tally = SymbolTally::parse("success  advantage success");
+        assert_eq!(tally.get("success"), 2);
+        assert_eq!(tally.get("advantage"), 1);
+        assert_eq!(tally.get("threat"), 0);
+        assert_eq!(tally.to_string(), "1 advantage, 2 success");
+        assert_eq!(SymbolTally::parse("").to_string(), "no symbols");
+    }
+
+    #[test]
+    fn test_roll_cancels_opposed_symbols() {
+        let roll = roll("3dHit + 1dMiss + 1dEdge").unwrap();
+        assert_eq!(roll.dice.len(), 5);
+        assert_eq!(roll.tally, SymbolTally::parse("success success advantage"));
+
+        let roll = registry()
+            .roll_with_rng("1dHit + 2dMiss", &mut ChaCha8Rng::seed_from_u64(1))
+            .unwrap();
+        assert_eq!(roll.tally, SymbolTally::parse("failure threat threat"));
+    }
+
+    #[test]
+    fn test_roll_subtracts_symbols() {
+        let roll = roll("2dHit - 1dHit - (1dEdge - 2dEdge)").unwrap();
+        assert_eq!(
+            roll.tally,
+            SymbolTally::parse("success advantage advantage")
+        );
+        assert_eq!(
+            roll.dice
+                .iter()
+                .map(|die| die.subtracted)
+                .collect::<Vec<_>>(),
+            vec![false, false, true, true, false, false]
+        );
+
+        let negated = registry()
+            .roll_with_rng("-1dHit + 2dEdge", &mut ChaCha8Rng::seed_from_u64(1))
+            .unwrap();
+        assert_eq!(
+            negated.tally,
+            SymbolTally::parse("failure advantage advantage advantage advantage")
+        );
+    }
+
+    #[test]
+    fn test_roll_picks_faces_from_the_die() {
+        let registry = SymbolRegistry::new().with_die("Coin", SymbolicDie::new(["heads", "tails"]));
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let roll = registry.roll_with_rng("20dCoin", &mut rng).unwrap();
+
+        let heads = roll.tally.get("heads");
+        assert_eq!(heads + roll.tally.get("tails"), 20);
+        assert!(heads > 0 && heads < 20);
+    }
+
+    #[test]
+    fn test_with_die_accepts_names_the_parser_reads() {
+        let die = || SymbolicDie::new(["success"]);
+        let registry = SymbolRegistry::new()
+            .with_die("A", die())
+            .with_die("Force", die())
+            .with_die("Fate", die());
+
+        assert!(registry.die("Force").is_some());
+        assert_eq!(
+            registry
+                .roll_with_rng("1dA + 1dForce + 1dFate", &mut ChaCha8Rng::seed_from_u64(1))
+                .unwrap()
+                .tally
+                .get("success"),
+            3
+        );
+    }
+
+    #[test]
+    fn test_with_die_rejects_names_the_parser_cannot_read() {
+        for name in [
+            "", "ability", "Ability2", "AbiLity", "F", "Fright", "Fdie", "Fx",
+        ] {
+            let result = std::panic::catch_unwind(|| {
+                SymbolRegistry::new().with_die(name, SymbolicDie::new(["success"]))
+            });
+            assert!(result.is_err(), "'{name}' should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_try_constructors_report_invalid_dice() {
+        let registry = SymbolRegistry::new()
+            .try_with_die("Force", SymbolicDie::try_new(["success"]).unwrap())
+            .unwrap();
+        assert!(registry.die("Force").is_some());
+
+        assert_eq!(
+            SymbolRegistry::new()
+                .try_with_die("Fright", SymbolicDie::new(["success"]))
+                .unwrap_err(),
+            DiceError::InvalidDieName {
+                name: "Fright".to_string()
+            }
+        );
+        assert_eq!(
+            SymbolicDie::try_new(Vec::<&str>::new()),
+            Err(DiceError::InvalidDieSize {
+                size: "0".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_roll_errors() {
+        assert_eq!(
+            roll("1dHit + 1dUnknown"),
+            Err(DiceError::UnknownDie {
+                name: "Unknown".to_string()
+            })
+        );
+        assert_eq!(roll("2dHit * 2"), Err(DiceError::InvalidSymbolicExpression));
+        assert_eq!(
+            roll("1dHit + 1d6"),
+            Err(DiceError::InvalidSymbolicExpression)
+        );
+        assert_eq!(
+            roll("30dHit"),
+            Err(DiceError::TooManyDice { count: 30, max: 25 })
+        );
+    }
+}
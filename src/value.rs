@@ -0,0 +1,234 @@
+// Copyright 2025 Ray Krueger
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed values of evaluated expressions
+//!
+//! [`RollResult::values`](crate::RollResult::values) flattens every result
+//! into one list of numbers, whose meaning depends on the operators used. A
+//! [`Value`] instead says what kind of thing an expression produced, and
+//! converts between kinds with a fixed set of coercions. The evaluator
+//! computes each node's value from its children's, coercing them where an
+//! operator needs a number, a condition or a list.
+
+use std::cmp::Reverse;
+
+use crate::result::{Die, Total};
+use crate::symbols::SymbolTally;
+use crate::DiceError;
+
+/// What an expression produced, as returned by [`roll_value`](crate::roll_value)
+///
+/// Arithmetic always produces a [`Scalar`](Self::Scalar), so `2d6 * 2 + 3`
+/// and `(2d6 + 3) * 2` have the same shape; [`roll_detailed`](crate::roll_detailed)
+/// still records how each scalar was reached.
+///
+/// Operators coerce their operands: arithmetic, comparisons and most
+/// functions use [`to_total`](Self::to_total), a conditional's condition uses
+/// [`to_bool`](Self::to_bool), and `max`, `min` and `sum` work on the items
+/// of [`into_list`](Self::into_list). A conditional gives the value of the branch
+/// it rolled, `sort` its argument's value ordered highest first, and symbolic
+/// dice rolled through a [`SymbolRegistry`](crate::SymbolRegistry) give
+/// [`Symbols`](Self::Symbols).
+///
+/// # Coercions
+///
+/// | Value     | [`to_total`](Self::to_total) | [`to_bool`](Self::to_bool) | [`into_list`](Self::into_list) |
+/// |-----------|------------------------------|----------------------------|--------------------------------|
+/// | `Scalar`  | the number                   | non-zero                   | a list of one                  |
+/// | `Pool`    | sum of the kept dice         | total is non-zero          | one scalar per kept die        |
+/// | `List`    | sum of the items' totals     | total is non-zero          | the items                      |
+/// | `Boolean` | 1 or 0                       | itself                     | a list of one                  |
+/// | `Symbols` | error                        | error                      | a list of one                  |
+///
+/// # Examples
+///
+/// ```
+/// use rollpoly::{roll_value, Value};
+///
+/// let stats = roll_value("4d6K3x6").unwrap();
+/// assert!(matches!(&stats, Value::List(rolls) if rolls.len() == 6));
+///
+/// let attack = roll_value("1d20 + 5 >= 15").unwrap();
+/// assert!(matches!(attack, Value::Boolean(_)));
+///
+/// let damage = roll_value("(2d6 + 3) * 2").unwrap();
+/// assert!(matches!(damage, Value::Scalar(_)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Value<T = i32> {
+    /// A single number: constants, arithmetic, success counts and most functions
+    Scalar(T),
+    /// The dice of one term, dropped dice included (e.g., "4d6K3")
+    Pool(Vec<Die>),
+    /// One value per roll of a repeat or kept member of a group (e.g., "3d6x6")
+    ///
+    /// Unlike a pool, which keeps its dropped dice flagged, a list leaves out
+    /// the members a group drops; [`roll_detailed`](crate::roll_detailed)
+    /// still records them.
+    List(Vec<Self>),
+    /// Whether a comparison held (e.g., "1d20 >= 15")
+    Boolean(bool),
+    /// Symbols rolled by symbolic dice, which have no number
+    Symbols(SymbolTally),
+}
+
+impl<T: Total> Value<T> {
+    /// Coerces the value to a number
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DiceError::InvalidSymbolicExpression`] for symbols, and
+    /// [`DiceError::Overflow`] if a sum does not fit in `T`.
+    pub fn to_total(&self) -> Result<T, DiceError> {
+        match self {
+            Self::Scalar(total) => Ok(*total),
+            Self::Pool(dice) => dice
+                .iter()
+                .filter(|die| die.kept)
+                .try_fold(T::default(), |sum, die| sum.checked_add(T::from(die.face)))
+                .ok_or(DiceError::Overflow {
+                    operation: "sum of dice",
+                }),
+            Self::List(items) => items.iter().try_fold(T::default(), |sum, item| {
+                sum.checked_add(item.to_total()?)
+                    .ok_or(DiceError::Overflow {
+                        operation: "list total",
+                    })
+            }),
+            Self::Boolean(held) => Ok(T::from(i32::from(*held))),
+            Self::Symbols(_) => Err(DiceError::InvalidSymbolicExpression),
+        }
+    }
+
+    /// Coerces the value to a condition, true when its total is non-zero
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`to_total`](Self::to_total).
+    pub fn to_bool(&self) -> Result<bool, DiceError> {
+        match self {
+            Self::Boolean(held) => Ok(*held),
+            _ => Ok(self.to_total()? != T::default()),
+        }
+    }
+
+    /// Coerces the value to a list of items
+    ///
+    /// A list gives its items and a pool gives one scalar per kept die. Any
+    /// other value is a list of one.
+    #[must_use]
+    pub fn into_list(self) -> Vec<Self> {
+        match self {
+            Self::List(items) => items,
+            Self::Pool(dice) => dice
+                .into_iter()
+                .filter(|die| die.kept)
+                .map(|die| Self::Scalar(T::from(die.face)))
+                .collect(),
+            value => vec![value],
+        }
+    }
+
+    /// The value with its dice or items ordered highest first, as `sort` does
+    pub(crate) fn sorted(self) -> Self {
+        match self {
+            Self::Pool(mut dice) => {
+                dice.sort_by_key(|die| Reverse(die.face));
+                Self::Pool(dice)
+            }
+            Self::List(mut items) => {
+                items.sort_by_key(|item| Reverse(item.to_total().ok()));
+                Self::List(items)
+            }
+            value => value,
+        }
+    }
+}
+
+impl<T> From<SymbolTally> for Value<T> {
+    fn from(tally: SymbolTally) -> Self {
+        Self::Symbols(tally)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(faces: &[i32]) -> Value {
+        Value::Pool(faces.iter().map(|&face| Die::new(face, 6)).collect())
+    }
+
+    #[test]
+    fn test_scalar_coercions() {
+        assert_eq!(Value::Scalar(7).to_total(), Ok(7));
+        assert_eq!(Value::Scalar(0).to_bool(), Ok(false));
+        assert_eq!(Value::Scalar(-2).to_bool(), Ok(true));
+        assert_eq!(Value::Scalar(7).into_list(), vec![Value::Scalar(7)]);
+    }
+
+    #[test]
+    fn test_pool_coercions_count_kept_dice() {
+        let Value::Pool(mut dice) = pool(&[6, 4, 1]) else {
+            unreachable!()
+        };
+        dice[2].kept = false;
+        let value = Value::<i32>::Pool(dice);
+
+        assert_eq!(value.to_total(), Ok(10));
+        assert_eq!(value.to_bool(), Ok(true));
+        assert_eq!(value.into_list(), vec![Value::Scalar(6), Value::Scalar(4)]);
+        assert_eq!(pool(&[]).to_bool(), Ok(false));
+    }
+
+    #[test]
+    fn test_list_coercions_sum_items() {
+        let list = Value::List(vec![Value::Scalar(3), pool(&[2, 5]), Value::Boolean(true)]);
+
+        assert_eq!(list.to_total(), Ok(11));
+        assert_eq!(list.to_bool(), Ok(true));
+        assert_eq!(list.clone().into_list().len(), 3);
+        assert_eq!(
+            list.sorted().into_list(),
+            vec![pool(&[2, 5]), Value::Scalar(3), Value::Boolean(true)]
+        );
+        assert_eq!(
+            Value::List(vec![Value::<i32>::Scalar(i32::MAX), Value::Scalar(1)]).to_total(),
+            Err(DiceError::Overflow {
+                operation: "list total"
+            })
+        );
+    }
+
+    #[test]
+    fn test_boolean_coercions() {
+        assert_eq!(Value::<i32>::Boolean(true).to_total(), Ok(1));
+        assert_eq!(Value::<i32>::Boolean(false).to_total(), Ok(0));
+        assert_eq!(Value::<i32>::Boolean(false).to_bool(), Ok(false));
+        assert_eq!(
+            Value::<i32>::Boolean(true).into_list(),
+            vec![Value::Boolean(true)]
+        );
+    }
+
+    #[test]
+    fn test_symbols_have_no_number() {
+        let value = Value::<i64>::from(SymbolTally::parse("success advantage"));
+
+        assert_eq!(value.to_total(), Err(DiceError::InvalidSymbolicExpression));
+        assert_eq!(value.to_bool(), Err(DiceError::InvalidSymbolicExpression));
+        assert_eq!(value.clone().into_list(), vec![value]);
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright 2025 Ray Krueger
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the rollpoly command line interface
+
+use std::process::Command;
+
+fn rollpoly(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_rollpoly"))
+        .args(args)
+        .output()
+        .expect("rollpoly should run");
+    assert!(output.status.success(), "rollpoly {args:?} failed");
+    String::from_utf8(output.stdout).expect("rollpoly should print UTF-8")
+}
+
+#[test]
+fn test_seed_pins_the_rolls() {
+    // The seed drives a ChaCha8 stream, which does not change between releases
+    assert_eq!(
+        rollpoly(&["--seed", "42", "4d6K3"]),
+        "You rolled a 13 with 4d6K3 using dice [6, 5, 2]\n"
+    );
+    assert_eq!(
+        rollpoly(&["--seed", "42", "-n", "3", "2d20 + 5"]),
+        "Rolling '2d20 + 5' 3 time(s)\n\
+         Roll 1: You rolled a 24 with 2d20 + 5 using dice [5, 14, 5]\n\
+         Roll 2: You rolled a 28 with 2d20 + 5 using dice [3, 20, 5]\n\
+         Roll 3: You rolled a 30 with 2d20 + 5 using dice [16, 9, 5]\n"
+    );
+}
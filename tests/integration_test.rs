@@ -17,7 +17,16 @@
 //! These tests verify the public API functionality and ensure that
 //! the library works correctly when used as an external dependency.
 
-use rollpoly::{roll, DiceError};
+#![allow(clippy::uninlined_format_args, clippy::manual_range_contains)]
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rollpoly::{
+    distribution, evaluate, evaluate_detailed_with_rng, evaluate_value_with_rng, parse, roll,
+    roll_detailed, roll_detailed_with_rng, roll_value_with_rng, roll_with_rng, Comparison,
+    Condition, DiceError, DiceExpression, DieType, DynamicDie, Function, GroupMember, Modifier,
+    ParseErrorKind, RerollType, RollLimits, RollResult, Roller, SymbolRegistry, SymbolicDie, Value,
+};
 
 #[test]
 fn test_public_api_basic_dice_rolling() {
@@ -144,3 +153,587 @@ fn test_edge_case_die_sizes() {
         assert!(result[0] >= 1 && result[0] <= die_size);
     }
 }
+
+#[test]
+fn test_public_api_parse_once_evaluate_many() {
+    // Validate notation once, store the AST and roll it repeatedly
+    let expression = parse("2d20K1 + 5").expect("Valid notation should parse");
+    assert!(matches!(expression, DiceExpression::Binary { .. }));
+
+    for _ in 0..20 {
+        let results = evaluate(&expression).expect("Stored expression should evaluate");
+        assert_eq!(results.len(), 2);
+        assert!((1..=20).contains(&results[0]));
+        assert_eq!(results[1], 5);
+    }
+}
+
+#[test]
+fn test_public_api_seeded_rolls_replay_identically() {
+    // Replaying a session with the same seed must produce the same dice
+    let session = ["1d20 + 5", "2d6 + 3", "4d6K3x6", "8d6"];
+
+    let mut original = ChaCha8Rng::seed_from_u64(99);
+    let mut replay = ChaCha8Rng::seed_from_u64(99);
+
+    for notation in session {
+        assert_eq!(
+            roll_with_rng(notation, &mut original).expect("Valid notation should work"),
+            roll_with_rng(notation, &mut replay).expect("Valid notation should work"),
+            "Replayed roll of '{notation}' should match"
+        );
+    }
+}
+
+#[test]
+fn test_public_api_detailed_result_reports_provenance() {
+    let result = roll_detailed("4d6K3 - 2").expect("Valid notation should work");
+
+    match &result {
+        RollResult::Binary { left, right, .. } => {
+            let dice = left.dice();
+            assert_eq!(dice.len(), 4);
+            assert_eq!(dice.iter().filter(|die| die.is_dropped()).count(), 1);
+            assert!(dice.iter().all(|die| die.sides == 6));
+            assert_eq!(**right, RollResult::Constant(2));
+        }
+        _ => panic!("Expected binary result"),
+    }
+
+    assert_eq!(result.total(), result.values().iter().sum::<i32>());
+}
+
+#[test]
+fn test_public_api_exact_distribution() {
+    let expression = parse("2d20K1").expect("Valid notation should parse");
+    let advantage = distribution(&expression).expect("Keep highest has an exact distribution");
+
+    // Chance of rolling at least 15 with advantage: 1 - (14/20)^2
+    assert!((advantage.probability_at_least(15) - 0.51).abs() < 1e-9);
+    assert_eq!(advantage.max(), 20);
+
+    let exploding = parse("2d6!").expect("Valid notation should parse");
+    assert!(distribution(&exploding).is_none());
+}
+
+#[test]
+fn test_public_api_custom_roll_limits() {
+    let strict = Roller::new(
+        RollLimits::default()
+            .with_max_dice_per_term(10)
+            .with_max_explosions(0),
+    );
+    let mut rng = ChaCha8Rng::seed_from_u64(17);
+
+    let error = strict.parse("20d6").unwrap_err();
+    assert_eq!(error, DiceError::TooManyDice { count: 20, max: 10 });
+    assert!(error.to_string().contains("maximum allowed is 10"));
+
+    // Explosions are capped rather than rejected
+    let result = strict.roll("10d2!", &mut rng).unwrap();
+    assert_eq!(result.dice().len(), 10);
+
+    let relaxed = Roller::new(RollLimits::default().with_max_dice_per_term(500));
+    assert_eq!(relaxed.roll("500d6", &mut rng).unwrap().dice().len(), 500);
+}
+
+#[test]
+fn test_public_api_parse_error_diagnostics() {
+    let error = parse("2d6 + 1d").unwrap_err();
+
+    match &error {
+        DiceError::InvalidNotation { kind, span, .. } => {
+            assert_eq!(*kind, ParseErrorKind::ExpectedNumber);
+            assert_eq!(*span, 8..8);
+        }
+        other => panic!("Expected InvalidNotation, got: {other:?}"),
+    }
+
+    let rendered = error.render();
+    assert!(rendered.starts_with("error: Expected number"));
+    assert!(rendered.ends_with("  | 2d6 + 1d\n  |         ^"));
+}
+
+#[test]
+fn test_public_api_overflow_is_an_error() {
+    let roller = Roller::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(21);
+
+    let error = roller
+        .roll("25d1000000 * 25d1000000", &mut rng)
+        .unwrap_err();
+    assert_eq!(
+        error,
+        DiceError::Overflow {
+            operation: "multiplication"
+        }
+    );
+    assert_eq!(error.to_string(), "Arithmetic overflow in multiplication");
+
+    let wide = roller
+        .roll_i64("25d1000000 * 25d1000000", &mut rng)
+        .unwrap();
+    assert!(wide.total() > i64::from(i32::MAX));
+}
+
+#[test]
+fn test_public_api_drop_lowest() {
+    let expression = parse("4d6dl1").unwrap();
+    assert_eq!(
+        expression,
+        DiceExpression::Dice {
+            count: 4,
+            die: DieType::Numbered(6),
+            modifiers: vec![Modifier::DropLowest(1)],
+        }
+    );
+
+    let result = roll_detailed("4d6dl1").unwrap();
+    assert_eq!(
+        result.dice().iter().filter(|die| die.is_dropped()).count(),
+        1
+    );
+    assert_eq!(roll("4d6x").unwrap().len(), 3);
+    assert_eq!(roll("3d6x6").unwrap().len(), 6);
+}
+
+#[test]
+fn test_public_api_chained_modifiers() {
+    for notation in ["4d6r1K3", "5d10!>8>7", "6d6R1X2", "1d20r1r2"] {
+        let expression = parse(notation).unwrap();
+        assert_eq!(expression.to_string(), notation);
+        assert!(
+            roll(notation).is_ok(),
+            "Chained notation '{notation}' should roll"
+        );
+    }
+
+    let result = roll_detailed("4d6r1K3").unwrap();
+    assert_eq!(result.dice().len(), 4);
+    assert_eq!(result.values().len(), 3);
+
+    let stats = distribution(&parse("4d6r1K3").unwrap()).unwrap();
+    assert_eq!(stats.min(), 3);
+    assert!(stats.mean() > distribution(&parse("4d6K3").unwrap()).unwrap().mean());
+}
+
+#[test]
+fn test_public_api_compounding_and_penetrating_explosions() {
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+    for _ in 0..20 {
+        let compounding = Roller::default().roll("3d6!!", &mut rng).unwrap();
+        assert_eq!(compounding.dice().len(), 3);
+        assert!(compounding.total() >= 3);
+
+        let penetrating = Roller::default().roll("3d6!p", &mut rng).unwrap();
+        assert!(penetrating.dice().len() >= 3);
+    }
+
+    assert_eq!(parse("2d10!!>8").unwrap().to_string(), "2d10!!>8");
+    assert_eq!(parse("2d10!p>8").unwrap().to_string(), "2d10!p>8");
+    assert!(distribution(&parse("3d6!!").unwrap()).is_none());
+}
+
+#[test]
+fn test_public_api_fate_and_percentile_dice() {
+    let mut rng = ChaCha8Rng::seed_from_u64(12);
+    for _ in 0..20 {
+        let fate = roll_with_rng("4dF", &mut rng).unwrap();
+        assert_eq!(fate.len(), 4);
+        assert!(fate.iter().all(|face| (-1..=1).contains(face)));
+
+        let percentile = roll_with_rng("d%", &mut rng).unwrap();
+        assert!((1..=100).contains(&percentile[0]));
+    }
+
+    assert_eq!(
+        parse("4dF.1").unwrap(),
+        DiceExpression::Dice {
+            count: 4,
+            die: DieType::FateSingle,
+            modifiers: vec![],
+        }
+    );
+    assert_eq!(parse("d%").unwrap().to_string(), "1d100");
+    assert!((distribution(&parse("4dF").unwrap()).unwrap().mean()).abs() < 1e-9);
+}
+
+#[test]
+fn test_public_api_custom_faces() {
+    assert_eq!(
+        parse("2d{-1,0,0,1}").unwrap(),
+        DiceExpression::Dice {
+            count: 2,
+            die: DieType::Custom(vec![-1, 0, 0, 1]),
+            modifiers: vec![],
+        }
+    );
+
+    let mut rng = ChaCha8Rng::seed_from_u64(6);
+    for _ in 0..20 {
+        let rolls = roll_with_rng("4d{2..7}K3", &mut rng).unwrap();
+        assert_eq!(rolls.len(), 3);
+        assert!(rolls.iter().all(|face| (2..=7).contains(face)));
+    }
+
+    let stats = distribution(&parse("d{1,1,2,3,5,8}").unwrap()).unwrap();
+    assert!((stats.mean() - 20.0 / 6.0).abs() < 1e-9);
+    assert!(matches!(
+        parse("d{5..2}"),
+        Err(DiceError::InvalidNotation {
+            kind: ParseErrorKind::InvalidFaceRange,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_public_api_symbolic_dice() {
+    let registry = SymbolRegistry::new()
+        .with_die("Ability", SymbolicDie::new(["success", "advantage"]))
+        .with_die("Proficiency", SymbolicDie::new(["success triumph"]))
+        .with_die("Difficulty", SymbolicDie::new(["success", "advantage"]))
+        .with_opposed("success", "failure")
+        .with_opposed("advantage", "threat");
+
+    let mut rng = ChaCha8Rng::seed_from_u64(14);
+    for _ in 0..20 {
+        let roll = registry
+            .roll_with_rng("2dAbility + 1dProficiency - 1dDifficulty", &mut rng)
+            .unwrap();
+        assert_eq!(roll.dice.len(), 4);
+        assert_eq!(roll.tally.get("triumph"), 1);
+
+        // Three symbols come up and the difficulty die takes one away
+        let net = roll.tally.get("success") - roll.tally.get("failure")
+            + roll.tally.get("advantage")
+            - roll.tally.get("threat");
+        assert_eq!(net, 2);
+        assert!(roll.tally.get("failure") == 0 || roll.tally.get("success") == 0);
+    }
+
+    assert_eq!(
+        roll("2dAbility"),
+        Err(DiceError::SymbolicDice {
+            name: "Ability".to_string()
+        })
+    );
+    assert!(distribution(&parse("2dAbility").unwrap()).is_none());
+}
+
+#[test]
+fn test_public_api_functions() {
+    assert_eq!(
+        parse("max(1, 1d4 - 2)").unwrap().to_string(),
+        "max(1, 1d4 - 2)"
+    );
+    assert!(matches!(
+        parse("clamp(1d20 + 8, 1, 20)").unwrap(),
+        DiceExpression::Function {
+            function: Function::Clamp,
+            ..
+        }
+    ));
+
+    let mut rng = ChaCha8Rng::seed_from_u64(15);
+    for _ in 0..50 {
+        let damage: i32 = roll_with_rng("max(1, 1d4 - 2)", &mut rng)
+            .unwrap()
+            .iter()
+            .sum();
+        assert!((1..=2).contains(&damage));
+
+        let halved: i32 = roll_with_rng("ceil(1d6 / 2)", &mut rng)
+            .unwrap()
+            .iter()
+            .sum();
+        assert!((1..=3).contains(&halved));
+    }
+
+    let stats = distribution(&parse("max(1d20, 1d20) + 5").unwrap()).unwrap();
+    assert_eq!(stats.min(), 6);
+    assert!((stats.mean() - 18.825).abs() < 1e-9);
+    assert!(matches!(
+        parse("avg(1d6, 1d8)"),
+        Err(DiceError::InvalidNotation {
+            kind: ParseErrorKind::UnknownFunction,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_public_api_unary_minus() {
+    assert_eq!(parse("-1d6 + 10").unwrap().to_string(), "-1d6 + 10");
+    assert_eq!(parse("2d6--3").unwrap(), parse("2d6 - -3").unwrap());
+
+    let mut rng = ChaCha8Rng::seed_from_u64(16);
+    for _ in 0..20 {
+        let values = roll_with_rng("-(2d4)", &mut rng).unwrap();
+        assert_eq!(values.len(), 2);
+        assert!(values.iter().all(|value| (-4..=-1).contains(value)));
+
+        let total: i32 = roll_with_rng("2d6 - -3", &mut rng).unwrap().iter().sum();
+        assert!((5..=15).contains(&total));
+    }
+}
+
+#[test]
+fn test_public_api_modulo_and_power() {
+    assert_eq!(parse("2**3**2").unwrap().to_string(), "2 ^ 3 ^ 2");
+    assert_eq!(roll("2 ^ 3 ^ 2").unwrap(), vec![512]);
+    assert_eq!(roll("-3 ^ 2").unwrap(), vec![-9]);
+    assert_eq!(roll("-7 % 3").unwrap(), vec![2]);
+    assert_eq!(roll("1d6 % 0"), Err(DiceError::DivisionByZero));
+    assert!(matches!(roll("10 ^ 10"), Err(DiceError::Overflow { .. })));
+
+    let ones = distribution(&parse("d100 % 10").unwrap()).unwrap();
+    assert_eq!(ones.len(), 10);
+    assert!((ones.mean() - 4.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_public_api_comparisons_and_conditionals() {
+    assert_eq!(
+        parse("1d20+5>=15?2d6:0").unwrap().to_string(),
+        "1d20 + 5 >= 15 ? 2d6 : 0"
+    );
+    assert_eq!(roll("3 >= 2").unwrap(), vec![1]);
+    assert_eq!(roll("3 != 3").unwrap(), vec![0]);
+    assert_eq!(roll("1d6 > 6 ? 1 / 0 : 7").unwrap(), vec![7]);
+
+    let attack = roll_detailed("1d20 + 5 >= 15").unwrap();
+    let face = attack.dice()[0].face;
+    assert_eq!(attack.passed(), Some(face + 5 >= 15));
+
+    let result = roll_detailed("1d20 >= 11 ? 2d6 : 0").unwrap();
+    assert_eq!(result.passed(), None);
+    assert!(result.dice().len() == 3 || result.total() == 0);
+
+    // Without a space, '>' after dice still counts successes
+    assert!(matches!(
+        parse("5d10>6").unwrap(),
+        DiceExpression::Dice { .. }
+    ));
+    assert!(matches!(
+        parse("1d20 >= 15 ? 2d6"),
+        Err(DiceError::InvalidNotation {
+            kind: ParseErrorKind::IncompleteConditional,
+            ..
+        })
+    ));
+
+    let hit = distribution(&parse("1d20 + 5 >= 15").unwrap()).unwrap();
+    assert!((hit.probability(1) - 0.55).abs() < 1e-9);
+}
+
+#[test]
+fn test_public_api_full_conditions() {
+    assert_eq!(
+        parse("4d6r{1,2}").unwrap(),
+        DiceExpression::Dice {
+            count: 4,
+            die: DieType::Numbered(6),
+            modifiers: vec![Modifier::Reroll {
+                condition: Condition::OneOf(vec![1, 2]),
+                reroll_type: RerollType::Once,
+            }],
+        }
+    );
+    assert!(matches!(
+        parse("10d10>=8").unwrap(),
+        DiceExpression::Dice { modifiers, .. } if modifiers == [Modifier::CountSuccesses {
+            success: Condition::Compare(Comparison::GreaterOrEqual, 8),
+            failure: None,
+        }]
+    ));
+
+    for _ in 0..20 {
+        let successes = roll("10d10>=8f{1,2}").unwrap()[0];
+        assert!((-10..=10).contains(&successes));
+        let rerolled = roll_detailed("8d6R{1,2}").unwrap();
+        assert!(rerolled.dice().iter().all(|die| die.face >= 3));
+    }
+}
+
+#[test]
+fn test_public_api_critical_thresholds() {
+    let mut rng = ChaCha8Rng::seed_from_u64(20);
+    let mut seen = (false, false);
+    for _ in 0..200 {
+        let expr = parse("1d20cs>19cf1 + 5").unwrap();
+        let result = evaluate_detailed_with_rng(&expr, &mut rng).unwrap();
+        let natural = result.dice()[0].face;
+        assert_eq!(result.has_critical_success(), natural == 20);
+        assert_eq!(result.has_critical_failure(), natural == 1);
+        seen.0 |= natural == 20;
+        seen.1 |= natural == 1;
+    }
+    assert_eq!(seen, (true, true));
+
+    // Without "cs" or "cf" nothing is marked
+    let result = roll_detailed("20d20").unwrap();
+    assert!(!result.has_critical_success() && !result.has_critical_failure());
+    assert_eq!(parse("1d20cf=1cs").unwrap().to_string(), "1d20cf1cs");
+}
+
+#[test]
+fn test_public_api_keep_middle_and_combined_selectors() {
+    assert!(matches!(
+        parse("6d6dh1dl1").unwrap(),
+        DiceExpression::Dice { modifiers, .. }
+            if modifiers == [Modifier::DropHighest(1), Modifier::DropLowest(1)]
+    ));
+
+    let result = roll_detailed("7d6km3").unwrap();
+    assert_eq!(result.dice().len(), 7);
+    assert_eq!(result.dice().iter().filter(|die| die.kept).count(), 3);
+    assert_eq!(
+        result.dice().iter().filter(|die| die.is_dropped()).count(),
+        4
+    );
+
+    // Trimming one die from each end of 6d6 is the same as keeping the middle 4
+    let trimmed = distribution(&parse("6d6dh1dl1").unwrap()).unwrap();
+    let middle = distribution(&parse("6d6km4").unwrap()).unwrap();
+    assert!((trimmed.mean() - 14.0).abs() < 1e-9);
+    assert!((trimmed.standard_deviation() - middle.standard_deviation()).abs() < 1e-9);
+}
+
+#[test]
+fn test_public_api_dynamic_dice() {
+    let expression = parse("(1d4)d(1d3*4)K(1d2)").unwrap();
+    assert!(matches!(
+        &expression,
+        DiceExpression::DynamicDice {
+            die: DynamicDie::Rolled(_),
+            modifiers,
+            ..
+        } if modifiers.len() == 1
+    ));
+    assert_eq!(expression.to_string(), "(1d4)d(1d3 * 4)K(1d2)");
+
+    for seed in 0..10 {
+        let result =
+            roll_detailed_with_rng("(1d4)d6", &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+        let RollResult::DynamicDice { parts, dice, .. } = &result else {
+            panic!("Expected a dynamic dice result, got {result:?}");
+        };
+        assert_eq!(
+            dice.dice().len(),
+            usize::try_from(parts[0].total()).unwrap()
+        );
+        assert_eq!(result.values().iter().sum::<i32>(), result.total());
+    }
+
+    // Rolled values are checked against the limits when they are rolled
+    let roller = Roller::new(RollLimits::default().with_max_dice_per_term(5));
+    assert!(roller.parse("(1d4)d6").is_ok());
+    assert_eq!(
+        roller.roll("(1d1 + 9)d6", &mut ChaCha8Rng::seed_from_u64(1)),
+        Err(DiceError::TooManyDice { count: 10, max: 5 })
+    );
+
+    let stats = distribution(&parse("(1d4)d6").unwrap()).unwrap();
+    assert!((stats.mean() - 8.75).abs() < 1e-9);
+}
+
+#[test]
+fn test_public_api_grouped_pools() {
+    let expression = parse("{2d6, 1d8, 1d10}K1").unwrap();
+    assert!(matches!(
+        &expression,
+        DiceExpression::Group { expressions, modifiers }
+            if expressions.len() == 3 && modifiers == &[Modifier::KeepHighest(1)]
+    ));
+    assert_eq!(
+        parse("{1d20+5,1d20+3}kh1").unwrap().to_string(),
+        "{1d20 + 5, 1d20 + 3}K1"
+    );
+
+    for seed in 0..10 {
+        let result = roll_detailed_with_rng(
+            "{1d20 + 5, 1d20 + 3}kh1",
+            &mut ChaCha8Rng::seed_from_u64(seed),
+        )
+        .unwrap();
+        let RollResult::Group { members, total } = &result else {
+            panic!("Expected a group result, got {result:?}");
+        };
+        let kept: Vec<&GroupMember> = members.iter().filter(|member| member.kept).collect();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].result.total(), *total);
+        assert!(members.iter().all(|member| member.result.total() <= *total));
+        assert_eq!(result.values(), vec![*total]);
+    }
+
+    let result =
+        roll_detailed_with_rng("{3d6, 3d6, 3d6}>10", &mut ChaCha8Rng::seed_from_u64(4)).unwrap();
+    assert!(matches!(result, RollResult::GroupSuccesses { .. }));
+    assert!((0..=3).contains(&result.total()));
+
+    let stats = distribution(&parse("{1d20, 1d20}K1").unwrap()).unwrap();
+    assert!((stats.mean() - 13.825).abs() < 1e-9);
+}
+
+#[test]
+fn test_public_api_repeat_lists() {
+    let expression = parse("(4d6K3x7)K6").unwrap();
+    assert!(matches!(
+        &expression,
+        DiceExpression::RepeatList { times: 7, modifiers, .. }
+            if modifiers == &[Modifier::KeepHighest(6)]
+    ));
+    assert_eq!(expression.to_string(), "(4d6K3x7)K6");
+
+    for seed in 0..10 {
+        let stats =
+            roll_with_rng("sort((4d6K3x7)K6)", &mut ChaCha8Rng::seed_from_u64(seed)).unwrap();
+        assert_eq!(stats.len(), 6);
+        assert!(stats.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(stats.iter().all(|stat| (3..=18).contains(stat)));
+    }
+
+    let result = roll_detailed_with_rng("(3d6x6)>=15", &mut ChaCha8Rng::seed_from_u64(2)).unwrap();
+    let RollResult::GroupSuccesses { members, total } = &result else {
+        panic!("Expected a success counting group, got {result:?}");
+    };
+    let successes = members.iter().filter(|member| member.success).count();
+    assert_eq!(*total, i32::try_from(successes).unwrap());
+
+    assert!(distribution(&expression).is_some());
+}
+
+#[test]
+fn test_public_api_typed_values() {
+    let mut rng = ChaCha8Rng::seed_from_u64(11);
+    let doubled = roll_value_with_rng("2d6 * 2 + 3", &mut rng).unwrap();
+    let grouped = roll_value_with_rng("(2d6 + 3) * 2", &mut rng).unwrap();
+    assert!(matches!(doubled, Value::Scalar(_)));
+    assert!(matches!(grouped, Value::Scalar(_)));
+
+    // The value and the detailed result come from the same evaluation
+    let pool = roll_value_with_rng("4d6K3", &mut ChaCha8Rng::seed_from_u64(4)).unwrap();
+    let result = roll_detailed_with_rng("4d6K3", &mut ChaCha8Rng::seed_from_u64(4)).unwrap();
+    assert!(matches!(&pool, Value::Pool(dice) if dice.len() == 4));
+    assert_eq!(pool.to_total(), Ok(result.total()));
+    assert_eq!(pool.into_list().len(), 3);
+
+    let stats = roll_value_with_rng("(4d6K3x7)K6", &mut rng).unwrap();
+    assert_eq!(stats.into_list().len(), 6);
+
+    let roller = Roller::default();
+    let expression = roller.parse("1d20 >= 1").unwrap();
+    let hit = roller.evaluate_value(&expression, &mut rng).unwrap();
+    assert_eq!(hit, Value::Boolean(true));
+    assert_eq!(hit.to_total(), Ok(1));
+    assert_eq!(evaluate_value_with_rng(&expression, &mut rng), Ok(hit));
+
+    let registry = SymbolRegistry::new().with_die("Hit", SymbolicDie::new(["success"]));
+    let symbols = registry.roll_value_with_rng("2dHit", &mut rng).unwrap();
+    assert!(matches!(&symbols, Value::Symbols(tally) if tally.get("success") == 2));
+    assert_eq!(
+        symbols.to_total(),
+        Err(DiceError::InvalidSymbolicExpression)
+    );
+}